@@ -0,0 +1,192 @@
+//! The administrative areas a point lies in, resolved as separate features. See
+//! [`Client::admin_hierarchy`](crate::PhotonApiClient::admin_hierarchy).
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::data::filter::PhotonLayer;
+use crate::data::{FeatureType, LatLon, PhotonFeature};
+
+/// The administrative levels, from the coarsest to the finest.
+pub const ADMIN_LAYERS: [PhotonLayer; 5] = [
+    PhotonLayer::Country,
+    PhotonLayer::State,
+    PhotonLayer::County,
+    PhotonLayer::City,
+    PhotonLayer::District,
+];
+
+/// The country, state, county, city and district of a point. Levels that do not exist at the
+/// point, or could not be resolved, are `None`.
+#[derive(Debug, Clone, Default)]
+pub struct AdminHierarchy {
+    pub country: Option<PhotonFeature>,
+    pub state: Option<PhotonFeature>,
+    pub county: Option<PhotonFeature>,
+    pub city: Option<PhotonFeature>,
+    pub district: Option<PhotonFeature>,
+}
+
+impl AdminHierarchy {
+    /// The feature of `layer`, or `None` for layers that are not administrative.
+    pub fn get(&self, layer: PhotonLayer) -> Option<&PhotonFeature> {
+        match layer {
+            PhotonLayer::Country => self.country.as_ref(),
+            PhotonLayer::State => self.state.as_ref(),
+            PhotonLayer::County => self.county.as_ref(),
+            PhotonLayer::City => self.city.as_ref(),
+            PhotonLayer::District => self.district.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The resolved levels, from the coarsest to the finest.
+    pub fn levels(&self) -> Vec<(PhotonLayer, &PhotonFeature)> {
+        ADMIN_LAYERS
+            .iter()
+            .filter_map(|layer| self.get(*layer).map(|feature| (*layer, feature)))
+            .collect()
+    }
+
+    pub(crate) fn set(&mut self, layer: PhotonLayer, feature: Option<PhotonFeature>) {
+        match layer {
+            PhotonLayer::Country => self.country = feature,
+            PhotonLayer::State => self.state = feature,
+            PhotonLayer::County => self.county = feature,
+            PhotonLayer::City => self.city = feature,
+            PhotonLayer::District => self.district = feature,
+            _ => {}
+        }
+    }
+}
+
+type CachedLevels = HashMap<(PhotonLayer, i64, i64), Option<PhotonFeature>>;
+
+/// Caches administrative levels for reuse by nearby points.
+///
+/// The cache divides the world into cells of a fixed size per layer; points in the same cell
+/// share their resolved level. Coarse levels use large cells, so e.g. the country is only looked
+/// up once for a whole region. Points close to a border can be assigned the area of a neighbour
+/// that lies in the same cell, so cells should be small compared to the areas. The cache is
+/// thread-safe and can be shared between threads.
+#[derive(Debug)]
+pub struct AdminCache {
+    cell_sizes: HashMap<PhotonLayer, f64>,
+    levels: Mutex<CachedLevels>,
+}
+
+impl Default for AdminCache {
+    fn default() -> Self {
+        AdminCache {
+            cell_sizes: [
+                (PhotonLayer::Country, 0.1),
+                (PhotonLayer::State, 0.05),
+                (PhotonLayer::County, 0.02),
+                (PhotonLayer::City, 0.01),
+                (PhotonLayer::District, 0.002),
+            ]
+            .into_iter()
+            .collect(),
+            levels: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl AdminCache {
+    /// Construct a new, empty `AdminCache`. Cells are 0.1° for countries, 0.05° for states,
+    /// 0.02° for counties, 0.01° for cities and 0.002° (about 200 meters) for districts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use cells of `degrees` for `layer`. A size of `0` disables caching for the layer.
+    pub fn cell_size(mut self, layer: PhotonLayer, degrees: f64) -> Self {
+        self.cell_sizes.insert(layer, degrees);
+        self
+    }
+
+    /// The cached level of `layer` for the cell of `coords`. `Some(None)` means that the level is
+    /// known not to exist there.
+    pub fn get(&self, layer: PhotonLayer, coords: &LatLon) -> Option<Option<PhotonFeature>> {
+        let key = self.key(layer, coords)?;
+        self.lock().get(&key).cloned()
+    }
+
+    /// Caches `feature` as level `layer` for the cell of `coords`.
+    pub fn insert(&self, layer: PhotonLayer, coords: &LatLon, feature: Option<PhotonFeature>) {
+        if let Some(key) = self.key(layer, coords) {
+            self.lock().insert(key, feature);
+        }
+    }
+
+    /// The number of cached levels.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn key(&self, layer: PhotonLayer, coords: &LatLon) -> Option<(PhotonLayer, i64, i64)> {
+        let size = *self.cell_sizes.get(&layer)?;
+        if size.is_nan() || size <= 0.0 {
+            return None;
+        }
+        let cell = |degrees: f64| (degrees / size).floor() as i64;
+        Some((layer, cell(coords.lat), cell(coords.lon)))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CachedLevels> {
+        // The map is always left in a consistent state, so a poisoned lock can be used.
+        self.levels
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Search radius in kilometers for the reverse search of each level. Photon finds areas by the
+/// distance to their center, which can be far away for large areas.
+pub(crate) fn search_radius(layer: PhotonLayer) -> u64 {
+    match layer {
+        PhotonLayer::Country => 1000,
+        PhotonLayer::State => 300,
+        PhotonLayer::County => 100,
+        PhotonLayer::City => 30,
+        _ => 10,
+    }
+}
+
+/// The name of the area of `layer` that `address` lies in, according to its address fields.
+pub(crate) fn area_name(address: &PhotonFeature, layer: PhotonLayer) -> Option<&str> {
+    let field = match layer {
+        PhotonLayer::Country => &address.country,
+        PhotonLayer::State => &address.state,
+        PhotonLayer::County => &address.county,
+        PhotonLayer::City => &address.city,
+        PhotonLayer::District => &address.district,
+        _ => &None,
+    };
+    let is_area_itself = address.feature_type() == FeatureType::from(layer);
+    field
+        .as_ref()
+        .or_else(|| address.name.as_ref().filter(|_| is_area_itself))
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+}
+
+/// The candidate that is the area `name`. Candidates are sorted by distance, so the closest area
+/// of that name wins.
+pub(crate) fn find_area(candidates: Vec<PhotonFeature>, name: &str) -> Option<PhotonFeature> {
+    let name = name.to_lowercase();
+    candidates.into_iter().find(|candidate| {
+        candidate
+            .name
+            .as_ref()
+            .map_or(false, |candidate| candidate.trim().to_lowercase() == name)
+    })
+}
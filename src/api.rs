@@ -1,25 +1,37 @@
 use std::error::Error;
+use std::io::{BufRead, Write};
 
 use serde::Deserialize;
 use ureq::{Agent, AgentBuilder, Request};
 
+use crate::admin::{self, AdminCache, AdminHierarchy};
+use crate::batch::{self, BatchOptions, BatchResult, Checkpoint};
+use crate::bulk::{self, BulkReverseOptions, BulkReverseResult, BulkReverseStats};
+use crate::data::address::{parse_address, StructuredAddress};
 use crate::data::filter::{ForwardFilter, ReverseFilter};
 use crate::data::json::PhotonFeatureCollection;
+use crate::data::parse::{is_coordinate, parse_coordinates};
+use crate::data::pluscode;
+use crate::data::scoring::{self, MatchQuery};
 use crate::data::{LatLon, PhotonFeature};
-use crate::error::PhotonError;
+use crate::error::{InvalidInputError, PhotonError};
+use crate::fallback::{FallbackMatch, FallbackStrategy};
+use crate::ndjson::{self, NdjsonStats};
+use crate::track::{self, TrackOptions, TrackSegment};
 
 type PhotonResult = Result<Vec<PhotonFeature>, Box<dyn Error>>;
 
 pub struct Client {
     forward_url: String,
     reverse_url: String,
+    structured_url: String,
     client: Agent,
 }
 
 impl Default for Client {
     /// Default Photon client, using https://photon.komoot.io for requests.
     fn default() -> Self {
-        Client::new(&"https://photon.komoot.io")
+        Client::new("https://photon.komoot.io")
     }
 }
 
@@ -35,6 +47,7 @@ impl Client {
         Client {
             forward_url: String::from(base_url) + "/api",
             reverse_url: String::from(base_url) + "/reverse",
+            structured_url: String::from(base_url) + "/structured",
             client: AgentBuilder::new().build(),
         }
     }
@@ -43,9 +56,15 @@ impl Client {
     /// 
     /// Results can be filtered by the optional `filter`. Pass `None` for no filter.
     /// 
+    /// Invalid coordinates or filter options are rejected with an
+    /// [`InvalidInputError`](crate::error::InvalidInputError) before any request is sent.
+    /// 
     /// This function is blocking, so no async features are involved here. It is, however, safe to
     /// call this function in parallel, since the entire API client is thread-safe.
     pub fn forward_search(&self, query: &str, filter: Option<ForwardFilter>) -> PhotonResult {
+        if let Some(filter) = &filter {
+            filter.validate()?;
+        }
         let mut request = self.client.get(&self.forward_url).query("q", query);
 
         if let Some(filter) = filter {
@@ -57,17 +76,82 @@ impl Client {
         self.parse_response(response)
     }
 
+    /// Performs a structured search for the components of `address`, for example the result of
+    /// [`parse_address`](crate::address::parse_address). This needs a Photon server with support
+    /// for the `/structured` endpoint.
+    ///
+    /// The `name` of the address is not sent, and `country` only if it is an ISO 3166-1 alpha-2
+    /// code. Results can be filtered by the optional `filter`. Pass `None` for no filter.
+    ///
+    /// Empty addresses, invalid coordinates or invalid filter options are rejected with an
+    /// [`InvalidInputError`](crate::error::InvalidInputError) before any request is sent.
+    pub fn structured_search(
+        &self,
+        address: &StructuredAddress,
+        filter: Option<ForwardFilter>,
+    ) -> PhotonResult {
+        if let Some(filter) = &filter {
+            filter.validate()?;
+        }
+        let country_code = address
+            .country
+            .as_deref()
+            .map(str::trim)
+            .filter(|country| country.len() == 2);
+        let params = [
+            ("countrycode", country_code),
+            ("state", address.state.as_deref()),
+            ("city", address.city.as_deref()),
+            ("postcode", address.postcode.as_deref()),
+            ("street", address.street.as_deref()),
+            ("housenumber", address.house_number.as_deref()),
+        ];
+        let mut request = self.client.get(&self.structured_url);
+        let mut is_empty = true;
+        for (param, value) in params {
+            if let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) {
+                request = request.query(param, value);
+                is_empty = false;
+            }
+        }
+        if is_empty {
+            return Err(Box::new(InvalidInputError::new(
+                "structured search needs at least one address component",
+            )));
+        }
+
+        if let Some(filter) = filter {
+            request = filter.append_to(request);
+        }
+
+        let response = request.call()?.into_json()?;
+
+        self.parse_response(response)
+    }
+
     /// Performs a reverse search for objects at the specified `coords`.
     /// 
+    /// Besides [`LatLon`], grid references ([`Utm`](crate::grid::Utm) and
+    /// [`Mgrs`](crate::grid::Mgrs)) can be passed directly. MGRS references that do not describe a
+    /// position are rejected with an [`InvalidInputError`](crate::error::InvalidInputError).
+    /// 
     /// Results can be filtered by the optional `filter`. Pass `None` for no filter.
     /// 
+    /// Invalid coordinates or filter options are rejected with an
+    /// [`InvalidInputError`](crate::error::InvalidInputError) before any request is sent.
+    /// 
     /// This function is blocking, so no async features are involved here. It is, however, safe to
     /// call this function in parallel, since the entire API client is thread-safe.
-    pub fn reverse_search(
-        &self,
-        coords: LatLon,
-        filter: Option<ReverseFilter>,
-    ) -> PhotonResult {
+    pub fn reverse_search<C>(&self, coords: C, filter: Option<ReverseFilter>) -> PhotonResult
+    where
+        C: TryInto<LatLon>,
+        C::Error: Into<Box<dyn Error>>,
+    {
+        let coords = coords.try_into().map_err(Into::into)?;
+        coords.validate()?;
+        if let Some(filter) = &filter {
+            filter.validate()?;
+        }
         let mut request = self
             .client
             .get(&self.reverse_url)
@@ -83,16 +167,329 @@ impl Client {
         self.parse_response(response)
     }
 
+    /// Resolves the country, state, county, city and district that `coords` lies in as separate
+    /// features.
+    ///
+    /// The names of the areas are taken from the address closest to `coords`. Each area is then
+    /// looked up with a reverse search restricted to its layer, and the closest feature with the
+    /// expected name is used. Pass an [`AdminCache`] to reuse levels for nearby points, which
+    /// saves most requests when resolving many points in the same region.
+    pub fn admin_hierarchy(
+        &self,
+        coords: impl Into<LatLon>,
+        cache: Option<&AdminCache>,
+    ) -> Result<AdminHierarchy, Box<dyn Error>> {
+        let coords = coords.into();
+        coords.validate()?;
+
+        let mut hierarchy = AdminHierarchy::default();
+        let mut address: Option<Option<PhotonFeature>> = None;
+        for layer in admin::ADMIN_LAYERS {
+            if let Some(level) = cache.and_then(|cache| cache.get(layer, &coords)) {
+                hierarchy.set(layer, level);
+                continue;
+            }
+            if address.is_none() {
+                let filter = ReverseFilter::new().limit(1);
+                address = Some(
+                    self.reverse_search(coords, Some(filter))?
+                        .into_iter()
+                        .next(),
+                );
+            }
+            let name = address
+                .as_ref()
+                .and_then(|address| address.as_ref())
+                .and_then(|address| admin::area_name(address, layer))
+                .map(String::from);
+
+            let level = match name {
+                Some(name) => {
+                    let filter = ReverseFilter::new()
+                        .layer(vec![layer])
+                        .radius(admin::search_radius(layer))
+                        .limit(10);
+                    admin::find_area(self.reverse_search(coords, Some(filter))?, &name)
+                }
+                None => None,
+            };
+            if let Some(cache) = cache {
+                cache.insert(layer, &coords, level.clone());
+            }
+            hierarchy.set(layer, level);
+        }
+        Ok(hierarchy)
+    }
+
+    /// Reverse-searches many `points` with one request per grid cell instead of one per point.
+    ///
+    /// Points are snapped to the grid of `options` and every cell is searched once, at its
+    /// center; all points of a cell get the same result. Larger cells mean fewer requests but less
+    /// accurate results, see [`bulk`](crate::bulk). Pass `None` for cells of 50 meters.
+    ///
+    /// Invalid points and failed requests are reported per point, so a single bad point does not
+    /// fail the whole batch. Invalid options are rejected with an
+    /// [`InvalidInputError`](crate::error::InvalidInputError) before any request is sent.
+    pub fn bulk_reverse_search(
+        &self,
+        points: &[LatLon],
+        options: Option<BulkReverseOptions>,
+    ) -> Result<BulkReverseResult, Box<dyn Error>> {
+        let options = options.unwrap_or_default();
+        let filter = options.filter.clone().unwrap_or_default().limit(1);
+        filter.validate()?;
+        options.grid.validate()?;
+
+        let (assignments, centers) = bulk::snap(&options.grid, points);
+        let search = |center: &LatLon| {
+            self.reverse_search(*center, Some(filter.clone()))
+                .map(|features| features.into_iter().next())
+                .map_err(|error| PhotonError::new(&error.to_string()))
+        };
+        let cell_results = batch::map_parallel(&centers, options.threads, search);
+
+        let stats = BulkReverseStats {
+            points: points.len(),
+            cells: centers.len(),
+            invalid_points: assignments.iter().filter(|cell| cell.is_err()).count(),
+            failed_requests: cell_results.iter().filter(|result| result.is_err()).count(),
+        };
+        let points = assignments
+            .into_iter()
+            .map(|cell| cell.map_err(|error| PhotonError::new(&error.to_string())))
+            .collect();
+        Ok(BulkReverseResult {
+            points,
+            cells: cell_results,
+            stats,
+        })
+    }
+
+    /// Annotates a GPS `track` with the streets and cities it passes through.
+    ///
+    /// Only a sample of the points is looked up (see [`track::sample`]), and consecutive samples on
+    /// the same street in the same city are compressed into one segment. Tracks can be read from
+    /// GPX files with [`track::read_gpx`]. Pass `None` for the default options.
+    pub fn annotate_track(
+        &self,
+        track: &[LatLon],
+        options: Option<TrackOptions>,
+    ) -> Result<Vec<TrackSegment>, Box<dyn Error>> {
+        let options = options.unwrap_or_default();
+        options.validate()?;
+        for point in track {
+            point.validate()?;
+        }
+
+        let filter = options.filter.clone().unwrap_or_default().limit(1);
+        let mut samples = Vec::new();
+        for index in track::sample(track, &options) {
+            let feature = self
+                .reverse_search(track[index], Some(filter.clone()))?
+                .into_iter()
+                .next();
+            samples.push((index, feature));
+        }
+        Ok(track::segments(track, &samples))
+    }
+
+    /// Geocodes many `queries`, e.g. the rows of a spreadsheet, with the same filter.
+    ///
+    /// Every query is forward-searched, structured addresses as free text, and the candidate with
+    /// the highest [`scoring`](crate::scoring) confidence is kept. Pass `None` to compare up to 5
+    /// candidates per query on one thread.
+    ///
+    /// Empty queries and failed requests are reported per query, so a single bad row does not fail
+    /// the whole batch. An invalid filter is rejected with an
+    /// [`InvalidInputError`](crate::error::InvalidInputError) before any request is sent.
+    pub fn geocode_batch(
+        &self,
+        queries: &[MatchQuery],
+        options: Option<BatchOptions>,
+    ) -> Result<Vec<BatchResult>, Box<dyn Error>> {
+        let options = options.unwrap_or_default();
+        options.filter.validate()?;
+        Ok(batch::map_parallel(queries, options.threads, |query| {
+            self.batch_match(query, &options.filter)
+        }))
+    }
+
+    /// Geocodes `queries` like [`geocode_batch`](Self::geocode_batch), and records their results
+    /// in `checkpoint`. `first_row` is the row number of the first query within the whole job.
+    ///
+    /// Rows that are already in the checkpoint are taken from it without sending a request, so a
+    /// job that was interrupted can be restarted from its first row. The checkpoint verifies that
+    /// the query of every resumed row is unchanged. Rows must be passed in ascending order, and the
+    /// checkpoint is written to disk before this function returns.
+    pub fn geocode_batch_checkpointed(
+        &self,
+        queries: &[MatchQuery],
+        first_row: usize,
+        checkpoint: &mut Checkpoint,
+        options: Option<BatchOptions>,
+    ) -> Result<Vec<BatchResult>, Box<dyn Error>> {
+        let options = options.unwrap_or_default();
+        options.filter.validate()?;
+
+        let mut results = Vec::with_capacity(queries.len());
+        let mut missing = Vec::new();
+        for (offset, query) in queries.iter().enumerate() {
+            let result = checkpoint.replay(first_row + offset, query)?;
+            if result.is_none() {
+                missing.push(offset);
+            }
+            results.push(result);
+        }
+
+        let searched = batch::map_parallel(&missing, options.threads, |offset| {
+            self.batch_match(&queries[*offset], &options.filter)
+        });
+        for (offset, result) in missing.into_iter().zip(searched) {
+            checkpoint.record(first_row + offset, &queries[offset], &result)?;
+            results[offset] = Some(result);
+        }
+        checkpoint.sync()?;
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    /// Performs a reverse search if `query` is a coordinate (see [`is_coordinate`] for what counts
+    /// as one), and a forward search for `query` otherwise.
+    ///
+    /// For reverse searches, only the options of `filter` that also apply to reverse searches are
+    /// used (see [`ReverseFilter::from`]).
+    pub fn smart_search(&self, query: &str, filter: Option<ForwardFilter>) -> PhotonResult {
+        match parse_coordinates(query) {
+            Ok(coords) if is_coordinate(query) => {
+                self.reverse_search(coords, filter.map(ReverseFilter::from))
+            }
+            _ => self.forward_search(query, filter),
+        }
+    }
+
+    /// Resolves a Plus Code to the center of the area it describes.
+    ///
+    /// Full codes (`8FWH4HPG+R2`) are decoded offline. Short codes need a reference locality, as in
+    /// `4HPG+R2 München`: the locality is looked up with a forward search and the code is recovered
+    /// relative to the first result.
+    pub fn resolve_plus_code(&self, input: &str) -> Result<LatLon, Box<dyn Error>> {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        // The code may be followed by a separator, as in `4HPG+R2, München`.
+        let strip = |word: &str| {
+            word.trim_matches(|c: char| c == ',' || c == ';')
+                .to_string()
+        };
+        let position = words
+            .iter()
+            .position(|word| pluscode::is_valid(&strip(word)))
+            .ok_or_else(|| InvalidInputError::new(&format!("'{}' contains no Plus Code", input)))?;
+        let code = strip(words[position]);
+        let code = code.as_str();
+        if pluscode::is_full(code) {
+            return Ok(LatLon::from_plus_code(code)?);
+        }
+
+        let locality = [&words[..position], &words[position + 1..]]
+            .concat()
+            .join(" ")
+            .trim_matches(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .to_string();
+        if locality.is_empty() {
+            return Err(Box::new(InvalidInputError::new(&format!(
+                "short Plus Code '{}' needs a reference locality",
+                code
+            ))));
+        }
+        let reference = self
+            .forward_search(&locality, Some(ForwardFilter::new().limit(1)))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| PhotonError::new(&format!("locality '{}' not found", locality)))?;
+
+        let full_code = pluscode::recover_nearest(code, &reference.coords)?;
+        Ok(LatLon::from_plus_code(&full_code)?)
+    }
+
+    /// Geocodes the single-line `address` with progressively coarser queries, for example without
+    /// the house number when the full address cannot be found.
+    ///
+    /// The address is split with [`parse_address`](crate::address::parse_address), and the levels
+    /// of `strategy` are searched in order. The first result whose confidence reaches the
+    /// strategy's minimum is returned together with the level that found it. Pass `None` for the
+    /// default strategy (see [`FallbackStrategy::new`]).
+    pub fn geocode_with_fallback(
+        &self,
+        address: &str,
+        strategy: Option<FallbackStrategy>,
+    ) -> Result<Option<FallbackMatch>, Box<dyn Error>> {
+        let strategy = strategy.unwrap_or_default();
+        strategy.filter.validate()?;
+        let address = parse_address(address)?;
+
+        for (index, reduced) in strategy.plan(&address) {
+            let level = &strategy.levels[index];
+            let mut filter = strategy.filter.clone();
+            if !level.layers.is_empty() {
+                filter = filter.layer(level.layers.clone());
+            }
+            let query = MatchQuery::from(&reduced);
+            let accepted = self
+                .forward_search(&reduced.to_string(), Some(filter))?
+                .into_iter()
+                .map(|feature| {
+                    let score = scoring::score(&query, &feature);
+                    (feature, score)
+                })
+                .find(|(_, score)| score.confidence >= strategy.min_confidence);
+            if let Some((feature, score)) = accepted {
+                return Ok(Some(FallbackMatch {
+                    feature,
+                    level: level.clone(),
+                    level_index: index,
+                    score,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Geocodes a stream of newline-delimited JSON queries from `input` and writes one JSON result
+    /// per line to `output`, in the order of the input. See [`ndjson`](crate::ndjson) for the
+    /// format of queries and results.
+    ///
+    /// Queries may override the filter of `options` with their own language, limit, layers,
+    /// bounding box and location bias. Lines are read only as fast as results can be written, so
+    /// memory use does not grow with the length of the stream. Pass `None` to compare up to 5
+    /// candidates per query on one thread.
+    ///
+    /// Invalid lines and failed requests are reported in the result of their line. Only an
+    /// invalid filter in `options` and read or write errors end the stream with an error.
+    pub fn geocode_ndjson<R: BufRead, W: Write + Send>(
+        &self,
+        input: R,
+        output: W,
+        options: Option<BatchOptions>,
+    ) -> Result<NdjsonStats, Box<dyn Error>> {
+        let options = options.unwrap_or_default();
+        options.filter.validate()?;
+        ndjson::geocode_stream(input, output, &options, |query, filter| {
+            self.batch_match(query, filter)
+        })
+    }
+
+    fn batch_match(&self, query: &MatchQuery, filter: &ForwardFilter) -> BatchResult {
+        let text = batch::query_text(query)?;
+        let candidates = self.forward_search(&text, Some(filter.clone()))?;
+        Ok(batch::best_match(query, candidates))
+    }
+
     fn parse_response(&self, response: serde_json::Value) -> PhotonResult {
         let deserialize_result = PhotonFeatureCollection::deserialize(&response);
         match deserialize_result {
-            Ok(features) => {
-                return Ok(features
-                    .features()
-                    .into_iter()
-                    .map(PhotonFeature::from)
-                    .collect());
-            }
+            Ok(features) => Ok(features
+                .features()
+                .into_iter()
+                .map(PhotonFeature::from)
+                .collect()),
             Err(error) => {
                 let message = self.try_parse_error(response);
                 match message {
@@ -119,6 +516,7 @@ fn test_base_url_trailing_slash() {
 
     assert_eq!(client_with.forward_url, client_without.forward_url);
     assert_eq!(client_with.reverse_url, client_without.reverse_url);
+    assert_eq!(client_with.structured_url, client_without.structured_url);
 }
 
 pub trait RequestAppend {
@@ -158,6 +556,14 @@ impl RequestAppend for ForwardFilter {
                 request = request.query("layer", &layer.to_string());
             }
         }
+        if let Some(tags) = self.osm_tag {
+            for tag in tags {
+                request = request.query("osm_tag", &tag);
+            }
+        }
+        if let Some(dedupe) = self.dedupe {
+            request = request.query("dedupe", if dedupe { "1" } else { "0" });
+        }
         if let Some(query) = self.additional_query {
             for (param, value) in query {
                 request = request.query(&param, &value);
@@ -184,6 +590,11 @@ impl RequestAppend for ReverseFilter {
                 request = request.query("layer", &layer.to_string());
             }
         }
+        if let Some(tags) = self.osm_tag {
+            for tag in tags {
+                request = request.query("osm_tag", &tag);
+            }
+        }
         if let Some(query) = self.additional_query {
             for (param, value) in query {
                 request = request.query(&param, &value);
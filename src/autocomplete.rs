@@ -0,0 +1,341 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::api::Client;
+use crate::data::filter::ForwardFilter;
+use crate::data::PhotonFeature;
+use crate::error::PhotonError;
+
+/// Configuration for an [`AutocompleteSession`]. This struct implements a builder pattern, so
+/// options can be easily constructed.
+#[derive(Debug, Clone)]
+pub struct AutocompleteOptions {
+    pub debounce: Duration,
+    pub min_query_length: usize,
+    pub cache_size: usize,
+    pub filter: Option<ForwardFilter>,
+}
+
+impl Default for AutocompleteOptions {
+    fn default() -> Self {
+        AutocompleteOptions {
+            debounce: Duration::from_millis(250),
+            min_query_length: 3,
+            cache_size: 32,
+            filter: None,
+        }
+    }
+}
+
+impl AutocompleteOptions {
+    /// Construct new `AutocompleteOptions` with a debounce of 250 ms, a minimum query length of 3
+    /// characters and a cache of 32 queries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time to wait after the last keystroke before a request is sent.
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Queries with fewer characters (ignoring surrounding whitespace) are not sent to the API.
+    pub fn min_query_length(mut self, length: usize) -> Self {
+        self.min_query_length = length;
+        self
+    }
+
+    /// Number of queries whose results are kept for reuse. `0` disables caching.
+    pub fn cache_size(mut self, size: usize) -> Self {
+        self.cache_size = size;
+        self
+    }
+
+    /// Filter applied to every request of the session.
+    pub fn filter(mut self, filter: ForwardFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+}
+
+/// Results for a single query of an [`AutocompleteSession`].
+#[derive(Debug, Clone)]
+pub struct AutocompleteResult {
+    pub query: String,
+    pub features: Result<Vec<PhotonFeature>, PhotonError>,
+}
+
+struct Pending {
+    generation: u64,
+    query: String,
+}
+
+struct Emitted {
+    generation: u64,
+    result: AutocompleteResult,
+}
+
+/// A search-as-you-type session on top of a [`Client`](crate::PhotonApiClient).
+///
+/// Pass every change of the search input to [`update`](Self::update). Requests are debounced and
+/// run on a background thread, and results are only handed out for the most recent query: results
+/// of queries that were superseded while their request was in flight are dropped.
+///
+/// Results are cached per query. If a query extends a cached one whose result set was complete
+/// (fewer results than the filter's `limit`), the cached results are filtered locally instead of
+/// sending a new request.
+pub struct AutocompleteSession {
+    latest: Arc<AtomicU64>,
+    /// The generation of the last result that was handed out.
+    delivered: AtomicU64,
+    queries: Sender<Pending>,
+    results: Receiver<Emitted>,
+}
+
+impl AutocompleteSession {
+    /// Starts a new session that sends its requests through `client`.
+    pub fn new(client: Arc<Client>, options: AutocompleteOptions) -> Self {
+        let latest = Arc::new(AtomicU64::new(0));
+        let (queries, pending) = mpsc::channel();
+        let (results_sender, results) = mpsc::channel();
+
+        let worker = Worker {
+            client,
+            latest: Arc::clone(&latest),
+            debounce: options.debounce,
+            min_query_length: options.min_query_length,
+            cache: ResultCache::new(options.cache_size),
+            filter: options.filter,
+            results: results_sender,
+        };
+        thread::spawn(move || worker.run(pending));
+
+        AutocompleteSession {
+            latest,
+            delivered: AtomicU64::new(0),
+            queries,
+            results,
+        }
+    }
+
+    /// Submits the current content of the search input. Any previous query is superseded.
+    ///
+    /// Queries shorter than the configured minimum length immediately yield an empty result.
+    pub fn update(&self, query: &str) {
+        let generation = self.latest.fetch_add(1, Ordering::SeqCst) + 1;
+        let query = query.trim().to_string();
+        let _ = self.queries.send(Pending { generation, query });
+    }
+
+    /// Returns the result for the latest query if it is already available.
+    pub fn try_recv(&self) -> Option<AutocompleteResult> {
+        while let Ok(emitted) = self.results.try_recv() {
+            if let Some(result) = self.if_latest(emitted) {
+                return Some(result);
+            }
+        }
+        None
+    }
+
+    /// Blocks until the result for the latest query is available.
+    ///
+    /// Returns `None` right away if no query was submitted since the last result was handed out,
+    /// and if the background thread has stopped, e.g. because a request panicked.
+    pub fn recv(&self) -> Option<AutocompleteResult> {
+        if self.delivered.load(Ordering::SeqCst) == self.latest.load(Ordering::SeqCst) {
+            return None;
+        }
+        while let Ok(emitted) = self.results.recv() {
+            if let Some(result) = self.if_latest(emitted) {
+                return Some(result);
+            }
+        }
+        None
+    }
+
+    /// Waits at most `timeout` for the result of the latest query.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<AutocompleteResult> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.results.recv_timeout(remaining) {
+                Ok(emitted) => {
+                    if let Some(result) = self.if_latest(emitted) {
+                        return Some(result);
+                    }
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+
+    fn if_latest(&self, emitted: Emitted) -> Option<AutocompleteResult> {
+        if emitted.generation == self.latest.load(Ordering::SeqCst) {
+            self.delivered.store(emitted.generation, Ordering::SeqCst);
+            Some(emitted.result)
+        } else {
+            None
+        }
+    }
+}
+
+struct Worker {
+    client: Arc<Client>,
+    latest: Arc<AtomicU64>,
+    debounce: Duration,
+    min_query_length: usize,
+    cache: ResultCache,
+    filter: Option<ForwardFilter>,
+    results: Sender<Emitted>,
+}
+
+impl Worker {
+    fn run(mut self, pending: Receiver<Pending>) {
+        while let Ok(mut next) = pending.recv() {
+            // Wait until the input has been quiet for `debounce`, keeping only the newest query.
+            // Short queries are answered right away, as they do not need a request.
+            while !self.is_short(&next.query) {
+                match pending.recv_timeout(self.debounce) {
+                    Ok(newer) => next = newer,
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            if self.is_stale(next.generation) {
+                continue;
+            }
+
+            let features = if self.is_short(&next.query) {
+                Ok(Vec::new())
+            } else {
+                self.search(&next.query)
+            };
+            if self.is_stale(next.generation) {
+                continue;
+            }
+
+            let result = AutocompleteResult {
+                query: next.query,
+                features,
+            };
+            let emitted = Emitted {
+                generation: next.generation,
+                result,
+            };
+            if self.results.send(emitted).is_err() {
+                return;
+            }
+        }
+    }
+
+    fn is_short(&self, query: &str) -> bool {
+        query.chars().count() < self.min_query_length
+    }
+
+    fn is_stale(&self, generation: u64) -> bool {
+        generation != self.latest.load(Ordering::SeqCst)
+    }
+
+    fn search(&mut self, query: &str) -> Result<Vec<PhotonFeature>, PhotonError> {
+        if let Some(features) = self.cache.lookup(query) {
+            return Ok(features);
+        }
+
+        let features = self
+            .client
+            .forward_search(query, self.filter.clone())
+            .map_err(|error| PhotonError::new(&error.to_string()))?;
+
+        let limit = self.filter.as_ref().and_then(|filter| filter.limit);
+        let complete = limit.map_or(false, |limit| (features.len() as u64) < limit);
+        self.cache.insert(query, features.clone(), complete);
+        Ok(features)
+    }
+}
+
+struct CacheEntry {
+    query: String,
+    features: Vec<PhotonFeature>,
+    complete: bool,
+}
+
+struct ResultCache {
+    capacity: usize,
+    entries: VecDeque<CacheEntry>,
+}
+
+impl ResultCache {
+    fn new(capacity: usize) -> Self {
+        ResultCache {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    fn lookup(&self, query: &str) -> Option<Vec<PhotonFeature>> {
+        let query = query.to_lowercase();
+        if let Some(entry) = self.entries.iter().find(|entry| entry.query == query) {
+            return Some(entry.features.clone());
+        }
+
+        // A complete result set for a prefix contains every result of the extended query.
+        let prefix = self
+            .entries
+            .iter()
+            .filter(|entry| entry.complete && query.starts_with(&entry.query))
+            .max_by_key(|entry| entry.query.len())?;
+        let terms: Vec<&str> = query.split_whitespace().collect();
+        let features: Vec<PhotonFeature> = prefix
+            .features
+            .iter()
+            .filter(|feature| matches_all_terms(feature, &terms))
+            .cloned()
+            .collect();
+
+        if features.is_empty() {
+            None
+        } else {
+            Some(features)
+        }
+    }
+
+    fn insert(&mut self, query: &str, features: Vec<PhotonFeature>, complete: bool) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(CacheEntry {
+            query: query.to_lowercase(),
+            features,
+            complete,
+        });
+    }
+}
+
+fn matches_all_terms(feature: &PhotonFeature, terms: &[&str]) -> bool {
+    let text = [
+        &feature.name,
+        &feature.street,
+        &feature.house_number,
+        &feature.postcode,
+        &feature.district,
+        &feature.city,
+        &feature.county,
+        &feature.state,
+        &feature.country,
+    ]
+    .iter()
+    .filter_map(|part| part.as_deref())
+    .collect::<Vec<&str>>()
+    .join(" ")
+    .to_lowercase();
+
+    terms.iter().all(|term| text.contains(term))
+}
@@ -0,0 +1,414 @@
+//! Geocoding of many queries at once, e.g. the rows of a spreadsheet. See
+//! [`Client::geocode_batch`](crate::PhotonApiClient::geocode_batch).
+//!
+//! Every query is forward-searched with the same filter, and the candidate that matches the query
+//! best according to [`scoring`](crate::scoring) is kept. Long runs can write a [`Checkpoint`]
+//! journal, so a restarted run skips the rows that were already geocoded.
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::filter::ForwardFilter;
+use crate::data::json::PhotonFeatureRaw;
+use crate::data::scoring::{self, MatchQuery, MatchScore};
+use crate::data::PhotonFeature;
+use crate::error::{InvalidInputError, PhotonError};
+
+/// Configuration for [`Client::geocode_batch`](crate::PhotonApiClient::geocode_batch). This struct
+/// implements a builder pattern, so options can be easily constructed.
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// The filter of every search.
+    pub filter: ForwardFilter,
+    pub threads: usize,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        BatchOptions {
+            filter: ForwardFilter::new().limit(5),
+            threads: 1,
+        }
+    }
+}
+
+impl BatchOptions {
+    /// Construct new `BatchOptions` that compare up to 5 candidates per query on one thread.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The filter of every search. Its limit is the number of candidates that are compared.
+    pub fn filter(mut self, filter: ForwardFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// The number of queries that are searched in parallel.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+}
+
+/// The best match for a query of a batch.
+#[derive(Debug, Clone)]
+pub struct BatchMatch {
+    pub feature: PhotonFeature,
+    pub score: MatchScore,
+}
+
+/// What went wrong with a query of a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchErrorKind {
+    /// The query was rejected before it was sent, e.g. because it is empty.
+    InvalidInput,
+    /// The server could not be reached.
+    Transport,
+    /// The server answered with an HTTP error status.
+    Status,
+    /// The response could not be read, or Photon reported an error.
+    Response,
+}
+
+impl BatchErrorKind {
+    /// Whether the query may succeed when it is sent again later.
+    pub fn is_temporary(&self) -> bool {
+        matches!(self, BatchErrorKind::Transport | BatchErrorKind::Status)
+    }
+}
+
+impl Display for BatchErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BatchErrorKind::InvalidInput => "invalid_input",
+            BatchErrorKind::Transport => "transport",
+            BatchErrorKind::Status => "status",
+            BatchErrorKind::Response => "response",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The error of a single query of a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchError {
+    pub kind: BatchErrorKind,
+    pub message: String,
+}
+
+impl BatchError {
+    pub fn new(kind: BatchErrorKind, message: &str) -> Self {
+        BatchError {
+            kind,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<Box<dyn Error>> for BatchError {
+    fn from(error: Box<dyn Error>) -> Self {
+        let kind = if error.is::<InvalidInputError>() {
+            BatchErrorKind::InvalidInput
+        } else if let Some(error) = error.downcast_ref::<ureq::Error>() {
+            match error {
+                ureq::Error::Status(..) => BatchErrorKind::Status,
+                ureq::Error::Transport(_) => BatchErrorKind::Transport,
+            }
+        } else {
+            BatchErrorKind::Response
+        };
+        BatchError::new(kind, &error.to_string())
+    }
+}
+
+impl From<InvalidInputError> for BatchError {
+    fn from(error: InvalidInputError) -> Self {
+        BatchError::new(BatchErrorKind::InvalidInput, &error.to_string())
+    }
+}
+
+impl Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", &self.message)
+    }
+}
+
+impl Error for BatchError {}
+
+/// The result of each query of a batch: the best match, `None` if nothing was found, or the error
+/// of the query or its request.
+pub type BatchResult = Result<Option<BatchMatch>, BatchError>;
+
+/// A journal of the rows of a batch that have been geocoded, for use with
+/// [`Client::geocode_batch_checkpointed`](crate::PhotonApiClient::geocode_batch_checkpointed).
+///
+/// The journal is a file with one JSON object per row, holding the query and its result or error.
+/// Every batch is appended and synced to disk before its results are returned. When the journal
+/// is opened again, the rows it contains are taken from it instead of being searched again, so an
+/// interrupted run continues where it stopped. A row that was only partially written when the
+/// run died is discarded and searched again, and so is a row that failed with a
+/// [temporary](BatchErrorKind::is_temporary) error. The retry is appended to the journal.
+#[derive(Debug)]
+pub struct Checkpoint {
+    replay: std::iter::Take<Lines<BufReader<File>>>,
+    pending: Option<JournalEntry>,
+    /// The latest entry of every row that was retried, by row.
+    retried: HashMap<usize, JournalEntry>,
+    /// Rows that were handed out for a retry and may be recorded again.
+    retrying: HashSet<usize>,
+    writer: BufWriter<File>,
+    next_row: usize,
+    last_recorded: Option<usize>,
+    resumed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalEntry {
+    row: usize,
+    query: String,
+    #[serde(default)]
+    feature: Option<PhotonFeatureRaw>,
+    #[serde(default)]
+    error: Option<BatchError>,
+}
+
+impl JournalEntry {
+    fn is_temporary(&self) -> bool {
+        self.error
+            .as_ref()
+            .map_or(false, |error| error.kind.is_temporary())
+    }
+}
+
+impl Checkpoint {
+    /// Opens the journal at `path`, or creates it if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Checkpoint, Box<dyn Error>> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        // Find the complete entries; an incomplete last line is cut off. Retries of rows that failed
+        // temporarily follow the row's first entry and are kept aside, the last one wins.
+        let (mut entries, mut valid_length, mut last_row) = (0, 0, None);
+        let mut temporary = HashSet::new();
+        let mut retried = HashMap::new();
+        let mut reader = BufReader::new(&file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let length = reader.read_line(&mut line)?;
+            if length == 0 {
+                break;
+            }
+            match serde_json::from_str::<JournalEntry>(&line) {
+                Ok(entry) if line.ends_with('\n') => {
+                    let is_retry = last_row.map_or(false, |last| entry.row <= last);
+                    if is_retry && !temporary.contains(&entry.row) {
+                        return Err(corrupt(path, entries + 1, "rows are not ascending").into());
+                    }
+                    if entry.is_temporary() {
+                        temporary.insert(entry.row);
+                    } else {
+                        temporary.remove(&entry.row);
+                    }
+                    if is_retry {
+                        retried.insert(entry.row, entry);
+                    } else {
+                        last_row = Some(entry.row);
+                    }
+                }
+                _ if reader.fill_buf()?.is_empty() => break,
+                Ok(_) | Err(_) => return Err(corrupt(path, entries + 1, "invalid entry").into()),
+            }
+            entries += 1;
+            valid_length += length as u64;
+        }
+        file.set_len(valid_length)?;
+
+        Ok(Checkpoint {
+            replay: BufReader::new(File::open(path)?).lines().take(entries),
+            pending: None,
+            retried,
+            retrying: HashSet::new(),
+            writer: BufWriter::new(file),
+            next_row: 0,
+            last_recorded: last_row,
+            resumed: 0,
+        })
+    }
+
+    /// The number of rows that were taken from the journal so far.
+    pub fn resumed(&self) -> usize {
+        self.resumed
+    }
+
+    /// The result of `row` from the journal, if it has one that is final. A row that failed with a
+    /// temporary error returns `None` so it is searched again. Rows must be requested in ascending
+    /// order.
+    pub(crate) fn replay(
+        &mut self,
+        row: usize,
+        query: &MatchQuery,
+    ) -> Result<Option<BatchResult>, Box<dyn Error>> {
+        if row < self.next_row {
+            return Err(InvalidInputError::new(&format!(
+                "rows must be geocoded in ascending order, got row {} after row {}",
+                row,
+                self.next_row - 1
+            ))
+            .into());
+        }
+        self.next_row = row + 1;
+
+        while self.pending.as_ref().map_or(true, |entry| entry.row < row) {
+            match self.replay.next() {
+                Some(line) => self.pending = Some(serde_json::from_str(&line?)?),
+                None => {
+                    self.pending = None;
+                    break;
+                }
+            }
+        }
+        let entry = match self.pending.take() {
+            Some(entry) if entry.row == row => entry,
+            other => {
+                self.pending = other;
+                return Ok(None);
+            }
+        };
+        let entry = self.retried.remove(&row).unwrap_or(entry);
+
+        let text = query_text(query).unwrap_or_default();
+        if entry.query != text {
+            return Err(InvalidInputError::new(&format!(
+                "the checkpoint does not match the input: row {} was '{}', but is '{}'",
+                row, entry.query, text
+            ))
+            .into());
+        }
+        if entry.is_temporary() {
+            self.retrying.insert(row);
+            return Ok(None);
+        }
+        self.resumed += 1;
+        Ok(Some(match (entry.error, entry.feature) {
+            (Some(error), _) => Err(error),
+            (None, Some(feature)) => {
+                let feature = PhotonFeature::from(feature);
+                let score = scoring::score(query, &feature);
+                Ok(Some(BatchMatch { feature, score }))
+            }
+            (None, None) => Ok(None),
+        }))
+    }
+
+    /// Appends the result of `row` to the journal. It is only written to disk by [`Self::sync`].
+    /// Rows must be recorded in ascending order, except for rows that [`Self::replay`] handed out
+    /// for a retry.
+    pub(crate) fn record(
+        &mut self,
+        row: usize,
+        query: &MatchQuery,
+        result: &BatchResult,
+    ) -> Result<(), Box<dyn Error>> {
+        let is_retry = self.retrying.remove(&row);
+        if let Some(last) = self.last_recorded.filter(|last| row <= *last && !is_retry) {
+            return Err(InvalidInputError::new(&format!(
+                "row {} cannot be added to the checkpoint after row {}",
+                row, last
+            ))
+            .into());
+        }
+        if !is_retry {
+            self.last_recorded = Some(row);
+        }
+        let (feature, error) = match result {
+            Ok(Some(best)) => (Some(PhotonFeatureRaw::from(&best.feature)), None),
+            Ok(None) => (None, None),
+            Err(error) => (None, Some(error.clone())),
+        };
+        let entry = JournalEntry {
+            row,
+            query: query_text(query).unwrap_or_default(),
+            feature,
+            error,
+        };
+        serde_json::to_writer(&mut self.writer, &entry)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Writes all recorded results to disk.
+    pub(crate) fn sync(&mut self) -> Result<(), Box<dyn Error>> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_data()?;
+        Ok(())
+    }
+}
+
+fn corrupt(path: &Path, line: usize, reason: &str) -> PhotonError {
+    PhotonError::new(&format!(
+        "checkpoint {} is corrupt at line {}: {}",
+        path.display(),
+        line,
+        reason
+    ))
+}
+
+/// The text that is searched for `query`: the trimmed free text, or the structured address
+/// formatted as one line. Empty queries are rejected.
+pub fn query_text(query: &MatchQuery) -> Result<String, InvalidInputError> {
+    let text = match query {
+        MatchQuery::Text(text) => text.trim().to_string(),
+        MatchQuery::Structured(address) => address.to_string(),
+    };
+    if text.is_empty() {
+        return Err(InvalidInputError::new("query is empty"));
+    }
+    Ok(text)
+}
+
+/// The candidate with the highest confidence for `query`.
+pub(crate) fn best_match(query: &MatchQuery, candidates: Vec<PhotonFeature>) -> Option<BatchMatch> {
+    scoring::rank(query, candidates)
+        .into_iter()
+        .next()
+        .map(|(feature, score)| BatchMatch { feature, score })
+}
+
+/// Applies `f` to all `items` on up to `threads` threads and returns the results in the order of
+/// the items.
+pub(crate) fn map_parallel<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let threads = threads.max(1);
+    if threads == 1 || items.len() < 2 {
+        return items.iter().map(f).collect();
+    }
+    let chunk_size = (items.len() + threads - 1) / threads;
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("search thread panicked"))
+            .collect()
+    })
+}
@@ -0,0 +1,348 @@
+//! `photon geocode`: geocodes the rows of a CSV or TSV file and writes them with their best match.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use lexopt::prelude::*;
+#[cfg(feature = "geopackage")]
+use photon_geocoding::batch::query_text;
+use photon_geocoding::batch::{BatchOptions, BatchResult, Checkpoint};
+#[cfg(feature = "geopackage")]
+use photon_geocoding::export::geopackage::{GeoPackageOptions, GeoPackageWriter};
+use photon_geocoding::scoring::{AddressField, MatchQuery};
+use photon_geocoding::{PhotonApiClient, StructuredAddress};
+
+use crate::FilterArgs;
+
+/// The help of the GeoPackage options, which only exist with the geopackage feature.
+#[cfg(feature = "geopackage")]
+macro_rules! gpkg_usage {
+    () => {
+        "  --gpkg FILE              Also write the matches to a GeoPackage layer
+  --extents                With --gpkg, also write a layer with the extents of the matches
+"
+    };
+}
+
+#[cfg(not(feature = "geopackage"))]
+macro_rules! gpkg_usage {
+    () => {
+        ""
+    };
+}
+
+const USAGE: &str = concat!(
+    "\
+Usage: photon geocode <INPUT> [OPTIONS]
+
+Geocodes every row of the CSV or TSV file INPUT (- for stdin) and writes the input columns
+followed by latitude, longitude, label, osm_id and confidence. The first row must contain the
+column names. Rows are either searched by the text of the --query columns, or by the address
+parts in the columns mapped with --street, --city etc.
+
+Long runs can be resumed: with --checkpoint, every geocoded row is recorded in a journal, and a
+restarted run with the same input takes these rows from the journal instead of searching them
+again. Rows that failed because the server could not be reached or answered with an error status
+are searched again. Rows that failed can be written to a separate file with --failed, which has
+the columns of the input and can be geocoded again on its own.
+
+With the geopackage feature, the matches can also be written to a GeoPackage layer for QGIS and
+other GIS applications with --gpkg.
+
+Options:
+  -o, --output FILE        Output file [default: stdout]
+  --tsv                    Read and write tab-separated values; the default for .tsv files
+  --query COLUMN           Column with a free-text query; repeat to join several columns
+  --name COLUMN            Column with the name of a place
+  --street COLUMN          Column with the street
+  --house-number COLUMN    Column with the house number
+  --postcode COLUMN        Column with the postcode
+  --city COLUMN            Column with the city
+  --state COLUMN           Column with the state
+  --country COLUMN         Column with the country
+  --threads N              Number of rows searched in parallel [default: 1]
+  --checkpoint FILE        Journal of geocoded rows, which is resumed if it exists
+  --failed FILE            Write the failed rows with row, error_kind and error columns
+",
+    filter_usage!(),
+    gpkg_usage!(),
+    "  -h, --help               Print this help"
+);
+
+/// The number of rows that are geocoded before their results are written.
+const CHUNK_SIZE: usize = 256;
+
+const RESULT_COLUMNS: [&str; 5] = ["latitude", "longitude", "label", "osm_id", "confidence"];
+
+const FAILURE_COLUMNS: [&str; 3] = ["row", "error_kind", "error"];
+
+/// How the columns of the input make up a query.
+#[derive(Debug, Default)]
+struct ColumnMapping {
+    query: Vec<String>,
+    address: Vec<(AddressField, String)>,
+}
+
+/// A [`ColumnMapping`] resolved against the header of the input.
+enum Columns {
+    Query(Vec<usize>),
+    Address(Vec<(AddressField, usize)>),
+}
+
+impl ColumnMapping {
+    fn resolve(&self, headers: &csv::StringRecord) -> Result<Columns, Box<dyn Error>> {
+        let index = |column: &str| {
+            headers
+                .iter()
+                .position(|header| header.trim() == column)
+                .ok_or_else(|| format!("no column '{}' in the input", column))
+        };
+        match (self.query.is_empty(), self.address.is_empty()) {
+            (false, true) => Ok(Columns::Query(
+                self.query
+                    .iter()
+                    .map(|column| index(column))
+                    .collect::<Result<_, _>>()?,
+            )),
+            (true, false) => Ok(Columns::Address(
+                self.address
+                    .iter()
+                    .map(|(field, column)| Ok((*field, index(column)?)))
+                    .collect::<Result<_, String>>()?,
+            )),
+            (false, false) => Err("--query cannot be combined with address columns".into()),
+            (true, true) => {
+                Err("map at least one column with --query or an address part such as --city".into())
+            }
+        }
+    }
+}
+
+impl Columns {
+    fn query(&self, record: &csv::StringRecord) -> MatchQuery {
+        let value = |index: usize| record.get(index).unwrap_or_default().trim();
+        match self {
+            Columns::Query(indices) => MatchQuery::Text(
+                indices
+                    .iter()
+                    .map(|index| value(*index))
+                    .filter(|value| !value.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            Columns::Address(fields) => {
+                let mut address = StructuredAddress::new();
+                for (field, index) in fields {
+                    let value = value(*index);
+                    if value.is_empty() {
+                        continue;
+                    }
+                    address = match field {
+                        AddressField::Name => address.name(value),
+                        AddressField::Street => address.street(value),
+                        AddressField::HouseNumber => address.house_number(value),
+                        AddressField::Postcode => address.postcode(value),
+                        AddressField::City => address.city(value),
+                        AddressField::State => address.state(value),
+                        AddressField::Country => address.country(value),
+                    };
+                }
+                MatchQuery::Structured(address)
+            }
+        }
+    }
+}
+
+pub fn run(api: &PhotonApiClient, mut parser: lexopt::Parser) -> Result<(), Box<dyn Error>> {
+    let mut input: Option<PathBuf> = None;
+    let mut output: Option<PathBuf> = None;
+    let mut tsv = false;
+    let mut mapping = ColumnMapping::default();
+    let mut filter = FilterArgs::new();
+    let mut threads = 1;
+    let mut checkpoint: Option<PathBuf> = None;
+    let mut failures: Option<PathBuf> = None;
+    #[cfg(feature = "geopackage")]
+    let (mut gpkg, mut extents): (Option<PathBuf>, bool) = (None, false);
+    while let Some(arg) = parser.next()? {
+        let mut address_column = |field: AddressField, parser: &mut lexopt::Parser| {
+            mapping.address.push((field, parser.value()?.string()?));
+            Ok::<(), lexopt::Error>(())
+        };
+        match arg {
+            Short('o') | Long("output") => output = Some(parser.value()?.into()),
+            Long("tsv") => tsv = true,
+            Long("query") => mapping.query.push(parser.value()?.string()?),
+            Long("name") => address_column(AddressField::Name, &mut parser)?,
+            Long("street") => address_column(AddressField::Street, &mut parser)?,
+            Long("house-number") => address_column(AddressField::HouseNumber, &mut parser)?,
+            Long("postcode") => address_column(AddressField::Postcode, &mut parser)?,
+            Long("city") => address_column(AddressField::City, &mut parser)?,
+            Long("state") => address_column(AddressField::State, &mut parser)?,
+            Long("country") => address_column(AddressField::Country, &mut parser)?,
+            Long(option) if FilterArgs::OPTIONS.contains(&option) => {
+                let option = option.to_string();
+                filter.parse(&option, &mut parser)?;
+            }
+            Long("threads") => threads = parser.value()?.parse()?,
+            Long("checkpoint") => checkpoint = Some(parser.value()?.into()),
+            Long("failed") => failures = Some(parser.value()?.into()),
+            #[cfg(feature = "geopackage")]
+            Long("gpkg") => gpkg = Some(parser.value()?.into()),
+            #[cfg(feature = "geopackage")]
+            Long("extents") => extents = true,
+            Short('h') | Long("help") => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            Value(path) if input.is_none() => input = Some(path.into()),
+            _ => return Err(arg.unexpected().into()),
+        }
+    }
+    let input = input.ok_or_else(|| format!("missing input file\n\n{}", USAGE))?;
+    let filter = filter.build()?;
+    let is_stdin = input.as_os_str() == "-";
+    tsv = tsv
+        || input
+            .extension()
+            .map_or(false, |extension| extension.eq_ignore_ascii_case("tsv"));
+    let delimiter = if tsv { b'\t' } else { b',' };
+
+    let source: Box<dyn Read> = if is_stdin {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(&input)?)
+    };
+    let sink: Box<dyn Write> = match &output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(source);
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_writer(sink);
+
+    let headers = reader.headers()?.clone();
+    let columns = mapping.resolve(&headers)?;
+    writer.write_record(headers.iter().chain(RESULT_COLUMNS))?;
+    let mut checkpoint = checkpoint.map(Checkpoint::open).transpose()?;
+    let mut failures = match failures {
+        Some(path) => {
+            let mut failures = csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .flexible(true)
+                .from_path(path)?;
+            failures.write_record(headers.iter().chain(FAILURE_COLUMNS))?;
+            Some(failures)
+        }
+        None => None,
+    };
+
+    #[cfg(feature = "geopackage")]
+    let mut gpkg = match gpkg {
+        Some(path) => {
+            let mut options = GeoPackageOptions::new().layer("geocoded");
+            if extents {
+                options = options.extent_layer("geocoded_extents");
+            }
+            Some(GeoPackageWriter::create(path, Some(options))?)
+        }
+        None if extents => return Err("--extents needs --gpkg".into()),
+        None => None,
+    };
+
+    let options = BatchOptions::new().filter(filter).threads(threads);
+    let (mut rows, mut matched, mut failed) = (0, 0, 0);
+    let mut records = reader.records();
+    loop {
+        let chunk = records
+            .by_ref()
+            .take(CHUNK_SIZE)
+            .collect::<Result<Vec<_>, _>>()?;
+        if chunk.is_empty() {
+            break;
+        }
+        let queries: Vec<MatchQuery> = chunk.iter().map(|record| columns.query(record)).collect();
+        let results = match &mut checkpoint {
+            Some(checkpoint) => {
+                api.geocode_batch_checkpointed(&queries, rows, checkpoint, Some(options.clone()))?
+            }
+            None => api.geocode_batch(&queries, Some(options.clone()))?,
+        };
+        #[cfg(feature = "geopackage")]
+        if let Some(gpkg) = &mut gpkg {
+            for (query, result) in queries.iter().zip(&results) {
+                if let Ok(Some(best)) = result {
+                    // The searched text is stored with the match.
+                    gpkg.add(&best.feature, query_text(query).ok().as_deref())?;
+                }
+            }
+        }
+        for (record, result) in chunk.iter().zip(results) {
+            rows += 1;
+            match &result {
+                Ok(Some(_)) => matched += 1,
+                Ok(None) => {}
+                Err(error) => {
+                    failed += 1;
+                    // Row 1 is the header.
+                    let row = (rows + 1).to_string();
+                    match &mut failures {
+                        Some(failures) => failures.write_record(record.iter().chain([
+                            row.as_str(),
+                            &error.kind.to_string(),
+                            &error.message,
+                        ]))?,
+                        None => eprintln!("row {}: {}", row, error),
+                    }
+                }
+            }
+            writer.write_record(
+                record
+                    .iter()
+                    .map(String::from)
+                    .chain(result_columns(&result)),
+            )?;
+        }
+        writer.flush()?;
+        if let Some(failures) = &mut failures {
+            failures.flush()?;
+        }
+    }
+
+    #[cfg(feature = "geopackage")]
+    if let Some(gpkg) = gpkg {
+        gpkg.finish()?;
+    }
+
+    eprintln!(
+        "{} rows, {} matched, {} without match, {} failed",
+        rows,
+        matched,
+        rows - matched - failed,
+        failed
+    );
+    if let Some(checkpoint) = &checkpoint {
+        eprintln!("{} rows resumed from the checkpoint", checkpoint.resumed());
+    }
+    Ok(())
+}
+
+fn result_columns(result: &BatchResult) -> Vec<String> {
+    match result {
+        Ok(Some(best)) => vec![
+            best.feature.coords.lat.to_string(),
+            best.feature.coords.lon.to_string(),
+            best.feature.label(),
+            best.feature.osm_id.to_string(),
+            format!("{:.3}", best.score.confidence),
+        ],
+        _ => vec![String::new(); RESULT_COLUMNS.len()],
+    }
+}
@@ -0,0 +1,115 @@
+//! Command line interface for photon-geocoding. Build it with `--features cli`.
+
+use std::error::Error;
+use std::process::ExitCode;
+
+use lexopt::prelude::*;
+use photon_geocoding::filter::{ForwardFilter, PhotonLayer};
+use photon_geocoding::{LatLon, PhotonApiClient};
+
+/// The help of the options parsed by [`FilterArgs`], for the usage texts of the commands.
+macro_rules! filter_usage {
+    () => {
+        "  --lang LANG              Language of the results
+  --limit N                Number of candidates compared per query [default: 5]
+  --layer LAYER            Restrict results to a layer, e.g. house or street; can be repeated
+  --bias LAT,LON           Prefer results close to this position
+"
+    };
+}
+
+mod geocode;
+mod ndjson;
+mod photos;
+
+const USAGE: &str = "\
+Usage: photon [--url URL] <COMMAND> [OPTIONS]
+
+Commands:
+  geocode <INPUT>     Geocode the rows of a CSV or TSV file
+  ndjson [INPUT]      Geocode a stream of newline-delimited JSON queries
+  photo-tags <DIR>    Reverse-geocode the GPS positions of JPEG and TIFF photos in DIR
+
+Options:
+  --url URL           Photon instance to use [default: https://photon.komoot.io]
+  -h, --help          Print this help, or the help of a command";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let mut parser = lexopt::Parser::from_env();
+    let mut url: Option<String> = None;
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Long("url") => url = Some(parser.value()?.string()?),
+            Short('h') | Long("help") => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            Value(command) => {
+                let api = match url {
+                    Some(url) => PhotonApiClient::new(&url),
+                    None => PhotonApiClient::default(),
+                };
+                return match command.string()?.as_str() {
+                    "geocode" => geocode::run(&api, parser),
+                    "ndjson" => ndjson::run(&api, parser),
+                    "photo-tags" => photos::run(&api, parser),
+                    other => Err(format!("unknown command '{}'\n\n{}", other, USAGE).into()),
+                };
+            }
+            _ => return Err(arg.unexpected().into()),
+        }
+    }
+    Err(format!("missing command\n\n{}", USAGE).into())
+}
+
+/// The search options of the commands that forward-search.
+struct FilterArgs {
+    filter: ForwardFilter,
+    layers: Vec<PhotonLayer>,
+}
+
+impl FilterArgs {
+    const OPTIONS: [&'static str; 4] = ["lang", "limit", "layer", "bias"];
+
+    fn new() -> Self {
+        FilterArgs {
+            filter: ForwardFilter::new().limit(5),
+            layers: Vec::new(),
+        }
+    }
+
+    /// Reads the value of `option`, which must be one of [`Self::OPTIONS`].
+    fn parse(&mut self, option: &str, parser: &mut lexopt::Parser) -> Result<(), Box<dyn Error>> {
+        let value = parser.value()?;
+        match option {
+            "lang" => self.filter = self.filter.clone().language(&value.string()?),
+            "limit" => self.filter = self.filter.clone().limit(value.parse()?),
+            "layer" => self.layers.push(value.parse()?),
+            "bias" => {
+                let coords: LatLon = value.parse()?;
+                self.filter = self.filter.clone().location_bias(coords, None, None);
+            }
+            _ => unreachable!("unknown filter option {}", option),
+        }
+        Ok(())
+    }
+
+    fn build(self) -> Result<ForwardFilter, Box<dyn Error>> {
+        let mut filter = self.filter;
+        if !self.layers.is_empty() {
+            filter = filter.layer(self.layers);
+        }
+        filter.validate()?;
+        Ok(filter)
+    }
+}
@@ -0,0 +1,75 @@
+//! `photon ndjson`: geocodes a stream of newline-delimited JSON queries.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use lexopt::prelude::*;
+use photon_geocoding::batch::BatchOptions;
+use photon_geocoding::PhotonApiClient;
+
+use crate::FilterArgs;
+
+const USAGE: &str = concat!(
+    "\
+Usage: photon ndjson [INPUT] [OPTIONS]
+
+Geocodes the JSON queries in each line of INPUT (stdin if omitted or -) and writes one JSON
+result per line, in the order of the input. A query has either a free-text \"query\" or address
+fields such as \"street\" and \"city\", and may override the options below with \"lang\",
+\"limit\", \"layer\", \"bbox\" and \"bias\":
+
+  {\"id\": 1, \"query\": \"Marienplatz 8, München\", \"lang\": \"de\"}
+  {\"id\": 2, \"street\": \"Unter den Linden 1\", \"city\": \"Berlin\"}
+
+Options:
+  -o, --output FILE        Output file [default: stdout]
+  --threads N              Number of queries searched in parallel [default: 1]
+",
+    filter_usage!(),
+    "  -h, --help               Print this help"
+);
+
+pub fn run(api: &PhotonApiClient, mut parser: lexopt::Parser) -> Result<(), Box<dyn Error>> {
+    let mut input: Option<PathBuf> = None;
+    let mut output: Option<PathBuf> = None;
+    let mut filter = FilterArgs::new();
+    let mut threads = 1;
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Short('o') | Long("output") => output = Some(parser.value()?.into()),
+            Long("threads") => threads = parser.value()?.parse()?,
+            Long(option) if FilterArgs::OPTIONS.contains(&option) => {
+                let option = option.to_string();
+                filter.parse(&option, &mut parser)?;
+            }
+            Short('h') | Long("help") => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            Value(path) if input.is_none() => input = Some(path.into()),
+            _ => return Err(arg.unexpected().into()),
+        }
+    }
+
+    let source: Box<dyn BufRead> = match &input {
+        Some(path) if path.as_os_str() != "-" => Box::new(BufReader::new(File::open(path)?)),
+        _ => Box::new(io::stdin().lock()),
+    };
+    let sink: Box<dyn Write + Send> = match &output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    let options = BatchOptions::new().filter(filter.build()?).threads(threads);
+    let stats = api.geocode_ndjson(source, sink, Some(options))?;
+
+    eprintln!(
+        "{} queries, {} matched, {} without match, {} failed",
+        stats.queries,
+        stats.matched,
+        stats.queries - stats.matched - stats.failed,
+        stats.failed
+    );
+    Ok(())
+}
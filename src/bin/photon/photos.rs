@@ -0,0 +1,330 @@
+//! `photon photo-tags`: reverse-geocodes the GPS positions in the EXIF data of photos and writes a
+//! sidecar report next to every image.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use exif::{In, Reader, Tag, Value};
+use lexopt::prelude::*;
+use photon_geocoding::bulk::{BulkReverseOptions, Grid};
+use photon_geocoding::filter::ReverseFilter;
+use photon_geocoding::{LatLon, PhotonApiClient, PhotonFeature};
+use serde_json::json;
+
+const USAGE: &str = "\
+Usage: photon photo-tags <DIR> [OPTIONS]
+
+Reads the GPS position of every JPEG and TIFF photo in DIR and its subdirectories and writes a
+report next to each image, e.g. IMG_0001.jpg.json. Photos taken at the same position are only
+looked up once.
+
+Options:
+  --format FORMAT     Format of the reports, json or csv [default: json]
+  --lang LANG         Language of the place names
+  --cell METERS       Share one lookup between all photos in a cell of this size, at the cost of
+                      accuracy [default: look up every position]
+  -h, --help          Print this help";
+
+const EXTENSIONS: [&str; 4] = ["jpg", "jpeg", "tif", "tiff"];
+
+const COLUMNS: [&str; 12] = [
+    "file",
+    "latitude",
+    "longitude",
+    "label",
+    "street",
+    "house_number",
+    "postcode",
+    "city",
+    "state",
+    "country",
+    "osm_id",
+    "error",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    Csv,
+}
+
+impl Format {
+    fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Csv => "csv",
+        }
+    }
+}
+
+pub fn run(api: &PhotonApiClient, mut parser: lexopt::Parser) -> Result<(), Box<dyn Error>> {
+    let mut dir: Option<PathBuf> = None;
+    let mut format = Format::Json;
+    let mut filter = ReverseFilter::new();
+    let mut cell: Option<f64> = None;
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Long("format") => {
+                format = match parser.value()?.string()?.to_lowercase().as_str() {
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    other => return Err(format!("unknown format '{}'", other).into()),
+                }
+            }
+            Long("lang") => filter = filter.language(&parser.value()?.string()?),
+            Long("cell") => cell = Some(parser.value()?.parse()?),
+            Short('h') | Long("help") => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            Value(path) if dir.is_none() => dir = Some(path.into()),
+            _ => return Err(arg.unexpected().into()),
+        }
+    }
+    let dir = dir.ok_or_else(|| format!("missing directory\n\n{}", USAGE))?;
+
+    let mut images = Vec::new();
+    find_images(&dir, &mut images)?;
+    images.sort();
+
+    let positions: Vec<Result<LatLon, String>> =
+        images.iter().map(|image| read_position(image)).collect();
+    let located: Vec<LatLon> = positions
+        .iter()
+        .filter_map(|position| position.as_ref().ok().copied())
+        .collect();
+    let lookups = match cell {
+        Some(size) => Lookups::shared(api, &located, Grid::Meters(size), filter)?,
+        None => Lookups::exact(api, &located, filter),
+    };
+
+    let mut assignments = lookups.assignments.iter();
+    for (image, position) in images.iter().zip(positions) {
+        let report = match position {
+            Ok(coords) => match assignments.next() {
+                Some(Ok(index)) => match &lookups.results[*index] {
+                    Ok(Some(feature)) => Report::found(image, coords, feature),
+                    Ok(None) => Report::failed(image, Some(coords), "no place found"),
+                    Err(error) => Report::failed(image, Some(coords), error),
+                },
+                Some(Err(error)) => Report::failed(image, Some(coords), error),
+                None => Report::failed(image, Some(coords), "position was not looked up"),
+            },
+            Err(error) => Report::failed(image, None, &error),
+        };
+        report.write(image, format)?;
+    }
+
+    eprintln!(
+        "{} photos, {} with GPS position, {} without; {} lookups ({:.0}% reused)",
+        images.len(),
+        located.len(),
+        images.len() - located.len(),
+        lookups.results.len(),
+        lookups.reuse_ratio() * 100.0
+    );
+    Ok(())
+}
+
+/// The reverse-geocoded positions of the located photos.
+struct Lookups {
+    /// The index into `results` of every located photo, or why it was not looked up.
+    assignments: Vec<Result<usize, String>>,
+    /// The place of every distinct lookup.
+    results: Vec<Result<Option<PhotonFeature>, String>>,
+}
+
+impl Lookups {
+    /// Looks up every distinct position once. Positions are compared at 6 decimal places, about
+    /// 10 centimeters, which is finer than the accuracy of GPS.
+    fn exact(api: &PhotonApiClient, located: &[LatLon], filter: ReverseFilter) -> Self {
+        let filter = filter.limit(1);
+        let mut positions: HashMap<(i64, i64), usize> = HashMap::new();
+        let mut results = Vec::new();
+        let assignments = located
+            .iter()
+            .map(|coords| {
+                let key = (
+                    (coords.lat * 1e6).round() as i64,
+                    (coords.lon * 1e6).round() as i64,
+                );
+                Ok(*positions.entry(key).or_insert_with(|| {
+                    results.push(
+                        api.reverse_search(*coords, Some(filter.clone()))
+                            .map(|features| features.into_iter().next())
+                            .map_err(|error| error.to_string()),
+                    );
+                    results.len() - 1
+                }))
+            })
+            .collect();
+        Lookups {
+            assignments,
+            results,
+        }
+    }
+
+    /// Looks up the center of every cell of `grid` once and shares it between all photos in it.
+    fn shared(
+        api: &PhotonApiClient,
+        located: &[LatLon],
+        grid: Grid,
+        filter: ReverseFilter,
+    ) -> Result<Self, Box<dyn Error>> {
+        let options = BulkReverseOptions::new().grid(grid).filter(filter);
+        let bulk = api.bulk_reverse_search(located, Some(options))?;
+        Ok(Lookups {
+            assignments: bulk
+                .points
+                .into_iter()
+                .map(|cell| cell.map_err(|error| error.message))
+                .collect(),
+            results: bulk
+                .cells
+                .into_iter()
+                .map(|result| result.map_err(|error| error.message))
+                .collect(),
+        })
+    }
+
+    /// The share of located photos that did not need a lookup of their own.
+    fn reuse_ratio(&self) -> f64 {
+        if self.assignments.is_empty() {
+            return 0.0;
+        }
+        1.0 - self.results.len() as f64 / self.assignments.len() as f64
+    }
+}
+
+/// Collects the photos in `dir` and its subdirectories.
+fn find_images(dir: &Path, images: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        // `file_type` does not follow symlinks, so a link to a parent directory can't loop.
+        if entry.file_type()?.is_dir() {
+            find_images(&path, images)?;
+        } else if path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(false, |extension| {
+                EXTENSIONS.contains(&extension.to_lowercase().as_str())
+            })
+        {
+            images.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The GPS position of a photo, or why it has none.
+fn read_position(image: &Path) -> Result<LatLon, String> {
+    let file = File::open(image).map_err(|error| error.to_string())?;
+    let exif = Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .map_err(|error| format!("no EXIF data: {}", error))?;
+    let coordinate = |tag: Tag, reference: Tag, negative: u8| {
+        let field = exif.get_field(tag, In::PRIMARY)?;
+        let degrees = match &field.value {
+            Value::Rational(parts) if !parts.is_empty() => parts
+                .iter()
+                .zip([1.0, 60.0, 3600.0])
+                .map(|(part, divisor)| part.to_f64() / divisor)
+                .sum::<f64>(),
+            _ => return None,
+        };
+        let is_negative = match exif.get_field(reference, In::PRIMARY).map(|f| &f.value) {
+            Some(Value::Ascii(values)) => values
+                .first()
+                .and_then(|value| value.first())
+                .map_or(false, |c| c.to_ascii_uppercase() == negative),
+            _ => false,
+        };
+        Some(if is_negative { -degrees } else { degrees })
+    };
+    match (
+        coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S'),
+        coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W'),
+    ) {
+        (Some(lat), Some(lon)) => LatLon::try_new(lat, lon).map_err(|error| error.to_string()),
+        _ => Err(String::from("no GPS position")),
+    }
+}
+
+/// The report of one photo, with the values of [`COLUMNS`].
+struct Report {
+    values: Vec<Option<String>>,
+}
+
+impl Report {
+    fn found(image: &Path, coords: LatLon, feature: &PhotonFeature) -> Self {
+        Report {
+            values: vec![
+                Some(file_name(image)),
+                Some(coords.lat.to_string()),
+                Some(coords.lon.to_string()),
+                Some(feature.label()),
+                feature.street.clone(),
+                feature.house_number.clone(),
+                feature.postcode.clone(),
+                feature.city.clone(),
+                feature.state.clone(),
+                feature.country.clone(),
+                Some(feature.osm_id.to_string()),
+                None,
+            ],
+        }
+    }
+
+    fn failed(image: &Path, coords: Option<LatLon>, error: &str) -> Self {
+        let mut values = vec![None; COLUMNS.len()];
+        values[0] = Some(file_name(image));
+        if let Some(coords) = coords {
+            values[1] = Some(coords.lat.to_string());
+            values[2] = Some(coords.lon.to_string());
+        }
+        values[COLUMNS.len() - 1] = Some(String::from(error));
+        Report { values }
+    }
+
+    /// Writes the report next to `image`, with the format's extension appended to its file name.
+    fn write(&self, image: &Path, format: Format) -> Result<(), Box<dyn Error>> {
+        let mut path = image.as_os_str().to_owned();
+        path.push(".");
+        path.push(format.extension());
+        match format {
+            Format::Json => {
+                let mut object = serde_json::Map::new();
+                for (column, value) in COLUMNS.iter().zip(&self.values) {
+                    let value = match (*column, value) {
+                        ("latitude" | "longitude", Some(value)) => json!(value.parse::<f64>()?),
+                        ("osm_id", Some(value)) => json!(value.parse::<u64>()?),
+                        (_, value) => json!(value),
+                    };
+                    object.insert(String::from(*column), value);
+                }
+                fs::write(path, serde_json::to_string_pretty(&object)? + "\n")?;
+            }
+            Format::Csv => {
+                let mut writer = csv::Writer::from_path(path)?;
+                writer.write_record(COLUMNS)?;
+                writer.write_record(
+                    self.values
+                        .iter()
+                        .map(|value| value.as_deref().unwrap_or_default()),
+                )?;
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn file_name(image: &Path) -> String {
+    image
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+}
@@ -0,0 +1,220 @@
+//! Reverse geocoding of large point sets. See
+//! [`Client::bulk_reverse_search`](crate::PhotonApiClient::bulk_reverse_search).
+//!
+//! Neighbouring points usually resolve to the same street, so points are snapped to the cells of
+//! a grid and every cell is only searched once, at its center. The cell size trades accuracy for
+//! throughput: results may be off by up to [`Grid::max_error`] meters.
+
+use std::collections::HashMap;
+
+use crate::data::filter::ReverseFilter;
+use crate::data::geo::EARTH_RADIUS;
+use crate::data::geohash;
+use crate::data::{LatLon, PhotonFeature};
+use crate::error::{InvalidInputError, PhotonError};
+
+/// The grid points are snapped to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Grid {
+    /// Geohash cells with the given number of characters. Precision 7 gives cells of about
+    /// 150 by 150 meters, precision 8 of about 38 by 19 meters.
+    Geohash(usize),
+    /// Cells of roughly the given size in meters.
+    Meters(f64),
+}
+
+impl Grid {
+    /// The largest distance between a point and the center of its cell in meters, at `lat`.
+    pub fn max_error(&self, lat: f64) -> f64 {
+        match self {
+            Grid::Geohash(precision) => {
+                let center = LatLon::new(lat.clamp(-89.0, 89.0), 0.0);
+                match geohash::encode(&center, *precision).and_then(|hash| geohash::decode(&hash)) {
+                    Ok(cell) => cell.center().haversine_distance(&cell.north_east),
+                    Err(_) => f64::NAN,
+                }
+            }
+            Grid::Meters(size) => size * std::f64::consts::FRAC_1_SQRT_2,
+        }
+    }
+
+    /// Checks the precision or cell size. This is done automatically before every bulk search.
+    pub fn validate(&self) -> Result<(), InvalidInputError> {
+        match self {
+            Grid::Geohash(precision) if *precision == 0 || *precision > geohash::MAX_PRECISION => {
+                Err(InvalidInputError::new(&format!(
+                    "geohash precision must be between 1 and {}, got {}",
+                    geohash::MAX_PRECISION,
+                    precision
+                )))
+            }
+            Grid::Meters(size) if size.is_nan() || *size <= 0.0 => Err(InvalidInputError::new(
+                &format!("grid cell size must be greater than 0, got {}", size),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The cell of `coords` and its center.
+    fn cell(&self, coords: &LatLon) -> Result<(CellKey, LatLon), InvalidInputError> {
+        self.validate()?;
+        coords.validate()?;
+        match self {
+            Grid::Geohash(precision) => {
+                let hash = geohash::encode(coords, *precision)?;
+                let center = geohash::decode(&hash)?.center();
+                Ok((CellKey::Geohash(hash), center))
+            }
+            Grid::Meters(size) => {
+                // Rows have a constant height; the width of the columns depends on the latitude
+                // of the row, so cells keep their size in meters towards the poles.
+                let height = (size / EARTH_RADIUS).to_degrees();
+                let row = ((coords.lat + 90.0) / height).floor();
+                let center_lat = (-90.0 + (row + 0.5) * height).min(90.0);
+                let circumference = 2.0 * std::f64::consts::PI * EARTH_RADIUS;
+                let columns = (circumference * center_lat.to_radians().cos() / size)
+                    .floor()
+                    .max(1.0);
+                let width = 360.0 / columns;
+                let column = ((coords.lon + 180.0) / width).floor().min(columns - 1.0);
+                let center_lon = -180.0 + (column + 0.5) * width;
+                Ok((
+                    CellKey::Meters(row as i64, column as i64),
+                    LatLon::new(center_lat, center_lon),
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum CellKey {
+    Geohash(String),
+    Meters(i64, i64),
+}
+
+/// Configuration for [`Client::bulk_reverse_search`](crate::PhotonApiClient::bulk_reverse_search).
+/// This struct implements a builder pattern, so options can be easily constructed.
+#[derive(Debug, Clone)]
+pub struct BulkReverseOptions {
+    pub grid: Grid,
+    pub filter: Option<ReverseFilter>,
+    pub threads: usize,
+}
+
+impl Default for BulkReverseOptions {
+    fn default() -> Self {
+        BulkReverseOptions {
+            grid: Grid::Meters(50.0),
+            filter: None,
+            threads: 1,
+        }
+    }
+}
+
+impl BulkReverseOptions {
+    /// Construct new `BulkReverseOptions` with cells of 50 meters, no filter and one thread.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn grid(mut self, grid: Grid) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    /// Options for every reverse search. The limit is always 1.
+    pub fn filter(mut self, filter: ReverseFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// The number of cells that are searched in parallel.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+}
+
+/// How well cells were reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BulkReverseStats {
+    /// The number of points.
+    pub points: usize,
+    /// The number of distinct cells, which equals the number of requests.
+    pub cells: usize,
+    /// Points that were invalid and not assigned to a cell.
+    pub invalid_points: usize,
+    /// Requests that failed.
+    pub failed_requests: usize,
+}
+
+impl BulkReverseStats {
+    /// The average number of points per cell.
+    pub fn points_per_cell(&self) -> f64 {
+        if self.cells == 0 {
+            return 0.0;
+        }
+        (self.points - self.invalid_points) as f64 / self.cells as f64
+    }
+
+    /// The share of valid points that did not need a request of their own, between `0` and `1`.
+    pub fn reuse_ratio(&self) -> f64 {
+        let valid = self.points - self.invalid_points;
+        if valid == 0 {
+            return 0.0;
+        }
+        (valid - self.cells) as f64 / valid as f64
+    }
+}
+
+/// The results of a bulk reverse search. Every cell is stored once, and points refer to their cell
+/// by index.
+#[derive(Debug, Clone)]
+pub struct BulkReverseResult {
+    /// The index into `cells` of every input point, in input order, or the error of an invalid
+    /// point.
+    pub points: Vec<Result<usize, PhotonError>>,
+    /// The closest feature to the center of every cell, `None` if there is none, or the error of
+    /// the cell's request.
+    pub cells: Vec<Result<Option<PhotonFeature>, PhotonError>>,
+    pub stats: BulkReverseStats,
+}
+
+impl BulkReverseResult {
+    /// The result of the input point at `index`: the closest feature to the center of its cell,
+    /// `None` if there is none, or the error of the point or its request.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn result(&self, index: usize) -> Result<Option<&PhotonFeature>, &PhotonError> {
+        let cell = self.points[index].as_ref()?;
+        self.cells[*cell].as_ref().map(Option::as_ref)
+    }
+
+    /// The results of all input points, in input order. See [`result`](Self::result).
+    pub fn results(
+        &self,
+    ) -> impl Iterator<Item = Result<Option<&PhotonFeature>, &PhotonError>> + '_ {
+        (0..self.points.len()).map(move |index| self.result(index))
+    }
+}
+
+/// Assigns `points` to cells. Returns the cell index of every point and the center of every cell.
+pub(crate) fn snap(
+    grid: &Grid,
+    points: &[LatLon],
+) -> (Vec<Result<usize, InvalidInputError>>, Vec<LatLon>) {
+    let mut cells: HashMap<CellKey, usize> = HashMap::new();
+    let mut centers = Vec::new();
+    let assignments = points
+        .iter()
+        .map(|point| {
+            let (key, center) = grid.cell(point)?;
+            Ok(*cells.entry(key).or_insert_with(|| {
+                centers.push(center);
+                centers.len() - 1
+            }))
+        })
+        .collect();
+    (assignments, centers)
+}
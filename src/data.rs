@@ -1,19 +1,92 @@
+pub mod address;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+mod bbox;
+pub mod category;
+pub mod dedupe;
+pub mod export;
 pub mod filter;
+pub mod geo;
+pub mod geohash;
+pub mod grid;
 pub mod json;
+pub mod label;
+pub mod parse;
+pub mod pluscode;
+pub mod scoring;
+pub mod wkt;
 
+use std::cmp::Ordering;
+use std::fmt;
+
+use self::filter::PhotonLayer;
 use self::json::PhotonFeatureRaw;
+use crate::error::InvalidInputError;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct LatLon {
     pub lat: f64,
     pub lon: f64,
 }
 
+/// How [`LatLon::try_new_with`] treats longitudes outside of `[-180, 180]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongitudeHandling {
+    /// Reject the coordinate.
+    Reject,
+    /// Wrap the longitude around the globe, e.g. `190` becomes `-170`.
+    Wrap,
+    /// Clamp the longitude to the nearest valid value, e.g. `190` becomes `180`.
+    Clamp,
+}
+
 impl LatLon {
+    /// Creates a coordinate without any checks. Use [`LatLon::try_new`] for user input.
     pub fn new(lat: f64, lon: f64) -> Self {
         LatLon { lat, lon }
     }
 
+    /// Creates a coordinate, rejecting NaN, infinite and out-of-range values.
+    pub fn try_new(lat: f64, lon: f64) -> Result<Self, InvalidInputError> {
+        Self::try_new_with(lat, lon, LongitudeHandling::Reject)
+    }
+
+    /// Creates a coordinate, treating longitudes outside of `[-180, 180]` as specified by
+    /// `handling`. Latitudes must always be within `[-90, 90]`.
+    pub fn try_new_with(
+        lat: f64,
+        lon: f64,
+        handling: LongitudeHandling,
+    ) -> Result<Self, InvalidInputError> {
+        let lon = match handling {
+            _ if !lon.is_finite() => lon,
+            LongitudeHandling::Reject => lon,
+            LongitudeHandling::Wrap if (-180.0..=180.0).contains(&lon) => lon,
+            LongitudeHandling::Wrap => geo::normalize_longitude(lon),
+            LongitudeHandling::Clamp => lon.clamp(-180.0, 180.0),
+        };
+        let coords = LatLon { lat, lon };
+        coords.validate()?;
+        Ok(coords)
+    }
+
+    /// Checks that both values are finite and within their valid range.
+    pub fn validate(&self) -> Result<(), InvalidInputError> {
+        if !self.lat.is_finite() || !(-90.0..=90.0).contains(&self.lat) {
+            return Err(InvalidInputError::new(&format!(
+                "latitude must be within [-90, 90], got {}",
+                self.lat
+            )));
+        }
+        if !self.lon.is_finite() || !(-180.0..=180.0).contains(&self.lon) {
+            return Err(InvalidInputError::new(&format!(
+                "longitude must be within [-180, 180], got {}",
+                self.lon
+            )));
+        }
+        Ok(())
+    }
+
     fn from_vec(vec: &[f64]) -> Self {
         assert!(vec.len() >= 2);
         LatLon {
@@ -23,13 +96,24 @@ impl LatLon {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OsmType {
     Relation,
     Way,
     Node,
 }
 
+impl OsmType {
+    /// The single-letter code Photon uses for this type.
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            OsmType::Relation => "R",
+            OsmType::Way => "W",
+            OsmType::Node => "N",
+        }
+    }
+}
+
 impl From<String> for OsmType {
     fn from(str: String) -> Self {
         match str.as_str() {
@@ -41,27 +125,135 @@ impl From<String> for OsmType {
     }
 }
 
+/// The type of a feature, as reported by Photon.
+///
+/// Types are ordered by granularity, from the most specific (`House`) to the least specific
+/// (`Country`), so results can be compared with `<` and `>`. `Other` types are ordered before all
+/// known types and among each other by name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FeatureType {
+    House,
+    Street,
+    Locality,
+    District,
+    City,
+    County,
+    State,
+    Country,
+    Other(String),
+}
+
+impl FeatureType {
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Other(_) => 0,
+            Self::House => 1,
+            Self::Street => 2,
+            Self::Locality => 3,
+            Self::District => 4,
+            Self::City => 5,
+            Self::County => 6,
+            Self::State => 7,
+            Self::Country => 8,
+        }
+    }
+}
+
+impl PartialOrd for FeatureType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FeatureType {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Other(a), Self::Other(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl From<&str> for FeatureType {
+    fn from(str: &str) -> Self {
+        match str.parse::<PhotonLayer>() {
+            Ok(layer) => Self::from(layer),
+            Err(_) => Self::Other(str.to_string()),
+        }
+    }
+}
+
+impl From<PhotonLayer> for FeatureType {
+    fn from(layer: PhotonLayer) -> Self {
+        match layer {
+            PhotonLayer::House => Self::House,
+            PhotonLayer::Street => Self::Street,
+            PhotonLayer::Locality => Self::Locality,
+            PhotonLayer::District => Self::District,
+            PhotonLayer::City => Self::City,
+            PhotonLayer::County => Self::County,
+            PhotonLayer::State => Self::State,
+            PhotonLayer::Country => Self::Country,
+        }
+    }
+}
+
+impl TryFrom<FeatureType> for PhotonLayer {
+    type Error = InvalidInputError;
+
+    fn try_from(feature_type: FeatureType) -> Result<Self, Self::Error> {
+        match feature_type {
+            FeatureType::House => Ok(Self::House),
+            FeatureType::Street => Ok(Self::Street),
+            FeatureType::Locality => Ok(Self::Locality),
+            FeatureType::District => Ok(Self::District),
+            FeatureType::City => Ok(Self::City),
+            FeatureType::County => Ok(Self::County),
+            FeatureType::State => Ok(Self::State),
+            FeatureType::Country => Ok(Self::Country),
+            FeatureType::Other(name) => Err(InvalidInputError::new(&format!(
+                "feature type '{}' has no layer",
+                name
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for FeatureType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Other(name) => write!(f, "{}", name),
+            _ => match PhotonLayer::try_from(self.clone()) {
+                Ok(layer) => write!(f, "{}", layer),
+                Err(_) => Ok(()),
+            },
+        }
+    }
+}
+
 /// A bounding box, described by two corner coordinates: south_west (min) and north_east (max).
-/// Semantically, south_west's coordinates are always smaller than north_east's, though this
-/// constraint is not enforced.
-#[derive(Debug)]
+/// Semantically, south_west's latitude is always smaller than north_east's, though this
+/// constraint is only enforced by [`BoundingBox::try_new`]. A south_west longitude greater than
+/// the north_east longitude describes a box crossing the antimeridian.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct BoundingBox {
     pub south_west: LatLon,
     pub north_east: LatLon,
 }
 
+/// Converts an extent in Photon's format, `[min_lon, max_lat, max_lon, min_lat]`.
 impl From<Vec<f64>> for BoundingBox {
     fn from(vec: Vec<f64>) -> Self {
         assert!(vec.len() >= 4);
 
         BoundingBox {
-            south_west: LatLon::from_vec(&vec[0..2]),
-            north_east: LatLon::from_vec(&vec[2..4]),
+            south_west: LatLon::new(vec[3], vec[0]),
+            north_east: LatLon::new(vec[1], vec[2]),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PhotonFeature {
     pub coords: LatLon,
 
@@ -109,3 +301,10 @@ impl From<PhotonFeatureRaw> for PhotonFeature {
         }
     }
 }
+
+impl PhotonFeature {
+    /// The typed variant of `r#type`.
+    pub fn feature_type(&self) -> FeatureType {
+        FeatureType::from(self.r#type.as_str())
+    }
+}
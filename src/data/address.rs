@@ -0,0 +1,511 @@
+//! Structured addresses and an offline, rule-based parser for single-line addresses.
+//!
+//! [`parse_address`] splits input such as `Marienplatz 8, 80331 München` or
+//! `10 Downing Street, London SW1A 2AA` into its components. It knows the postcode and house
+//! number conventions of Germany, Austria, Switzerland, France, the United Kingdom and the
+//! United States, and works best when the parts of the address are separated by commas.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::InvalidInputError;
+
+/// Country names the parser recognises at the end of an address, with their ISO 3166-1 alpha-2
+/// codes.
+const COUNTRIES: &[(&str, &[&str])] = &[
+    ("DE", &["germany", "deutschland", "allemagne"]),
+    ("AT", &["austria", "österreich", "oesterreich", "autriche"]),
+    ("CH", &["switzerland", "schweiz", "suisse", "svizzera"]),
+    ("FR", &["france", "frankreich"]),
+    (
+        "GB",
+        &[
+            "united kingdom",
+            "uk",
+            "great britain",
+            "england",
+            "scotland",
+            "wales",
+            "northern ireland",
+        ],
+    ),
+    (
+        "US",
+        &[
+            "united states",
+            "united states of america",
+            "usa",
+            "vereinigte staaten",
+        ],
+    ),
+];
+
+/// Country prefixes in front of postcodes, as in `D-80331` or `CH-8001`.
+const POSTCODE_PREFIXES: &[(&str, &str)] = &[
+    ("D", "DE"),
+    ("DE", "DE"),
+    ("A", "AT"),
+    ("AT", "AT"),
+    ("CH", "CH"),
+    ("F", "FR"),
+    ("FR", "FR"),
+];
+
+const US_STATES: [&str; 51] = [
+    "AL", "AK", "AZ", "AR", "CA", "CO", "CT", "DE", "DC", "FL", "GA", "HI", "ID", "IL", "IN", "IA",
+    "KS", "KY", "LA", "ME", "MD", "MA", "MI", "MN", "MS", "MO", "MT", "NE", "NV", "NH", "NJ", "NM",
+    "NY", "NC", "ND", "OH", "OK", "OR", "PA", "RI", "SC", "SD", "TN", "TX", "UT", "VT", "VA", "WA",
+    "WV", "WI", "WY",
+];
+
+/// French house number suffixes, as in `12 bis rue de Rivoli`.
+const HOUSE_NUMBER_SUFFIXES: [&str; 3] = ["bis", "ter", "quater"];
+
+/// An address split into its components. This struct implements a builder pattern, so addresses
+/// can be easily constructed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StructuredAddress {
+    pub name: Option<String>,
+    pub street: Option<String>,
+    pub house_number: Option<String>,
+    pub postcode: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    /// Country name or ISO 3166-1 alpha-2 code.
+    pub country: Option<String>,
+}
+
+impl StructuredAddress {
+    /// Construct a new `StructuredAddress`. All fields are set to `None` in the beginning.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn street(mut self, street: &str) -> Self {
+        self.street = Some(street.to_string());
+        self
+    }
+
+    pub fn house_number(mut self, house_number: &str) -> Self {
+        self.house_number = Some(house_number.to_string());
+        self
+    }
+
+    pub fn postcode(mut self, postcode: &str) -> Self {
+        self.postcode = Some(postcode.to_string());
+        self
+    }
+
+    pub fn city(mut self, city: &str) -> Self {
+        self.city = Some(city.to_string());
+        self
+    }
+
+    pub fn state(mut self, state: &str) -> Self {
+        self.state = Some(state.to_string());
+        self
+    }
+
+    pub fn country(mut self, country: &str) -> Self {
+        self.country = Some(country.to_string());
+        self
+    }
+
+    /// Whether no component is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for StructuredAddress {
+    /// Formats the address as a free-text query, e.g. `Marienplatz 8, 80331 München`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |parts: &[&Option<String>]| {
+            parts
+                .iter()
+                .filter_map(|part| part.as_deref())
+                .filter(|part| !part.trim().is_empty())
+                .collect::<Vec<&str>>()
+                .join(" ")
+        };
+        let groups = [
+            join(&[&self.name]),
+            join(&[&self.street, &self.house_number]),
+            join(&[&self.postcode, &self.city]),
+            join(&[&self.state]),
+            join(&[&self.country]),
+        ];
+        let text = groups
+            .iter()
+            .filter(|group| !group.is_empty())
+            .cloned()
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{}", text)
+    }
+}
+
+impl FromStr for StructuredAddress {
+    type Err = InvalidInputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_address(s)
+    }
+}
+
+/// Splits a single-line address into its components.
+///
+/// Countries are returned as ISO 3166-1 alpha-2 codes. They are taken from a country name or code
+/// at the end of the input, from a postcode prefix such as `D-80331`, or from the format of UK
+/// postcodes and US state and ZIP code pairs. Parts that cannot be assigned with confidence are
+/// left out rather than guessed, so e.g. a US address without commas yields no city.
+pub fn parse_address(input: &str) -> Result<StructuredAddress, InvalidInputError> {
+    let mut segments = split_segments(input);
+    if segments.is_empty() {
+        return Err(InvalidInputError::new("address is empty"));
+    }
+
+    let mut address = StructuredAddress::new();
+    let mut country = take_country(&mut segments);
+    if segments.is_empty() {
+        address.country = country.map(String::from);
+        return Ok(address);
+    }
+
+    // Segments that are left for name, street and state, and the position of the city among them.
+    let mut rest: Vec<Vec<String>> = Vec::new();
+    let mut city_position = None;
+    let mut street_tail = Vec::new();
+
+    if let Some(found) = find_postcode(&segments) {
+        address.postcode = Some(found.postcode);
+        address.state = found.state;
+        country = country.or(found.country);
+
+        let tokens = &segments[found.segment];
+        let before = &tokens[..found.start - usize::from(address.state.is_some())];
+        let after = &tokens[found.end..];
+        rest.extend(segments[..found.segment].iter().cloned());
+        if !after.is_empty() {
+            // Postcode before city, as in `80331 München`.
+            address.city = Some(after.join(" "));
+            if !before.is_empty() {
+                rest.push(before.to_vec());
+            }
+        } else if !before.is_empty() && !before.iter().any(|t| is_house_number(t)) {
+            // City before postcode, as in `London SW1A 2AA`.
+            address.city = Some(before.join(" "));
+        } else {
+            if !before.is_empty() {
+                rest.push(before.to_vec());
+            }
+            // City in its own segment, as in `Springfield, IL 62701`.
+            if rest
+                .last()
+                .map_or(false, |segment| !has_house_number(segment))
+            {
+                address.city = rest.pop().map(|segment| segment.join(" "));
+            }
+        }
+        city_position = Some(rest.len());
+        rest.extend(segments[found.segment + 1..].iter().cloned());
+    } else {
+        rest = segments;
+        if rest.last().map_or(false, |last| is_us_state(last)) && rest.len() > 1 {
+            address.state = rest.pop().map(|segment| segment.join(" "));
+            country = country.or(Some("US"));
+        }
+        let numbered = rest.iter().rposition(|segment| has_house_number(segment));
+        let position = match numbered {
+            Some(position) => position + 1,
+            None if rest.len() == 1 => 0,
+            None => rest.len() - 1,
+        };
+        if position < rest.len() {
+            address.city = Some(rest.remove(position).join(" "));
+            city_position = Some(position);
+        }
+    }
+
+    let city_position = city_position.unwrap_or(rest.len());
+    let street_position = rest[..city_position]
+        .iter()
+        .rposition(|segment| has_house_number(segment))
+        .or_else(|| city_position.checked_sub(1));
+    if let Some(position) = street_position {
+        let (street, house_number, tail) = split_street(&rest[position]);
+        address.street = street;
+        address.house_number = house_number;
+        street_tail = tail;
+        if position > 0 {
+            address.name = Some(
+                rest[..position]
+                    .iter()
+                    .map(|segment| segment.join(" "))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            );
+        }
+    }
+    if address.city.is_none() && !street_tail.is_empty() {
+        // Street and city without a comma in between, as in `Marienplatz 8 München`.
+        address.city = Some(street_tail.join(" "));
+    }
+    if address.state.is_none() {
+        address.state = rest.get(city_position).map(|segment| segment.join(" "));
+    }
+    address.country = country.map(String::from);
+    Ok(address)
+}
+
+struct PostcodeMatch {
+    segment: usize,
+    start: usize,
+    end: usize,
+    postcode: String,
+    country: Option<&'static str>,
+    state: Option<String>,
+}
+
+fn split_segments(input: &str) -> Vec<Vec<String>> {
+    let mut segments: Vec<Vec<String>> = Vec::new();
+    let mut pending_number: Option<String> = None;
+    for segment in input.split([',', ';', '\n']) {
+        let mut tokens: Vec<String> = segment.split_whitespace().map(String::from).collect();
+        if tokens.is_empty() {
+            continue;
+        }
+        if let Some(number) = pending_number.take() {
+            tokens.insert(0, number);
+        }
+        // A house number in its own segment belongs to the street, as in `12, rue de Rivoli`.
+        if tokens.len() == 1 && is_house_number(&tokens[0]) && tokens[0].len() < 4 {
+            pending_number = tokens.pop();
+            continue;
+        }
+        segments.push(tokens);
+    }
+    if let Some(number) = pending_number {
+        segments.push(vec![number]);
+    }
+    segments
+}
+
+/// Removes a country name or code from the end of the address.
+fn take_country(segments: &mut Vec<Vec<String>>) -> Option<&'static str> {
+    let last = segments.last()?;
+    let whole = normalize_country(&last.join(" "));
+    if let Some(code) = country_code(&whole) {
+        segments.pop();
+        return Some(code);
+    }
+    if whole.len() == 2 {
+        let code = whole.to_uppercase();
+        if let Some((code, _)) = COUNTRIES.iter().find(|(c, _)| *c == code) {
+            segments.pop();
+            return Some(code);
+        }
+    }
+
+    // A country name after the city, as in `75001 Paris France`.
+    for length in (1..=4).rev() {
+        if last.len() <= length {
+            continue;
+        }
+        let name = normalize_country(&last[last.len() - length..].join(" "));
+        if let Some(code) = country_code(&name) {
+            let last = segments.last_mut()?;
+            last.truncate(last.len() - length);
+            return Some(code);
+        }
+    }
+    None
+}
+
+fn normalize_country(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '.')
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn country_code(name: &str) -> Option<&'static str> {
+    COUNTRIES
+        .iter()
+        .find(|(_, names)| names.contains(&name))
+        .map(|(code, _)| *code)
+}
+
+fn find_postcode(segments: &[Vec<String>]) -> Option<PostcodeMatch> {
+    let positions = || {
+        segments
+            .iter()
+            .enumerate()
+            .flat_map(|(s, tokens)| (0..tokens.len()).map(move |t| (s, t)))
+            .collect::<Vec<(usize, usize)>>()
+            .into_iter()
+            .rev()
+    };
+    let found = |segment, start, end, postcode: String, country| PostcodeMatch {
+        segment,
+        start,
+        end,
+        postcode,
+        country,
+        state: None,
+    };
+
+    // UK postcodes, as in `SW1A 2AA` or `SW1A2AA`.
+    for (s, t) in positions() {
+        let token = &segments[s][t];
+        if t > 0 && is_uk_inward(token) && is_uk_outward(&segments[s][t - 1]) {
+            let postcode = format!("{} {}", segments[s][t - 1], token).to_uppercase();
+            return Some(found(s, t - 1, t + 1, postcode, Some("GB")));
+        }
+        let uppercase = token.chars().all(|c| !c.is_lowercase());
+        if uppercase && token.len() >= 5 && token.len() <= 7 && token.is_ascii() {
+            let (outward, inward) = token.split_at(token.len() - 3);
+            if is_uk_outward(outward) && is_uk_inward(inward) {
+                let postcode = format!("{} {}", outward, inward);
+                return Some(found(s, t, t + 1, postcode, Some("GB")));
+            }
+        }
+    }
+
+    // Postcodes with a country prefix, as in `D-80331`.
+    for (s, t) in positions() {
+        if let Some((prefix, code)) = segments[s][t].split_once('-') {
+            let country = POSTCODE_PREFIXES
+                .iter()
+                .find(|(p, _)| p.eq_ignore_ascii_case(prefix))
+                .map(|(_, country)| *country);
+            if let (Some(country), true) = (country, is_numeric_postcode(code)) {
+                return Some(found(s, t, t + 1, code.to_string(), Some(country)));
+            }
+        }
+    }
+
+    // US ZIP codes after the state, as in `CA 94043` or `CA 94043-1351`.
+    for (s, t) in positions() {
+        let token = &segments[s][t];
+        if t > 0 && is_zip_code(token) && US_STATES.contains(&segments[s][t - 1].as_str()) {
+            let mut zip = found(s, t, t + 1, token.clone(), Some("US"));
+            zip.state = Some(segments[s][t - 1].clone());
+            return Some(zip);
+        }
+    }
+
+    // Plain postcodes with four or five digits. A leading number is a house number in front of the
+    // street, unless the address is too short to contain a street.
+    for (s, t) in positions() {
+        let token = &segments[s][t];
+        let leading = s == 0 && t == 0 && (segments.len() > 1 || segments[0].len() > 3);
+        if !leading && is_numeric_postcode(token) {
+            return Some(found(s, t, t + 1, token.clone(), None));
+        }
+    }
+    None
+}
+
+fn is_numeric_postcode(token: &str) -> bool {
+    (token.len() == 4 || token.len() == 5) && token.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_zip_code(token: &str) -> bool {
+    let (zip, extension) = token.split_once('-').unwrap_or((token, "0000"));
+    zip.len() == 5
+        && extension.len() == 4
+        && zip
+            .chars()
+            .chain(extension.chars())
+            .all(|c| c.is_ascii_digit())
+}
+
+fn is_us_state(segment: &[String]) -> bool {
+    segment.len() == 1 && US_STATES.contains(&segment[0].as_str())
+}
+
+/// The outward part of a UK postcode: one or two letters, a digit and an optional letter or digit.
+fn is_uk_outward(token: &str) -> bool {
+    let chars: Vec<char> = token.to_uppercase().chars().collect();
+    let letters = chars.iter().take_while(|c| c.is_ascii_alphabetic()).count();
+    (letters == 1 || letters == 2)
+        && chars.len() > letters
+        && chars.len() <= letters + 2
+        && chars[letters].is_ascii_digit()
+        && chars[letters + 1..]
+            .iter()
+            .all(|c| c.is_ascii_alphanumeric())
+}
+
+/// The inward part of a UK postcode: a digit and two letters.
+fn is_uk_inward(token: &str) -> bool {
+    let chars: Vec<char> = token.chars().collect();
+    chars.len() == 3
+        && chars[0].is_ascii_digit()
+        && chars[1..].iter().all(|c| c.is_ascii_alphabetic())
+}
+
+/// Whether `token` looks like a house number, such as `8`, `12a`, `4-6` or `12/3`.
+fn is_house_number(token: &str) -> bool {
+    let part = |part: &str| {
+        let digits = part.chars().take_while(char::is_ascii_digit).count();
+        let suffix = &part[digits..];
+        (1..=5).contains(&digits)
+            && suffix.chars().count() <= 1
+            && suffix.chars().all(char::is_alphabetic)
+    };
+    match token.split_once(['-', '/']) {
+        Some((first, second)) => part(first) && part(second),
+        None => part(token),
+    }
+}
+
+fn has_house_number(segment: &[String]) -> bool {
+    segment.iter().any(|token| is_house_number(token))
+}
+
+/// Splits a segment into street, house number and the words after the house number.
+fn split_street(tokens: &[String]) -> (Option<String>, Option<String>, Vec<String>) {
+    let number_with_suffix = |position: usize| -> (String, usize) {
+        let number = tokens[position].clone();
+        match tokens.get(position + 1) {
+            Some(suffix) if HOUSE_NUMBER_SUFFIXES.contains(&suffix.to_lowercase().as_str()) => {
+                (format!("{} {}", number, suffix), position + 2)
+            }
+            Some(suffix)
+                if suffix.chars().count() == 1 && suffix.chars().all(char::is_alphabetic) =>
+            {
+                (format!("{}{}", number, suffix), position + 2)
+            }
+            _ => (number, position + 1),
+        }
+    };
+    let join = |tokens: &[String]| Some(tokens.join(" ")).filter(|s| !s.is_empty());
+
+    if tokens.len() > 1 && is_house_number(&tokens[0]) {
+        // House number first, as in `10 Downing Street` or `12 rue de Rivoli`.
+        let (number, end) = number_with_suffix(0);
+        return (join(&tokens[end..]), Some(number), Vec::new());
+    }
+    match tokens.iter().rposition(|token| is_house_number(token)) {
+        Some(position) if position > 0 => {
+            // Street first, as in `Marienplatz 8`.
+            let (number, end) = number_with_suffix(position);
+            (
+                join(&tokens[..position]),
+                Some(number),
+                tokens[end..].to_vec(),
+            )
+        }
+        _ => (join(tokens), None, Vec::new()),
+    }
+}
@@ -0,0 +1,241 @@
+//! Conversion of features into [Arrow](https://arrow.apache.org) record batches and
+//! [Parquet](https://parquet.apache.org) files, e.g. for DuckDB or Spark. Needs the `arrow`
+//! feature.
+//!
+//! Every feature is a row, together with the key of the query it was found for. The columns are
+//! the same for every batch, see [`schema`]. Address fields are nullable, and the extent is split
+//! into four nullable float columns.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+
+use crate::data::PhotonFeature;
+
+/// How the values of a column are taken from a feature and the key of its query.
+#[derive(Clone, Copy)]
+enum Values {
+    Text(fn(&str, &PhotonFeature) -> Option<String>),
+    Id(fn(&PhotonFeature) -> u64),
+    Float(fn(&PhotonFeature) -> Option<f64>),
+}
+
+impl Values {
+    fn data_type(&self) -> DataType {
+        match self {
+            Values::Text(_) => DataType::Utf8,
+            Values::Id(_) => DataType::UInt64,
+            Values::Float(_) => DataType::Float64,
+        }
+    }
+
+    fn array(&self, rows: &[(&str, &PhotonFeature)]) -> ArrayRef {
+        match self {
+            Values::Text(value) => Arc::new(
+                rows.iter()
+                    .map(|(query, feature)| value(query, feature))
+                    .collect::<StringArray>(),
+            ),
+            Values::Id(value) => Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|(_, feature)| value(feature)),
+            )),
+            Values::Float(value) => Arc::new(
+                rows.iter()
+                    .map(|(_, feature)| value(feature))
+                    .collect::<Float64Array>(),
+            ),
+        }
+    }
+}
+
+/// The name, nullability and values of every column, in order.
+const COLUMNS: [(&str, bool, Values); 23] = [
+    (
+        "query",
+        false,
+        Values::Text(|query, _| Some(query.to_string())),
+    ),
+    ("osm_id", false, Values::Id(|feature| feature.osm_id)),
+    (
+        "osm_type",
+        false,
+        Values::Text(|_, feature| Some(String::from(feature.osm_type.code()))),
+    ),
+    (
+        "osm_key",
+        false,
+        Values::Text(|_, feature| Some(feature.osm_key.clone())),
+    ),
+    (
+        "osm_value",
+        false,
+        Values::Text(|_, feature| Some(feature.osm_value.clone())),
+    ),
+    (
+        "type",
+        false,
+        Values::Text(|_, feature| Some(feature.r#type.clone())),
+    ),
+    (
+        "latitude",
+        false,
+        Values::Float(|feature| Some(feature.coords.lat)),
+    ),
+    (
+        "longitude",
+        false,
+        Values::Float(|feature| Some(feature.coords.lon)),
+    ),
+    (
+        "name",
+        true,
+        Values::Text(|_, feature| feature.name.clone()),
+    ),
+    (
+        "house_number",
+        true,
+        Values::Text(|_, feature| feature.house_number.clone()),
+    ),
+    (
+        "street",
+        true,
+        Values::Text(|_, feature| feature.street.clone()),
+    ),
+    (
+        "postcode",
+        true,
+        Values::Text(|_, feature| feature.postcode.clone()),
+    ),
+    (
+        "district",
+        true,
+        Values::Text(|_, feature| feature.district.clone()),
+    ),
+    (
+        "city",
+        true,
+        Values::Text(|_, feature| feature.city.clone()),
+    ),
+    (
+        "county",
+        true,
+        Values::Text(|_, feature| feature.county.clone()),
+    ),
+    (
+        "state",
+        true,
+        Values::Text(|_, feature| feature.state.clone()),
+    ),
+    (
+        "country",
+        true,
+        Values::Text(|_, feature| feature.country.clone()),
+    ),
+    (
+        "country_code",
+        true,
+        Values::Text(|_, feature| feature.country_iso_code.clone()),
+    ),
+    (
+        "extent_min_lat",
+        true,
+        Values::Float(|feature| feature.extent.map(|e| e.south_west.lat)),
+    ),
+    (
+        "extent_min_lon",
+        true,
+        Values::Float(|feature| feature.extent.map(|e| e.south_west.lon)),
+    ),
+    (
+        "extent_max_lat",
+        true,
+        Values::Float(|feature| feature.extent.map(|e| e.north_east.lat)),
+    ),
+    (
+        "extent_max_lon",
+        true,
+        Values::Float(|feature| feature.extent.map(|e| e.north_east.lon)),
+    ),
+    (
+        "label",
+        false,
+        Values::Text(|_, feature| Some(feature.label())),
+    ),
+];
+
+/// The schema of all record batches created by this module: the query key, the OSM object, the
+/// coordinates, the address fields, the extent and the one-line label of each feature.
+pub fn schema() -> SchemaRef {
+    Arc::new(Schema::new(
+        COLUMNS
+            .iter()
+            .map(|(name, nullable, values)| Field::new(*name, values.data_type(), *nullable))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Converts features and the keys of the queries they were found for into a record batch with
+/// the columns of [`schema`].
+pub fn to_record_batch<'a, I>(features: I) -> Result<RecordBatch, ArrowError>
+where
+    I: IntoIterator<Item = (&'a str, &'a PhotonFeature)>,
+{
+    let rows: Vec<(&str, &PhotonFeature)> = features.into_iter().collect();
+    let columns: Vec<ArrayRef> = COLUMNS
+        .iter()
+        .map(|(_, _, values)| values.array(&rows))
+        .collect();
+    RecordBatch::try_new(schema(), columns)
+}
+
+/// Writes features in batches into a Parquet file with the columns of [`schema`].
+pub struct ParquetWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+}
+
+impl ParquetWriter<File> {
+    /// Creates the Parquet file at `path`, replacing an existing file.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        Self::new(File::create(path)?)
+    }
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    pub fn new(output: W) -> Result<Self, Box<dyn Error>> {
+        Ok(ParquetWriter {
+            writer: ArrowWriter::try_new(output, schema(), None)?,
+        })
+    }
+
+    /// Adds a batch of features and the keys of the queries they were found for.
+    pub fn write<'a, I>(&mut self, features: I) -> Result<(), Box<dyn Error>>
+    where
+        I: IntoIterator<Item = (&'a str, &'a PhotonFeature)>,
+    {
+        self.writer.write(&to_record_batch(features)?)?;
+        Ok(())
+    }
+
+    /// Writes the remaining rows and the footer. The file is incomplete without it.
+    pub fn close(self) -> Result<(), Box<dyn Error>> {
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+/// Writes features and the keys of the queries they were found for into a new Parquet file at
+/// `path`.
+pub fn write_parquet<'a, I>(path: impl AsRef<Path>, features: I) -> Result<(), Box<dyn Error>>
+where
+    I: IntoIterator<Item = (&'a str, &'a PhotonFeature)>,
+{
+    let mut writer = ParquetWriter::create(path)?;
+    writer.write(features)?;
+    writer.close()
+}
@@ -0,0 +1,270 @@
+use crate::error::InvalidInputError;
+use crate::geo::EARTH_RADIUS;
+use crate::{BoundingBox, LatLon};
+
+const FULL_CIRCLE: f64 = 360.0;
+
+impl BoundingBox {
+    /// Creates a bounding box after checking that both corners are valid coordinates and that
+    /// `south_west` is not north of `north_east`.
+    ///
+    /// A `south_west` longitude greater than the `north_east` longitude describes a box that
+    /// crosses the antimeridian.
+    pub fn try_new(south_west: LatLon, north_east: LatLon) -> Result<Self, InvalidInputError> {
+        let bbox = BoundingBox {
+            south_west,
+            north_east,
+        };
+        bbox.validate()?;
+        Ok(bbox)
+    }
+
+    /// Checks that both corners are valid coordinates and that `south_west` is not north of
+    /// `north_east`.
+    pub fn validate(&self) -> Result<(), InvalidInputError> {
+        self.south_west.validate()?;
+        self.north_east.validate()?;
+        if self.south_west.lat > self.north_east.lat {
+            return Err(InvalidInputError::new(&format!(
+                "south-west latitude {} lies north of north-east latitude {}",
+                self.south_west.lat, self.north_east.lat
+            )));
+        }
+        Ok(())
+    }
+
+    /// Creates the smallest bounding box containing the circle of `radius` meters around `center`.
+    ///
+    /// If the circle reaches a pole, the box covers all longitudes.
+    pub fn from_center(center: LatLon, radius: f64) -> Result<Self, InvalidInputError> {
+        center.validate()?;
+        if !radius.is_finite() || radius < 0.0 {
+            return Err(InvalidInputError::new(&format!(
+                "radius must be a non-negative number, got {}",
+                radius
+            )));
+        }
+
+        let angular = radius / EARTH_RADIUS;
+        let south = center.lat - angular.to_degrees();
+        let north = center.lat + angular.to_degrees();
+        if south <= -90.0 || north >= 90.0 {
+            return Ok(full_width(south.max(-90.0), north.min(90.0)));
+        }
+
+        let ratio = angular.sin() / center.lat.to_radians().cos();
+        if ratio >= 1.0 {
+            return Ok(full_width(south, north));
+        }
+        let delta_lon = ratio.asin().to_degrees();
+        Ok(from_arc(
+            south,
+            north,
+            center.lon - delta_lon,
+            2.0 * delta_lon,
+        ))
+    }
+
+    /// Whether the box crosses the antimeridian, i.e. its west edge lies east of its east edge.
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.south_west.lon > self.north_east.lon
+    }
+
+    /// Width of the box in degrees of longitude.
+    pub fn width_degrees(&self) -> f64 {
+        if self.crosses_antimeridian() {
+            self.north_east.lon - self.south_west.lon + FULL_CIRCLE
+        } else {
+            self.north_east.lon - self.south_west.lon
+        }
+    }
+
+    /// Height of the box in degrees of latitude.
+    pub fn height_degrees(&self) -> f64 {
+        self.north_east.lat - self.south_west.lat
+    }
+
+    /// Whether `point` lies inside the box or on its border.
+    pub fn contains(&self, point: &LatLon) -> bool {
+        if point.lat < self.south_west.lat || point.lat > self.north_east.lat {
+            return false;
+        }
+        if self.crosses_antimeridian() {
+            point.lon >= self.south_west.lon || point.lon <= self.north_east.lon
+        } else {
+            point.lon >= self.south_west.lon && point.lon <= self.north_east.lon
+        }
+    }
+
+    /// Whether `other` lies completely inside this box.
+    pub fn contains_box(&self, other: &BoundingBox) -> bool {
+        if other.south_west.lat < self.south_west.lat || other.north_east.lat > self.north_east.lat
+        {
+            return false;
+        }
+        if is_full_width(self) {
+            return true;
+        }
+        if is_full_width(other) {
+            return false;
+        }
+
+        let (west, width) = self.arc();
+        let (other_west, other_width) = other.arc();
+        [-FULL_CIRCLE, 0.0, FULL_CIRCLE].iter().any(|shift| {
+            other_west + shift >= west && other_west + shift + other_width <= west + width
+        })
+    }
+
+    /// The center of the box in degrees. For boxes crossing the antimeridian, the center lies on
+    /// the antimeridian side.
+    pub fn center(&self) -> LatLon {
+        let lat = (self.south_west.lat + self.north_east.lat) / 2.0;
+        let lon = wrap_lon(self.south_west.lon + self.width_degrees() / 2.0);
+        LatLon::new(lat, lon)
+    }
+
+    /// Surface area of the box on a spherical earth in square meters.
+    pub fn area(&self) -> f64 {
+        let lat_term =
+            self.north_east.lat.to_radians().sin() - self.south_west.lat.to_radians().sin();
+        EARTH_RADIUS * EARTH_RADIUS * lat_term.abs() * self.width_degrees().to_radians()
+    }
+
+    /// Returns a box that is `margin` meters larger on every side.
+    ///
+    /// Latitudes are clamped at the poles. If the box then touches a pole, or becomes wider than
+    /// the whole earth, it covers all longitudes.
+    pub fn expand(&self, margin: f64) -> BoundingBox {
+        let delta_lat = (margin / EARTH_RADIUS).to_degrees();
+        let south = (self.south_west.lat - delta_lat).max(-90.0);
+        let north = (self.north_east.lat + delta_lat).min(90.0);
+        if south <= -90.0 || north >= 90.0 {
+            return full_width(south, north);
+        }
+
+        // The longitude margin is largest at the edge closest to a pole.
+        let widest_lat = south.abs().max(north.abs());
+        let delta_lon = (margin / (EARTH_RADIUS * widest_lat.to_radians().cos())).to_degrees();
+        from_arc(
+            south,
+            north,
+            self.south_west.lon - delta_lon,
+            self.width_degrees() + 2.0 * delta_lon,
+        )
+    }
+
+    /// The area covered by both boxes, or `None` if they do not overlap.
+    ///
+    /// If two boxes crossing the antimeridian overlap in two separate areas, the larger one is
+    /// returned.
+    pub fn intersection(&self, other: &BoundingBox) -> Option<BoundingBox> {
+        let south = self.south_west.lat.max(other.south_west.lat);
+        let north = self.north_east.lat.min(other.north_east.lat);
+        if south > north {
+            return None;
+        }
+        if is_full_width(self) {
+            return Some(from_arc(
+                south,
+                north,
+                other.south_west.lon,
+                other.width_degrees(),
+            ));
+        }
+        if is_full_width(other) {
+            return Some(from_arc(
+                south,
+                north,
+                self.south_west.lon,
+                self.width_degrees(),
+            ));
+        }
+
+        let (west, width) = self.arc();
+        let (other_west, other_width) = other.arc();
+        [-FULL_CIRCLE, 0.0, FULL_CIRCLE]
+            .iter()
+            .filter_map(|shift| {
+                let start = west.max(other_west + shift);
+                let end = (west + width).min(other_west + shift + other_width);
+                if start <= end {
+                    Some((start, end - start))
+                } else {
+                    None
+                }
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(start, width)| from_arc(south, north, start, width))
+    }
+
+    /// The smallest box containing both boxes.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let south = self.south_west.lat.min(other.south_west.lat);
+        let north = self.north_east.lat.max(other.north_east.lat);
+        if is_full_width(self) || is_full_width(other) {
+            return full_width(south, north);
+        }
+
+        let (west, width) = self.arc();
+        let (other_west, other_width) = other.arc();
+        let (start, width) = [-FULL_CIRCLE, 0.0, FULL_CIRCLE]
+            .iter()
+            .map(|shift| {
+                let start = west.min(other_west + shift);
+                let end = (west + width).max(other_west + shift + other_width);
+                (start, end - start)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((west, width));
+        from_arc(south, north, start, width)
+    }
+
+    /// The corners as a closed, counter-clockwise ring of `(lon, lat)` positions, starting and
+    /// ending in the south-west.
+    pub(crate) fn ring(&self) -> [(f64, f64); 5] {
+        let (south_west, north_east) = (&self.south_west, &self.north_east);
+        [
+            (south_west.lon, south_west.lat),
+            (north_east.lon, south_west.lat),
+            (north_east.lon, north_east.lat),
+            (south_west.lon, north_east.lat),
+            (south_west.lon, south_west.lat),
+        ]
+    }
+
+    fn arc(&self) -> (f64, f64) {
+        (self.south_west.lon, self.width_degrees())
+    }
+}
+
+fn is_full_width(bbox: &BoundingBox) -> bool {
+    bbox.width_degrees() >= FULL_CIRCLE
+}
+
+fn full_width(south: f64, north: f64) -> BoundingBox {
+    BoundingBox {
+        south_west: LatLon::new(south, -180.0),
+        north_east: LatLon::new(north, 180.0),
+    }
+}
+
+/// Builds a box from a longitude arc that starts at `west` and spans `width` degrees eastwards.
+fn from_arc(south: f64, north: f64, west: f64, width: f64) -> BoundingBox {
+    if width >= FULL_CIRCLE {
+        return full_width(south, north);
+    }
+    BoundingBox {
+        south_west: LatLon::new(south, wrap_lon(west)),
+        north_east: LatLon::new(north, wrap_lon(west + width)),
+    }
+}
+
+/// Maps a longitude onto `[-180, 180]`, leaving values that are already in range untouched.
+fn wrap_lon(lon: f64) -> f64 {
+    if (-180.0..=180.0).contains(&lon) {
+        lon
+    } else {
+        crate::geo::normalize_longitude(lon)
+    }
+}
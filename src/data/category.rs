@@ -0,0 +1,483 @@
+//! Typed categories for common OpenStreetMap `key=value` tags.
+//!
+//! Every [`Category`] belongs to a [`CategoryGroup`]. Features can be classified with
+//! [`PhotonFeature::category`], and searches can be restricted to categories with
+//! [`ForwardFilter::categories`](crate::filter::ForwardFilter::categories) and
+//! [`ForwardFilter::category_groups`](crate::filter::ForwardFilter::category_groups).
+
+use crate::PhotonFeature;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CategoryGroup {
+    Amenity,
+    Shop,
+    Transport,
+    Natural,
+    Tourism,
+    Leisure,
+    Place,
+    Road,
+    Building,
+}
+
+impl CategoryGroup {
+    /// The OSM keys whose values all belong to this group.
+    pub fn osm_keys(&self) -> &'static [&'static str] {
+        match self {
+            Self::Amenity => &["amenity"],
+            Self::Shop => &["shop"],
+            Self::Transport => &["railway", "public_transport", "aeroway"],
+            Self::Natural => &["natural", "water", "waterway"],
+            Self::Tourism => &["tourism"],
+            Self::Leisure => &["leisure"],
+            Self::Place => &["place"],
+            Self::Road => &["highway"],
+            Self::Building => &["building"],
+        }
+    }
+
+    /// The group of all features with the OSM key `key`.
+    pub fn from_osm_key(key: &str) -> Option<Self> {
+        ALL_GROUPS
+            .iter()
+            .find(|group| group.osm_keys().contains(&key))
+            .copied()
+    }
+}
+
+const ALL_GROUPS: [CategoryGroup; 9] = [
+    CategoryGroup::Amenity,
+    CategoryGroup::Shop,
+    CategoryGroup::Transport,
+    CategoryGroup::Natural,
+    CategoryGroup::Tourism,
+    CategoryGroup::Leisure,
+    CategoryGroup::Place,
+    CategoryGroup::Road,
+    CategoryGroup::Building,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    // Amenity
+    Restaurant,
+    Cafe,
+    FastFood,
+    Bar,
+    Pub,
+    Hospital,
+    Clinic,
+    Doctors,
+    Pharmacy,
+    School,
+    University,
+    Kindergarten,
+    Library,
+    Bank,
+    Atm,
+    PostOffice,
+    Police,
+    FireStation,
+    TownHall,
+    PlaceOfWorship,
+    Cinema,
+    Theatre,
+    Fuel,
+    ChargingStation,
+    Parking,
+    Toilets,
+    // Shop
+    Supermarket,
+    Convenience,
+    Bakery,
+    Butcher,
+    Clothes,
+    Hairdresser,
+    DepartmentStore,
+    Mall,
+    // Transport
+    RailwayStation,
+    RailwayHalt,
+    SubwayEntrance,
+    TramStop,
+    BusStop,
+    BusStation,
+    Airport,
+    FerryTerminal,
+    // Natural
+    Peak,
+    Volcano,
+    Glacier,
+    Beach,
+    Wood,
+    Water,
+    River,
+    Spring,
+    Cave,
+    // Tourism
+    Hotel,
+    Hostel,
+    GuestHouse,
+    CampSite,
+    Museum,
+    Attraction,
+    Viewpoint,
+    Zoo,
+    ThemePark,
+    // Leisure
+    Park,
+    Garden,
+    Playground,
+    SportsCentre,
+    Stadium,
+    SwimmingPool,
+    NatureReserve,
+    // Place
+    City,
+    Town,
+    Village,
+    Hamlet,
+    Suburb,
+    Neighbourhood,
+    Island,
+    // Road
+    Motorway,
+    PrimaryRoad,
+    SecondaryRoad,
+    ResidentialRoad,
+    Footway,
+    Cycleway,
+}
+
+impl Category {
+    /// All categories, grouped like [`CategoryGroup`].
+    pub const ALL: [Category; 80] = [
+        Category::Restaurant,
+        Category::Cafe,
+        Category::FastFood,
+        Category::Bar,
+        Category::Pub,
+        Category::Hospital,
+        Category::Clinic,
+        Category::Doctors,
+        Category::Pharmacy,
+        Category::School,
+        Category::University,
+        Category::Kindergarten,
+        Category::Library,
+        Category::Bank,
+        Category::Atm,
+        Category::PostOffice,
+        Category::Police,
+        Category::FireStation,
+        Category::TownHall,
+        Category::PlaceOfWorship,
+        Category::Cinema,
+        Category::Theatre,
+        Category::Fuel,
+        Category::ChargingStation,
+        Category::Parking,
+        Category::Toilets,
+        Category::Supermarket,
+        Category::Convenience,
+        Category::Bakery,
+        Category::Butcher,
+        Category::Clothes,
+        Category::Hairdresser,
+        Category::DepartmentStore,
+        Category::Mall,
+        Category::RailwayStation,
+        Category::RailwayHalt,
+        Category::SubwayEntrance,
+        Category::TramStop,
+        Category::BusStop,
+        Category::BusStation,
+        Category::Airport,
+        Category::FerryTerminal,
+        Category::Peak,
+        Category::Volcano,
+        Category::Glacier,
+        Category::Beach,
+        Category::Wood,
+        Category::Water,
+        Category::River,
+        Category::Spring,
+        Category::Cave,
+        Category::Hotel,
+        Category::Hostel,
+        Category::GuestHouse,
+        Category::CampSite,
+        Category::Museum,
+        Category::Attraction,
+        Category::Viewpoint,
+        Category::Zoo,
+        Category::ThemePark,
+        Category::Park,
+        Category::Garden,
+        Category::Playground,
+        Category::SportsCentre,
+        Category::Stadium,
+        Category::SwimmingPool,
+        Category::NatureReserve,
+        Category::City,
+        Category::Town,
+        Category::Village,
+        Category::Hamlet,
+        Category::Suburb,
+        Category::Neighbourhood,
+        Category::Island,
+        Category::Motorway,
+        Category::PrimaryRoad,
+        Category::SecondaryRoad,
+        Category::ResidentialRoad,
+        Category::Footway,
+        Category::Cycleway,
+    ];
+}
+
+/// Known tags per category. The first tag of a category is its canonical one.
+const TAGS: &[(Category, &str, &str)] = &[
+    (Category::Restaurant, "amenity", "restaurant"),
+    (Category::Cafe, "amenity", "cafe"),
+    (Category::FastFood, "amenity", "fast_food"),
+    (Category::Bar, "amenity", "bar"),
+    (Category::Pub, "amenity", "pub"),
+    (Category::Hospital, "amenity", "hospital"),
+    (Category::Clinic, "amenity", "clinic"),
+    (Category::Doctors, "amenity", "doctors"),
+    (Category::Pharmacy, "amenity", "pharmacy"),
+    (Category::School, "amenity", "school"),
+    (Category::University, "amenity", "university"),
+    (Category::University, "amenity", "college"),
+    (Category::Kindergarten, "amenity", "kindergarten"),
+    (Category::Library, "amenity", "library"),
+    (Category::Bank, "amenity", "bank"),
+    (Category::Atm, "amenity", "atm"),
+    (Category::PostOffice, "amenity", "post_office"),
+    (Category::Police, "amenity", "police"),
+    (Category::FireStation, "amenity", "fire_station"),
+    (Category::TownHall, "amenity", "townhall"),
+    (Category::PlaceOfWorship, "amenity", "place_of_worship"),
+    (Category::Cinema, "amenity", "cinema"),
+    (Category::Theatre, "amenity", "theatre"),
+    (Category::Fuel, "amenity", "fuel"),
+    (Category::ChargingStation, "amenity", "charging_station"),
+    (Category::Parking, "amenity", "parking"),
+    (Category::Toilets, "amenity", "toilets"),
+    (Category::Supermarket, "shop", "supermarket"),
+    (Category::Convenience, "shop", "convenience"),
+    (Category::Convenience, "shop", "kiosk"),
+    (Category::Bakery, "shop", "bakery"),
+    (Category::Butcher, "shop", "butcher"),
+    (Category::Clothes, "shop", "clothes"),
+    (Category::Hairdresser, "shop", "hairdresser"),
+    (Category::DepartmentStore, "shop", "department_store"),
+    (Category::Mall, "shop", "mall"),
+    (Category::RailwayStation, "railway", "station"),
+    (Category::RailwayStation, "public_transport", "station"),
+    (Category::RailwayHalt, "railway", "halt"),
+    (Category::SubwayEntrance, "railway", "subway_entrance"),
+    (Category::TramStop, "railway", "tram_stop"),
+    (Category::BusStop, "highway", "bus_stop"),
+    (Category::BusStation, "amenity", "bus_station"),
+    (Category::Airport, "aeroway", "aerodrome"),
+    (Category::Airport, "aeroway", "terminal"),
+    (Category::FerryTerminal, "amenity", "ferry_terminal"),
+    (Category::Peak, "natural", "peak"),
+    (Category::Volcano, "natural", "volcano"),
+    (Category::Glacier, "natural", "glacier"),
+    (Category::Beach, "natural", "beach"),
+    (Category::Wood, "natural", "wood"),
+    (Category::Wood, "landuse", "forest"),
+    (Category::Water, "natural", "water"),
+    (Category::Water, "water", "lake"),
+    (Category::River, "waterway", "river"),
+    (Category::River, "water", "river"),
+    (Category::Spring, "natural", "spring"),
+    (Category::Cave, "natural", "cave_entrance"),
+    (Category::Hotel, "tourism", "hotel"),
+    (Category::Hostel, "tourism", "hostel"),
+    (Category::GuestHouse, "tourism", "guest_house"),
+    (Category::CampSite, "tourism", "camp_site"),
+    (Category::Museum, "tourism", "museum"),
+    (Category::Attraction, "tourism", "attraction"),
+    (Category::Viewpoint, "tourism", "viewpoint"),
+    (Category::Zoo, "tourism", "zoo"),
+    (Category::ThemePark, "tourism", "theme_park"),
+    (Category::Park, "leisure", "park"),
+    (Category::Garden, "leisure", "garden"),
+    (Category::Playground, "leisure", "playground"),
+    (Category::SportsCentre, "leisure", "sports_centre"),
+    (Category::Stadium, "leisure", "stadium"),
+    (Category::SwimmingPool, "leisure", "swimming_pool"),
+    (Category::NatureReserve, "leisure", "nature_reserve"),
+    (Category::NatureReserve, "boundary", "protected_area"),
+    (Category::City, "place", "city"),
+    (Category::Town, "place", "town"),
+    (Category::Village, "place", "village"),
+    (Category::Hamlet, "place", "hamlet"),
+    (Category::Suburb, "place", "suburb"),
+    (Category::Neighbourhood, "place", "neighbourhood"),
+    (Category::Neighbourhood, "place", "quarter"),
+    (Category::Island, "place", "island"),
+    (Category::Motorway, "highway", "motorway"),
+    (Category::PrimaryRoad, "highway", "primary"),
+    (Category::PrimaryRoad, "highway", "trunk"),
+    (Category::SecondaryRoad, "highway", "secondary"),
+    (Category::SecondaryRoad, "highway", "tertiary"),
+    (Category::ResidentialRoad, "highway", "residential"),
+    (Category::ResidentialRoad, "highway", "living_street"),
+    (Category::Footway, "highway", "footway"),
+    (Category::Footway, "highway", "pedestrian"),
+    (Category::Cycleway, "highway", "cycleway"),
+];
+
+impl Category {
+    /// Looks up the category of an OSM `key=value` tag.
+    pub fn from_osm_tag(key: &str, value: &str) -> Option<Self> {
+        TAGS.iter()
+            .find(|(_, k, v)| *k == key && *v == value)
+            .map(|(category, _, _)| *category)
+    }
+
+    /// All OSM tags that belong to this category, as `(key, value)` pairs.
+    pub fn osm_tags(&self) -> Vec<(&'static str, &'static str)> {
+        TAGS.iter()
+            .filter(|(category, _, _)| category == self)
+            .map(|(_, key, value)| (*key, *value))
+            .collect()
+    }
+
+    pub fn group(&self) -> CategoryGroup {
+        match self {
+            Self::Restaurant
+            | Self::Cafe
+            | Self::FastFood
+            | Self::Bar
+            | Self::Pub
+            | Self::Hospital
+            | Self::Clinic
+            | Self::Doctors
+            | Self::Pharmacy
+            | Self::School
+            | Self::University
+            | Self::Kindergarten
+            | Self::Library
+            | Self::Bank
+            | Self::Atm
+            | Self::PostOffice
+            | Self::Police
+            | Self::FireStation
+            | Self::TownHall
+            | Self::PlaceOfWorship
+            | Self::Cinema
+            | Self::Theatre
+            | Self::Fuel
+            | Self::ChargingStation
+            | Self::Parking
+            | Self::Toilets => CategoryGroup::Amenity,
+            Self::Supermarket
+            | Self::Convenience
+            | Self::Bakery
+            | Self::Butcher
+            | Self::Clothes
+            | Self::Hairdresser
+            | Self::DepartmentStore
+            | Self::Mall => CategoryGroup::Shop,
+            Self::RailwayStation
+            | Self::RailwayHalt
+            | Self::SubwayEntrance
+            | Self::TramStop
+            | Self::BusStop
+            | Self::BusStation
+            | Self::Airport
+            | Self::FerryTerminal => CategoryGroup::Transport,
+            Self::Peak
+            | Self::Volcano
+            | Self::Glacier
+            | Self::Beach
+            | Self::Wood
+            | Self::Water
+            | Self::River
+            | Self::Spring
+            | Self::Cave => CategoryGroup::Natural,
+            Self::Hotel
+            | Self::Hostel
+            | Self::GuestHouse
+            | Self::CampSite
+            | Self::Museum
+            | Self::Attraction
+            | Self::Viewpoint
+            | Self::Zoo
+            | Self::ThemePark => CategoryGroup::Tourism,
+            Self::Park
+            | Self::Garden
+            | Self::Playground
+            | Self::SportsCentre
+            | Self::Stadium
+            | Self::SwimmingPool
+            | Self::NatureReserve => CategoryGroup::Leisure,
+            Self::City
+            | Self::Town
+            | Self::Village
+            | Self::Hamlet
+            | Self::Suburb
+            | Self::Neighbourhood
+            | Self::Island => CategoryGroup::Place,
+            Self::Motorway
+            | Self::PrimaryRoad
+            | Self::SecondaryRoad
+            | Self::ResidentialRoad
+            | Self::Footway
+            | Self::Cycleway => CategoryGroup::Road,
+        }
+    }
+}
+
+impl PhotonFeature {
+    /// The category of the feature's `osm_key` and `osm_value`, if it is a known one.
+    pub fn category(&self) -> Option<Category> {
+        Category::from_osm_tag(&self.osm_key, &self.osm_value)
+    }
+
+    /// The category group of the feature. Unlike [`category`](Self::category), this also covers
+    /// unknown values of a known key, e.g. any `shop`.
+    pub fn category_group(&self) -> Option<CategoryGroup> {
+        match self.category() {
+            Some(category) => Some(category.group()),
+            None => CategoryGroup::from_osm_key(&self.osm_key),
+        }
+    }
+}
+
+/// `osm_tag` filter values that include all features of the given categories.
+pub(crate) fn category_tags(categories: &[Category]) -> Vec<String> {
+    categories
+        .iter()
+        .flat_map(|category| category.osm_tags())
+        .map(|(key, value)| format!("{}:{}", key, value))
+        .collect()
+}
+
+/// `osm_tag` filter values that include all features of the given groups.
+///
+/// Besides the keys of the groups, these are the tags of their categories with other keys, e.g.
+/// `highway:bus_stop` for [`CategoryGroup::Transport`]. Tags of other groups under the included
+/// keys are excluded, e.g. `!highway:bus_stop` for [`CategoryGroup::Road`] alone.
+pub(crate) fn category_group_tags(groups: &[CategoryGroup]) -> Vec<String> {
+    let is_included_key = |key: &str| groups.iter().any(|group| group.osm_keys().contains(&key));
+    let mut tags: Vec<String> = groups
+        .iter()
+        .flat_map(|group| group.osm_keys())
+        .map(|key| key.to_string())
+        .collect();
+    for (category, key, value) in TAGS {
+        let included = groups.contains(&category.group());
+        if included && !is_included_key(key) {
+            tags.push(format!("{}:{}", key, value));
+        } else if !included && is_included_key(key) {
+            tags.push(format!("!{}:{}", key, value));
+        }
+    }
+    tags
+}
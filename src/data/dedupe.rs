@@ -0,0 +1,153 @@
+//! Removal of results that describe the same physical place, such as a building returned both as
+//! way and as node, or a street that is split into several segments.
+//!
+//! Duplicates are merged into the best-ranked, i.e. first, feature of their group, whose extent is
+//! widened to cover all of them. Photon's own deduplication can be controlled with
+//! [`ForwardFilter::dedupe`](crate::filter::ForwardFilter::dedupe).
+
+use crate::{BoundingBox, FeatureType, PhotonFeature};
+
+/// Default distance for [`DedupeRule::SameNameNearby`] in meters.
+pub const DEFAULT_MAX_DISTANCE: f64 = 100.0;
+
+/// When two features are considered duplicates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DedupeRule {
+    /// Same `osm_type` and `osm_id`.
+    SameOsmObject,
+    /// Same name and city, and at most `max_distance` meters apart.
+    SameNameNearby { max_distance: f64 },
+    /// Streets with the same name in the same city.
+    SameStreetInCity,
+}
+
+/// Removes and merges duplicates according to a set of rules. This struct implements a builder
+/// pattern, so rules can be easily configured.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deduplicator {
+    rules: Vec<DedupeRule>,
+}
+
+impl Default for Deduplicator {
+    fn default() -> Self {
+        Deduplicator {
+            rules: vec![
+                DedupeRule::SameOsmObject,
+                DedupeRule::SameNameNearby {
+                    max_distance: DEFAULT_MAX_DISTANCE,
+                },
+                DedupeRule::SameStreetInCity,
+            ],
+        }
+    }
+}
+
+impl Deduplicator {
+    /// Construct a new `Deduplicator` with all rules and a distance of 100 meters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use only `rules`.
+    pub fn rules(mut self, rules: &[DedupeRule]) -> Self {
+        self.rules = rules.to_vec();
+        self
+    }
+
+    /// Additionally use `rule`.
+    pub fn rule(mut self, rule: DedupeRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Whether `a` and `b` are duplicates according to any rule.
+    pub fn is_duplicate(&self, a: &PhotonFeature, b: &PhotonFeature) -> bool {
+        self.rules.iter().any(|rule| matches(rule, a, b))
+    }
+
+    /// Merges duplicates into the first feature of their group and keeps the order of the
+    /// remaining features. Duplicates of duplicates belong to the same group.
+    pub fn dedupe(&self, features: Vec<PhotonFeature>) -> Vec<PhotonFeature> {
+        let mut groups: Vec<usize> = (0..features.len()).collect();
+        for i in 0..features.len() {
+            for j in i + 1..features.len() {
+                if self.is_duplicate(&features[i], &features[j]) {
+                    let (root_i, root_j) = (root(&groups, i), root(&groups, j));
+                    // The lower index is the better-ranked feature and becomes the representative.
+                    groups[root_i.max(root_j)] = root_i.min(root_j);
+                }
+            }
+        }
+
+        let mut extents: Vec<Option<BoundingBox>> = vec![None; features.len()];
+        let mut sizes = vec![0usize; features.len()];
+        for (i, feature) in features.iter().enumerate() {
+            let representative = root(&groups, i);
+            let extent = feature.extent.unwrap_or(BoundingBox {
+                south_west: feature.coords,
+                north_east: feature.coords,
+            });
+            extents[representative] = Some(match extents[representative] {
+                Some(merged) => merged.union(&extent),
+                None => extent,
+            });
+            sizes[representative] += 1;
+        }
+
+        features
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| root(&groups, *i) == *i)
+            .map(|(i, mut feature)| {
+                if sizes[i] > 1 {
+                    feature.extent = extents[i];
+                }
+                feature
+            })
+            .collect()
+    }
+}
+
+/// Merges duplicates with the default rules of [`Deduplicator`].
+pub fn dedupe(features: Vec<PhotonFeature>) -> Vec<PhotonFeature> {
+    Deduplicator::default().dedupe(features)
+}
+
+fn root(groups: &[usize], mut index: usize) -> usize {
+    while groups[index] != index {
+        index = groups[index];
+    }
+    index
+}
+
+fn matches(rule: &DedupeRule, a: &PhotonFeature, b: &PhotonFeature) -> bool {
+    match rule {
+        DedupeRule::SameOsmObject => a.osm_type == b.osm_type && a.osm_id == b.osm_id,
+        DedupeRule::SameNameNearby { max_distance } => {
+            same(&a.name, &b.name)
+                && same_or_missing(&a.city, &b.city)
+                && a.coords.haversine_distance(&b.coords) <= *max_distance
+        }
+        DedupeRule::SameStreetInCity => {
+            a.feature_type() == FeatureType::Street
+                && b.feature_type() == FeatureType::Street
+                && same(&a.name, &b.name)
+                && same(&a.city, &b.city)
+        }
+    }
+}
+
+/// Whether both values are set and equal, ignoring case and surrounding whitespace.
+fn same(a: &Option<String>, b: &Option<String>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let (a, b) = (a.trim(), b.trim());
+            !a.is_empty() && a.to_lowercase() == b.to_lowercase()
+        }
+        _ => false,
+    }
+}
+
+fn same_or_missing(a: &Option<String>, b: &Option<String>) -> bool {
+    (a.is_none() && b.is_none()) || same(a, b)
+}
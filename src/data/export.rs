@@ -0,0 +1,41 @@
+//! Export of features into files for GIS applications, GPS devices and Google Earth.
+
+#[cfg(feature = "geopackage")]
+pub mod geopackage;
+pub mod gpx;
+pub mod kml;
+
+use crate::label::AddressFormatter;
+use crate::PhotonFeature;
+
+/// The name of `feature`: its own name, or the first line of its address if it has none, e.g. the
+/// street and house number.
+fn display_name(feature: &PhotonFeature, lines: &[String]) -> String {
+    feature
+        .name
+        .clone()
+        .or_else(|| lines.first().cloned())
+        .unwrap_or_else(|| feature.osm_value.clone())
+}
+
+/// The name and the description of `feature`, which is its address with lines separated by `\n`.
+fn name_and_description(feature: &PhotonFeature, formatter: &AddressFormatter) -> (String, String) {
+    let lines = formatter.lines(feature);
+    (display_name(feature, &lines), lines.join("\n"))
+}
+
+/// Escapes `text` for use in XML content and attribute values.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
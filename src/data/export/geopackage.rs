@@ -0,0 +1,432 @@
+//! Export of features into [GeoPackage](https://www.geopackage.org) files, which QGIS and most
+//! other GIS applications open directly. Needs the `geopackage` feature.
+//!
+//! Features are written as a point layer with their address fields as attributes. Optionally, the
+//! extents of the features are written as a second layer of polygons. All geometries use WGS 84
+//! (EPSG:4326).
+
+use std::error::Error;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::data::{BoundingBox, LatLon, PhotonFeature};
+use crate::error::InvalidInputError;
+
+/// The `application_id` of GeoPackage files: "GPKG".
+const APPLICATION_ID: i32 = 0x4750_4B47;
+/// GeoPackage version 1.3.
+const USER_VERSION: i32 = 10300;
+const SRS_ID: i32 = 4326;
+
+const WGS84_DEFINITION: &str = "GEOGCS[\"WGS 84\",DATUM[\"WGS_1984\",SPHEROID[\"WGS 84\",6378137,\
+298.257223563,AUTHORITY[\"EPSG\",\"7030\"]],AUTHORITY[\"EPSG\",\"6326\"]],PRIMEM[\"Greenwich\",0,\
+AUTHORITY[\"EPSG\",\"8901\"]],UNIT[\"degree\",0.0174532925199433,AUTHORITY[\"EPSG\",\"9122\"]],\
+AUTHORITY[\"EPSG\",\"4326\"]]";
+
+const METADATA_TABLES: &str = "
+CREATE TABLE IF NOT EXISTS gpkg_spatial_ref_sys (
+    srs_name TEXT NOT NULL,
+    srs_id INTEGER PRIMARY KEY,
+    organization TEXT NOT NULL,
+    organization_coordsys_id INTEGER NOT NULL,
+    definition TEXT NOT NULL,
+    description TEXT
+);
+CREATE TABLE IF NOT EXISTS gpkg_contents (
+    table_name TEXT NOT NULL PRIMARY KEY,
+    data_type TEXT NOT NULL,
+    identifier TEXT UNIQUE,
+    description TEXT DEFAULT '',
+    last_change DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+    min_x DOUBLE,
+    min_y DOUBLE,
+    max_x DOUBLE,
+    max_y DOUBLE,
+    srs_id INTEGER,
+    CONSTRAINT fk_gc_r_srs_id FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id)
+);
+CREATE TABLE IF NOT EXISTS gpkg_geometry_columns (
+    table_name TEXT NOT NULL,
+    column_name TEXT NOT NULL,
+    geometry_type_name TEXT NOT NULL,
+    srs_id INTEGER NOT NULL,
+    z TINYINT NOT NULL,
+    m TINYINT NOT NULL,
+    CONSTRAINT pk_geom_cols PRIMARY KEY (table_name, column_name),
+    CONSTRAINT uk_gc_table_name UNIQUE (table_name),
+    CONSTRAINT fk_gc_tn FOREIGN KEY (table_name) REFERENCES gpkg_contents(table_name),
+    CONSTRAINT fk_gc_srs FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys (srs_id)
+);
+";
+
+/// The attribute columns of both layers, after `fid` and `geom`.
+const ATTRIBUTES: [&str; 17] = [
+    "query",
+    "osm_id",
+    "osm_type",
+    "osm_key",
+    "osm_value",
+    "type",
+    "name",
+    "house_number",
+    "street",
+    "postcode",
+    "district",
+    "city",
+    "county",
+    "state",
+    "country",
+    "country_code",
+    "label",
+];
+
+/// Configuration for [`GeoPackageWriter`]. This struct implements a builder pattern, so options can
+/// be easily constructed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeoPackageOptions {
+    /// The name of the point layer.
+    pub layer: String,
+    /// The name of the polygon layer with the extents, or `None` to not write extents.
+    pub extent_layer: Option<String>,
+}
+
+impl Default for GeoPackageOptions {
+    fn default() -> Self {
+        GeoPackageOptions {
+            layer: String::from("features"),
+            extent_layer: None,
+        }
+    }
+}
+
+impl GeoPackageOptions {
+    /// Construct new `GeoPackageOptions` that write a point layer called `features` and no extents.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn layer(mut self, name: &str) -> Self {
+        self.layer = name.to_string();
+        self
+    }
+
+    /// Also write the extents of the features into a polygon layer called `name`. Features without
+    /// an extent, or with one that crosses the antimeridian, are left out of this layer.
+    pub fn extent_layer(mut self, name: &str) -> Self {
+        self.extent_layer = Some(name.to_string());
+        self
+    }
+
+    /// Checks the layer names. This is done automatically before a file is opened.
+    pub fn validate(&self) -> Result<(), InvalidInputError> {
+        for name in std::iter::once(&self.layer).chain(&self.extent_layer) {
+            if name.trim().is_empty() || name.starts_with("gpkg_") || name.starts_with("rtree_") {
+                return Err(InvalidInputError::new(&format!(
+                    "'{}' is not a valid layer name",
+                    name
+                )));
+            }
+        }
+        if self.extent_layer.as_ref() == Some(&self.layer) {
+            return Err(InvalidInputError::new(
+                "the point layer and the extent layer need different names",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Writes features into the layers of a GeoPackage file.
+///
+/// The layers and all features are written in one transaction, which is committed by
+/// [`finish`](Self::finish). If the writer is dropped without finishing, the file is left as it
+/// was, without the layers.
+pub struct GeoPackageWriter {
+    connection: Connection,
+    points: Layer,
+    extents: Option<Layer>,
+}
+
+/// A layer and the bounds of its geometries.
+struct Layer {
+    name: String,
+    bounds: Option<(f64, f64, f64, f64)>,
+}
+
+impl Layer {
+    fn include(&mut self, south_west: &LatLon, north_east: &LatLon) {
+        let (min_x, min_y, max_x, max_y) = self.bounds.unwrap_or((
+            south_west.lon,
+            south_west.lat,
+            north_east.lon,
+            north_east.lat,
+        ));
+        self.bounds = Some((
+            min_x.min(south_west.lon),
+            min_y.min(south_west.lat),
+            max_x.max(north_east.lon),
+            max_y.max(north_east.lat),
+        ));
+    }
+}
+
+impl GeoPackageWriter {
+    /// Opens the GeoPackage at `path`, or creates it if it does not exist, and adds the layers of
+    /// `options`. Pass `None` for a single point layer called `features`.
+    ///
+    /// Fails if one of the layers already exists in the file.
+    pub fn create(
+        path: impl AsRef<Path>,
+        options: Option<GeoPackageOptions>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let options = options.unwrap_or_default();
+        options.validate()?;
+
+        let connection = Connection::open(path)?;
+        connection.execute_batch("BEGIN")?;
+        connection.execute_batch(METADATA_TABLES)?;
+        let spatial_ref_sys = [
+            (
+                "Undefined cartesian SRS",
+                -1,
+                "NONE",
+                -1,
+                "undefined",
+                "undefined cartesian coordinate reference system",
+            ),
+            (
+                "Undefined geographic SRS",
+                0,
+                "NONE",
+                0,
+                "undefined",
+                "undefined geographic coordinate reference system",
+            ),
+            (
+                "WGS 84 geodetic",
+                SRS_ID,
+                "EPSG",
+                SRS_ID,
+                WGS84_DEFINITION,
+                "longitude/latitude coordinates in decimal degrees on the WGS 84 spheroid",
+            ),
+        ];
+        for (name, id, organization, organization_id, definition, description) in spatial_ref_sys {
+            connection.execute(
+                "INSERT OR IGNORE INTO gpkg_spatial_ref_sys VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    name,
+                    id,
+                    organization,
+                    organization_id,
+                    definition,
+                    description
+                ],
+            )?;
+        }
+
+        let writer = GeoPackageWriter {
+            connection,
+            points: Layer {
+                name: options.layer,
+                bounds: None,
+            },
+            extents: options
+                .extent_layer
+                .map(|name| Layer { name, bounds: None }),
+        };
+        writer.create_layer(&writer.points.name, "POINT")?;
+        if let Some(extents) = &writer.extents {
+            writer.create_layer(&extents.name, "POLYGON")?;
+        }
+        // Part of the transaction, so a file the layers could not be added to keeps its header.
+        writer
+            .connection
+            .pragma_update(None, "application_id", APPLICATION_ID)?;
+        writer
+            .connection
+            .pragma_update(None, "user_version", USER_VERSION)?;
+        Ok(writer)
+    }
+
+    fn create_layer(&self, name: &str, geometry_type: &str) -> Result<(), Box<dyn Error>> {
+        let exists: bool = self.connection.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = ?1)",
+            [name],
+            |row| row.get(0),
+        )?;
+        if exists {
+            return Err(InvalidInputError::new(&format!("layer '{}' already exists", name)).into());
+        }
+        let columns: Vec<String> = ATTRIBUTES
+            .iter()
+            .map(|column| match *column {
+                "osm_id" => String::from("osm_id INTEGER NOT NULL"),
+                _ => format!("{} TEXT", quote(column)),
+            })
+            .collect();
+        self.connection.execute_batch(&format!(
+            "CREATE TABLE {} (fid INTEGER PRIMARY KEY AUTOINCREMENT, geom {}, {})",
+            quote(name),
+            geometry_type,
+            columns.join(", ")
+        ))?;
+        self.connection.execute(
+            "INSERT INTO gpkg_contents (table_name, data_type, identifier, srs_id) \
+             VALUES (?1, 'features', ?1, ?2)",
+            params![name, SRS_ID],
+        )?;
+        self.connection.execute(
+            "INSERT INTO gpkg_geometry_columns VALUES (?1, 'geom', ?2, ?3, 0, 0)",
+            params![name, geometry_type, SRS_ID],
+        )?;
+        Ok(())
+    }
+
+    /// Adds `feature`, and the key of the query it was found for, if any.
+    pub fn add(
+        &mut self,
+        feature: &PhotonFeature,
+        query: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.insert(
+            &self.points.name,
+            point_geometry(&feature.coords),
+            feature,
+            query,
+        )?;
+        self.points.include(&feature.coords, &feature.coords);
+
+        let extent = feature
+            .extent
+            .filter(|extent| !extent.crosses_antimeridian());
+        if let (Some(layer), Some(extent)) = (&self.extents, extent) {
+            self.insert(&layer.name, polygon_geometry(&extent), feature, query)?;
+        }
+        if let (Some(layer), Some(extent)) = (&mut self.extents, extent) {
+            layer.include(&extent.south_west, &extent.north_east);
+        }
+        Ok(())
+    }
+
+    fn insert(
+        &self,
+        layer: &str,
+        geometry: Vec<u8>,
+        feature: &PhotonFeature,
+        query: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let columns: Vec<String> = ATTRIBUTES.iter().map(|column| quote(column)).collect();
+        let placeholders: Vec<String> = (0..=ATTRIBUTES.len())
+            .map(|i| format!("?{}", i + 1))
+            .collect();
+        let sql = format!(
+            "INSERT INTO {} (geom, {}) VALUES ({})",
+            quote(layer),
+            columns.join(", "),
+            placeholders.join(", ")
+        );
+        self.connection.prepare_cached(&sql)?.execute(params![
+            geometry,
+            query,
+            feature.osm_id as i64,
+            feature.osm_type.code(),
+            feature.osm_key,
+            feature.osm_value,
+            feature.r#type,
+            feature.name,
+            feature.house_number,
+            feature.street,
+            feature.postcode,
+            feature.district,
+            feature.city,
+            feature.county,
+            feature.state,
+            feature.country,
+            feature.country_iso_code,
+            feature.label(),
+        ])?;
+        Ok(())
+    }
+
+    /// Stores the bounds of the layers and commits all features to the file.
+    pub fn finish(self) -> Result<(), Box<dyn Error>> {
+        for layer in std::iter::once(&self.points).chain(&self.extents) {
+            if let Some((min_x, min_y, max_x, max_y)) = layer.bounds {
+                self.connection.execute(
+                    "UPDATE gpkg_contents SET min_x = ?1, min_y = ?2, max_x = ?3, max_y = ?4, \
+                     last_change = strftime('%Y-%m-%dT%H:%M:%fZ','now') WHERE table_name = ?5",
+                    params![min_x, min_y, max_x, max_y, layer.name],
+                )?;
+            }
+        }
+        self.connection.execute_batch("COMMIT")?;
+        Ok(())
+    }
+}
+
+/// Writes `features` into the GeoPackage at `path`, see [`GeoPackageWriter::create`]. Pass `None`
+/// for a single point layer called `features`.
+pub fn write_geopackage(
+    path: impl AsRef<Path>,
+    features: &[PhotonFeature],
+    options: Option<GeoPackageOptions>,
+) -> Result<(), Box<dyn Error>> {
+    let mut writer = GeoPackageWriter::create(path, options)?;
+    for feature in features {
+        writer.add(feature, None)?;
+    }
+    writer.finish()
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// The GeoPackage binary header, with an envelope of `[min_x, max_x, min_y, max_y]` if given.
+fn header(envelope: Option<[f64; 4]>) -> Vec<u8> {
+    // Flags: little endian, and envelope type 1 if there is one.
+    let flags = if envelope.is_some() {
+        0b0000_0011
+    } else {
+        0b0000_0001
+    };
+    let mut blob = vec![b'G', b'P', 0, flags];
+    blob.extend_from_slice(&SRS_ID.to_le_bytes());
+    for value in envelope.iter().flatten() {
+        blob.extend_from_slice(&value.to_le_bytes());
+    }
+    blob
+}
+
+fn point_geometry(coords: &LatLon) -> Vec<u8> {
+    let mut blob = header(None);
+    // Little-endian WKB point.
+    blob.push(1);
+    blob.extend_from_slice(&1u32.to_le_bytes());
+    blob.extend_from_slice(&coords.lon.to_le_bytes());
+    blob.extend_from_slice(&coords.lat.to_le_bytes());
+    blob
+}
+
+fn polygon_geometry(extent: &BoundingBox) -> Vec<u8> {
+    let (south_west, north_east) = (&extent.south_west, &extent.north_east);
+    let mut blob = header(Some([
+        south_west.lon,
+        north_east.lon,
+        south_west.lat,
+        north_east.lat,
+    ]));
+    // Little-endian WKB polygon with one closed, counter-clockwise ring.
+    let ring = extent.ring();
+    blob.push(1);
+    blob.extend_from_slice(&3u32.to_le_bytes());
+    blob.extend_from_slice(&1u32.to_le_bytes());
+    blob.extend_from_slice(&(ring.len() as u32).to_le_bytes());
+    for (x, y) in ring {
+        blob.extend_from_slice(&x.to_le_bytes());
+        blob.extend_from_slice(&y.to_le_bytes());
+    }
+    blob
+}
@@ -0,0 +1,98 @@
+//! Export of features into GPX files for handheld GPS devices.
+//!
+//! Every feature becomes a waypoint with its name, its address as description and its OSM value,
+//! e.g. `restaurant`, as type.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::{escape_xml, name_and_description};
+use crate::label::AddressFormatter;
+use crate::PhotonFeature;
+
+/// Configuration for [`write_gpx`]. This struct implements a builder pattern, so options can be
+/// easily constructed.
+#[derive(Debug, Clone)]
+pub struct GpxOptions {
+    /// The application that is named as creator of the file.
+    pub creator: String,
+    /// Formats the description of each waypoint.
+    pub formatter: AddressFormatter,
+}
+
+impl Default for GpxOptions {
+    fn default() -> Self {
+        GpxOptions {
+            creator: String::from("photon-geocoding"),
+            formatter: AddressFormatter::default(),
+        }
+    }
+}
+
+impl GpxOptions {
+    /// Construct new `GpxOptions` with `photon-geocoding` as creator and the built-in address
+    /// templates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn creator(mut self, creator: &str) -> Self {
+        self.creator = creator.to_string();
+        self
+    }
+
+    pub fn formatter(mut self, formatter: AddressFormatter) -> Self {
+        self.formatter = formatter;
+        self
+    }
+}
+
+/// Writes `features` as GPX 1.1 waypoints to `output`. Pass `None` for the default options.
+pub fn write_gpx<W: Write>(
+    mut output: W,
+    features: &[PhotonFeature],
+    options: Option<GpxOptions>,
+) -> io::Result<()> {
+    let options = options.unwrap_or_default();
+    writeln!(output, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        output,
+        r#"<gpx version="1.1" creator="{}" xmlns="http://www.topografix.com/GPX/1/1">"#,
+        escape_xml(&options.creator)
+    )?;
+    for feature in features {
+        let (name, description) = name_and_description(feature, &options.formatter);
+        writeln!(
+            output,
+            r#"  <wpt lat="{}" lon="{}">"#,
+            feature.coords.lat, feature.coords.lon
+        )?;
+        writeln!(output, "    <name>{}</name>", escape_xml(&name))?;
+        writeln!(output, "    <desc>{}</desc>", escape_xml(&description))?;
+        writeln!(
+            output,
+            "    <type>{}</type>",
+            escape_xml(&feature.osm_value)
+        )?;
+        writeln!(output, "  </wpt>")?;
+    }
+    writeln!(output, "</gpx>")?;
+    output.flush()
+}
+
+/// Writes `features` into a new GPX file at `path`, see [`write_gpx`].
+pub fn write_gpx_file(
+    path: impl AsRef<Path>,
+    features: &[PhotonFeature],
+    options: Option<GpxOptions>,
+) -> io::Result<()> {
+    write_gpx(BufWriter::new(File::create(path)?), features, options)
+}
+
+/// `features` as a GPX document, see [`write_gpx`].
+pub fn to_gpx(features: &[PhotonFeature], options: Option<GpxOptions>) -> String {
+    let mut gpx = Vec::new();
+    write_gpx(&mut gpx, features, options).expect("writing to a Vec does not fail");
+    String::from_utf8(gpx).expect("GPX is valid UTF-8")
+}
@@ -0,0 +1,168 @@
+//! Export of features into KML files for Google Earth.
+//!
+//! Every feature becomes a placemark with its name, its address as description and its other
+//! properties as extended data. Features with an extent are drawn as their point and the extent
+//! polygon; extents that cross the antimeridian are left out.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::{escape_xml, name_and_description};
+use crate::label::AddressFormatter;
+use crate::{BoundingBox, LatLon, PhotonFeature};
+
+/// Configuration for [`write_kml`]. This struct implements a builder pattern, so options can be
+/// easily constructed.
+#[derive(Debug, Clone)]
+pub struct KmlOptions {
+    /// The name of the KML document.
+    pub name: String,
+    /// Formats the description of each placemark.
+    pub formatter: AddressFormatter,
+}
+
+impl Default for KmlOptions {
+    fn default() -> Self {
+        KmlOptions {
+            name: String::from("Photon results"),
+            formatter: AddressFormatter::default(),
+        }
+    }
+}
+
+impl KmlOptions {
+    /// Construct new `KmlOptions` for a document called "Photon results" with the built-in address
+    /// templates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn formatter(mut self, formatter: AddressFormatter) -> Self {
+        self.formatter = formatter;
+        self
+    }
+}
+
+/// Writes `features` as a KML document to `output`. Pass `None` for the default options.
+pub fn write_kml<W: Write>(
+    mut output: W,
+    features: &[PhotonFeature],
+    options: Option<KmlOptions>,
+) -> io::Result<()> {
+    let options = options.unwrap_or_default();
+    writeln!(output, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(output, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+    writeln!(output, "<Document>")?;
+    writeln!(output, "  <name>{}</name>", escape_xml(&options.name))?;
+    for feature in features {
+        write_placemark(&mut output, feature, &options.formatter)?;
+    }
+    writeln!(output, "</Document>")?;
+    writeln!(output, "</kml>")?;
+    output.flush()
+}
+
+/// Writes `features` into a new KML file at `path`, see [`write_kml`].
+pub fn write_kml_file(
+    path: impl AsRef<Path>,
+    features: &[PhotonFeature],
+    options: Option<KmlOptions>,
+) -> io::Result<()> {
+    write_kml(BufWriter::new(File::create(path)?), features, options)
+}
+
+/// `features` as a KML document, see [`write_kml`].
+pub fn to_kml(features: &[PhotonFeature], options: Option<KmlOptions>) -> String {
+    let mut kml = Vec::new();
+    write_kml(&mut kml, features, options).expect("writing to a Vec does not fail");
+    String::from_utf8(kml).expect("KML is valid UTF-8")
+}
+
+fn write_placemark<W: Write>(
+    output: &mut W,
+    feature: &PhotonFeature,
+    formatter: &AddressFormatter,
+) -> io::Result<()> {
+    let (name, description) = name_and_description(feature, formatter);
+    writeln!(output, "  <Placemark>")?;
+    writeln!(output, "    <name>{}</name>", escape_xml(&name))?;
+    writeln!(
+        output,
+        "    <description>{}</description>",
+        escape_xml(&description)
+    )?;
+
+    writeln!(output, "    <ExtendedData>")?;
+    let osm_id = feature.osm_id.to_string();
+    let data = [
+        ("osm_id", Some(&osm_id)),
+        ("osm_type", Some(&feature.osm_type.code().to_string())),
+        ("osm_key", Some(&feature.osm_key)),
+        ("osm_value", Some(&feature.osm_value)),
+        ("type", Some(&feature.r#type)),
+        ("house_number", feature.house_number.as_ref()),
+        ("street", feature.street.as_ref()),
+        ("postcode", feature.postcode.as_ref()),
+        ("district", feature.district.as_ref()),
+        ("city", feature.city.as_ref()),
+        ("county", feature.county.as_ref()),
+        ("state", feature.state.as_ref()),
+        ("country", feature.country.as_ref()),
+        ("country_code", feature.country_iso_code.as_ref()),
+    ];
+    for (key, value) in data {
+        if let Some(value) = value {
+            writeln!(
+                output,
+                "      <Data name=\"{}\"><value>{}</value></Data>",
+                key,
+                escape_xml(value)
+            )?;
+        }
+    }
+    writeln!(output, "    </ExtendedData>")?;
+
+    match feature
+        .extent
+        .filter(|extent| !extent.crosses_antimeridian())
+    {
+        Some(extent) => {
+            writeln!(output, "    <MultiGeometry>")?;
+            write_point(output, &feature.coords, "      ")?;
+            write_polygon(output, &extent, "      ")?;
+            writeln!(output, "    </MultiGeometry>")?;
+        }
+        None => write_point(output, &feature.coords, "    ")?,
+    }
+    writeln!(output, "  </Placemark>")
+}
+
+fn write_point<W: Write>(output: &mut W, coords: &LatLon, indent: &str) -> io::Result<()> {
+    writeln!(
+        output,
+        "{}<Point><coordinates>{},{}</coordinates></Point>",
+        indent, coords.lon, coords.lat
+    )
+}
+
+/// Writes the extent as a counter-clockwise ring, as KML requires for outer boundaries.
+fn write_polygon<W: Write>(output: &mut W, extent: &BoundingBox, indent: &str) -> io::Result<()> {
+    let coordinates: Vec<String> = extent
+        .ring()
+        .iter()
+        .map(|(lon, lat)| format!("{},{}", lon, lat))
+        .collect();
+    writeln!(
+        output,
+        "{}<Polygon><outerBoundaryIs><LinearRing><coordinates>{}</coordinates></LinearRing>\
+         </outerBoundaryIs></Polygon>",
+        indent,
+        coordinates.join(" ")
+    )
+}
@@ -1,7 +1,15 @@
 use std::fmt;
+use std::str::FromStr;
 
+use crate::category::{self, Category, CategoryGroup};
+use crate::error::InvalidInputError;
 use crate::{BoundingBox, LatLon};
 
+/// Highest zoom level accepted for the location bias.
+pub const MAX_ZOOM: u64 = 18;
+
+/// Layers are ordered by granularity, from `House` to `Country`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum PhotonLayer {
     House,
     Street,
@@ -28,8 +36,27 @@ impl fmt::Display for PhotonLayer {
     }
 }
 
+impl FromStr for PhotonLayer {
+    type Err = InvalidInputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "house" => Ok(Self::House),
+            "street" => Ok(Self::Street),
+            "locality" => Ok(Self::Locality),
+            "district" => Ok(Self::District),
+            "city" => Ok(Self::City),
+            "county" => Ok(Self::County),
+            "state" => Ok(Self::State),
+            "country" => Ok(Self::Country),
+            _ => Err(InvalidInputError::new(&format!("unknown layer '{}'", s))),
+        }
+    }
+}
+
 /// Filtering options for forward searches. This struct implements a builder pattern, so filters
 /// can be easily constructed.
+#[derive(Debug, Clone, Default)]
 pub struct ForwardFilter {
     pub location_bias: Option<LatLon>,
     pub location_bias_zoom: Option<u64>,
@@ -38,24 +65,11 @@ pub struct ForwardFilter {
     pub limit: Option<u64>,
     pub lang: Option<String>,
     pub layer: Option<Vec<PhotonLayer>>,
+    pub osm_tag: Option<Vec<String>>,
+    pub dedupe: Option<bool>,
     pub additional_query: Option<Vec<(String, String)>>,
 }
 
-impl Default for ForwardFilter {
-    fn default() -> Self {
-        ForwardFilter {
-            location_bias: None,
-            location_bias_zoom: None,
-            location_bias_scale: None,
-            bounding_box: None,
-            limit: None,
-            lang: None,
-            layer: None,
-            additional_query: None,
-        }
-    }
-}
-
 impl ForwardFilter {
     /// Construct a new `ForwardFilter`. All fields are set to `None` in the beginning.
     pub fn new() -> Self {
@@ -89,7 +103,7 @@ impl ForwardFilter {
     /// Return results in a specific language. Photon currently supports `DE`, `EN` and `FR`.
     /// Defaults to the local language of a search result.
     pub fn language(mut self, lang: &str) -> Self {
-        self.lang = Some(String::from(lang.to_lowercase()));
+        self.lang = Some(lang.to_lowercase());
         self
     }
 
@@ -99,6 +113,37 @@ impl ForwardFilter {
         self
     }
 
+    /// Filter results by OSM tags, e.g. `amenity:restaurant`, `shop` or `!highway`.
+    /// See [Photon documentation](https://github.com/komoot/photon#filter-results-by-tags-and-values)
+    pub fn osm_tag(mut self, tags: Vec<&str>) -> Self {
+        self.osm_tag = Some(tags.iter().map(|tag| tag.to_string()).collect());
+        self
+    }
+
+    /// Only return features of the given categories. The categories' tags are added to `osm_tag`.
+    pub fn categories(mut self, categories: &[Category]) -> Self {
+        self.osm_tag
+            .get_or_insert_with(Vec::new)
+            .extend(category::category_tags(categories));
+        self
+    }
+
+    /// Only return features of the given category groups. The groups' keys and tags are added to
+    /// `osm_tag`.
+    pub fn category_groups(mut self, groups: &[CategoryGroup]) -> Self {
+        self.osm_tag
+            .get_or_insert_with(Vec::new)
+            .extend(category::category_group_tags(groups));
+        self
+    }
+
+    /// Enable or disable Photon's server-side removal of duplicate results, which is enabled by
+    /// default. See [`dedupe`](crate::dedupe) for deduplication with configurable rules.
+    pub fn dedupe(mut self, dedupe: bool) -> Self {
+        self.dedupe = Some(dedupe);
+        self
+    }
+
     /// Add additional query strings to the request. Example: [Filtering by tags and values](https://github.com/komoot/photon#filter-results-by-tags-and-values)
     pub fn additional_query(mut self, query: Vec<(&str, &str)>) -> Self {
         self.additional_query = Some(
@@ -109,30 +154,48 @@ impl ForwardFilter {
         );
         self
     }
+
+    /// Checks all coordinates and numeric options of the filter. This is done automatically
+    /// before every request.
+    pub fn validate(&self) -> Result<(), InvalidInputError> {
+        if let Some(bias) = &self.location_bias {
+            bias.validate()?;
+        }
+        if let Some(zoom) = self.location_bias_zoom {
+            if zoom > MAX_ZOOM {
+                return Err(InvalidInputError::new(&format!(
+                    "zoom must be at most {}, got {}",
+                    MAX_ZOOM, zoom
+                )));
+            }
+        }
+        if let Some(scale) = self.location_bias_scale {
+            if !scale.is_finite() || !(0.0..=1.0).contains(&scale) {
+                return Err(InvalidInputError::new(&format!(
+                    "location bias scale must be within [0, 1], got {}",
+                    scale
+                )));
+            }
+        }
+        if let Some(bbox) = &self.bounding_box {
+            bbox.validate()?;
+        }
+        validate_limit(self.limit)
+    }
 }
 
 /// Filtering options for reverse searches. This struct implements a builder pattern, so filters
 /// can be easily constructed.
+#[derive(Debug, Clone, Default)]
 pub struct ReverseFilter {
     pub radius: Option<u64>,
     pub limit: Option<u64>,
     pub lang: Option<String>,
     pub layer: Option<Vec<PhotonLayer>>,
+    pub osm_tag: Option<Vec<String>>,
     pub additional_query: Option<Vec<(String, String)>>,
 }
 
-impl Default for ReverseFilter {
-    fn default() -> Self {
-        ReverseFilter {
-            radius: None,
-            limit: None,
-            lang: None,
-            layer: None,
-            additional_query: None,
-        }
-    }
-}
-
 impl ReverseFilter {
     /// Construct a new `ReverseFilter`. All fields are set to `None` in the beginning.
     pub fn new() -> Self {
@@ -153,7 +216,7 @@ impl ReverseFilter {
     /// Return results in a specific language. Photon currently supports `DE`, `EN` and `FR`.
     /// Defaults to the local language of a search result.
     pub fn language(mut self, lang: &str) -> Self {
-        self.lang = Some(String::from(lang.to_lowercase()));
+        self.lang = Some(lang.to_lowercase());
         self
     }
 
@@ -163,6 +226,30 @@ impl ReverseFilter {
         self
     }
 
+    /// Filter results by OSM tags, e.g. `amenity:restaurant`, `shop` or `!highway`.
+    /// See [Photon documentation](https://github.com/komoot/photon#filter-results-by-tags-and-values)
+    pub fn osm_tag(mut self, tags: Vec<&str>) -> Self {
+        self.osm_tag = Some(tags.iter().map(|tag| tag.to_string()).collect());
+        self
+    }
+
+    /// Only return features of the given categories. The categories' tags are added to `osm_tag`.
+    pub fn categories(mut self, categories: &[Category]) -> Self {
+        self.osm_tag
+            .get_or_insert_with(Vec::new)
+            .extend(category::category_tags(categories));
+        self
+    }
+
+    /// Only return features of the given category groups. The groups' keys and tags are added to
+    /// `osm_tag`.
+    pub fn category_groups(mut self, groups: &[CategoryGroup]) -> Self {
+        self.osm_tag
+            .get_or_insert_with(Vec::new)
+            .extend(category::category_group_tags(groups));
+        self
+    }
+
     /// Add additional query strings to the request. Example: [Filtering by tags and values](https://github.com/komoot/photon#filter-results-by-tags-and-values)
     pub fn additional_query(mut self, query: Vec<(&str, &str)>) -> Self {
         self.additional_query = Some(
@@ -173,4 +260,34 @@ impl ReverseFilter {
         );
         self
     }
+
+    /// Checks all numeric options of the filter. This is done automatically before every request.
+    pub fn validate(&self) -> Result<(), InvalidInputError> {
+        if self.radius == Some(0) {
+            return Err(InvalidInputError::new("radius must be greater than 0"));
+        }
+        validate_limit(self.limit)
+    }
+}
+
+impl From<ForwardFilter> for ReverseFilter {
+    /// Keeps the options that both kinds of searches have in common: `limit`, `lang`, `layer`,
+    /// `osm_tag` and `additional_query`.
+    fn from(filter: ForwardFilter) -> Self {
+        ReverseFilter {
+            radius: None,
+            limit: filter.limit,
+            lang: filter.lang,
+            layer: filter.layer,
+            osm_tag: filter.osm_tag,
+            additional_query: filter.additional_query,
+        }
+    }
+}
+
+fn validate_limit(limit: Option<u64>) -> Result<(), InvalidInputError> {
+    if limit == Some(0) {
+        return Err(InvalidInputError::new("limit must be greater than 0"));
+    }
+    Ok(())
 }
@@ -0,0 +1,167 @@
+//! Distance and bearing calculations on [`LatLon`] coordinates and helpers to order search results
+//! by their distance to a point.
+//!
+//! All distances are in meters, all angles in degrees.
+
+use std::cmp::Ordering;
+
+use crate::{LatLon, PhotonFeature};
+
+/// Mean earth radius in meters, as used by the spherical formulas.
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+// WGS84 ellipsoid, used by Vincenty's formula.
+const WGS84_A: f64 = 6_378_137.0;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+const WGS84_B: f64 = WGS84_A * (1.0 - WGS84_F);
+
+impl LatLon {
+    /// Great-circle distance to `other` on a spherical earth (haversine formula).
+    ///
+    /// Fast and accurate to about 0.5 %, which is plenty for ranking search results.
+    pub fn haversine_distance(&self, other: &LatLon) -> f64 {
+        let phi1 = self.lat.to_radians();
+        let phi2 = other.lat.to_radians();
+        let d_phi = (other.lat - self.lat).to_radians();
+        let d_lambda = (other.lon - self.lon).to_radians();
+
+        let a =
+            (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * a.sqrt().atan2((1.0 - a).sqrt())
+    }
+
+    /// Distance to `other` on the WGS84 ellipsoid (Vincenty's inverse formula), accurate to
+    /// millimeters.
+    ///
+    /// Returns `None` if the iteration does not converge, which can happen for nearly antipodal
+    /// points. Use [`haversine_distance`](Self::haversine_distance) as a fallback in that case.
+    pub fn vincenty_distance(&self, other: &LatLon) -> Option<f64> {
+        let l = (other.lon - self.lon).to_radians();
+        let u1 = ((1.0 - WGS84_F) * self.lat.to_radians().tan()).atan();
+        let u2 = ((1.0 - WGS84_F) * other.lat.to_radians().tan()).atan();
+        let (sin_u1, cos_u1) = u1.sin_cos();
+        let (sin_u2, cos_u2) = u2.sin_cos();
+
+        let mut lambda = l;
+        for _ in 0..200 {
+            let (sin_lambda, cos_lambda) = lambda.sin_cos();
+            let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+                + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+            .sqrt();
+            if sin_sigma == 0.0 {
+                return Some(0.0); // coincident points
+            }
+            let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+            let sigma = sin_sigma.atan2(cos_sigma);
+            let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+            let cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+            let cos_2sigma_m = if cos_sq_alpha == 0.0 {
+                0.0 // equatorial line
+            } else {
+                cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+            };
+            let c = WGS84_F / 16.0 * cos_sq_alpha * (4.0 + WGS84_F * (4.0 - 3.0 * cos_sq_alpha));
+            let previous = lambda;
+            lambda = l
+                + (1.0 - c)
+                    * WGS84_F
+                    * sin_alpha
+                    * (sigma
+                        + c * sin_sigma
+                            * (cos_2sigma_m
+                                + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)));
+
+            if (lambda - previous).abs() < 1e-12 {
+                let u_sq =
+                    cos_sq_alpha * (WGS84_A * WGS84_A - WGS84_B * WGS84_B) / (WGS84_B * WGS84_B);
+                let a = 1.0
+                    + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+                let b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+                let delta_sigma = b
+                    * sin_sigma
+                    * (cos_2sigma_m
+                        + b / 4.0
+                            * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)
+                                - b / 6.0
+                                    * cos_2sigma_m
+                                    * (-3.0 + 4.0 * sin_sigma * sin_sigma)
+                                    * (-3.0 + 4.0 * cos_2sigma_m * cos_2sigma_m)));
+                return Some(WGS84_B * a * (sigma - delta_sigma));
+            }
+        }
+        None
+    }
+
+    /// Initial bearing (forward azimuth) of the great circle from `self` to `other`, in degrees
+    /// clockwise from north in the range `[0, 360)`.
+    pub fn initial_bearing(&self, other: &LatLon) -> f64 {
+        let phi1 = self.lat.to_radians();
+        let phi2 = other.lat.to_radians();
+        let d_lambda = (other.lon - self.lon).to_radians();
+
+        let y = d_lambda.sin() * phi2.cos();
+        let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+        (y.atan2(x).to_degrees() + 360.0) % 360.0
+    }
+
+    /// The point reached when travelling `distance` meters from `self` along the great circle with
+    /// the initial `bearing` (degrees clockwise from north).
+    pub fn destination(&self, bearing: f64, distance: f64) -> LatLon {
+        let delta = distance / EARTH_RADIUS;
+        let theta = bearing.to_radians();
+        let phi1 = self.lat.to_radians();
+        let lambda1 = self.lon.to_radians();
+
+        let phi2 = (phi1.sin() * delta.cos() + phi1.cos() * delta.sin() * theta.cos()).asin();
+        let lambda2 = lambda1
+            + (theta.sin() * delta.sin() * phi1.cos()).atan2(delta.cos() - phi1.sin() * phi2.sin());
+
+        LatLon::new(phi2.to_degrees(), normalize_longitude(lambda2.to_degrees()))
+    }
+
+    /// The point halfway between `self` and `other` along the great circle.
+    pub fn midpoint(&self, other: &LatLon) -> LatLon {
+        let phi1 = self.lat.to_radians();
+        let phi2 = other.lat.to_radians();
+        let lambda1 = self.lon.to_radians();
+        let d_lambda = (other.lon - self.lon).to_radians();
+
+        let bx = phi2.cos() * d_lambda.cos();
+        let by = phi2.cos() * d_lambda.sin();
+        let phi3 = (phi1.sin() + phi2.sin()).atan2(((phi1.cos() + bx).powi(2) + by * by).sqrt());
+        let lambda3 = lambda1 + by.atan2(phi1.cos() + bx);
+
+        LatLon::new(phi3.to_degrees(), normalize_longitude(lambda3.to_degrees()))
+    }
+}
+
+/// Sorts `features` by their (haversine) distance to `point`, closest first.
+///
+/// The sort is stable, so features at the same distance keep Photon's ranking.
+pub fn sort_by_distance(features: &mut [PhotonFeature], point: &LatLon) {
+    features.sort_by(|a, b| {
+        let distance_a = a.coords.haversine_distance(point);
+        let distance_b = b.coords.haversine_distance(point);
+        distance_a
+            .partial_cmp(&distance_b)
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+/// Keeps only the features that are at most `max_distance` meters away from `point`, preserving
+/// their order.
+pub fn filter_by_distance(
+    features: Vec<PhotonFeature>,
+    point: &LatLon,
+    max_distance: f64,
+) -> Vec<PhotonFeature> {
+    features
+        .into_iter()
+        .filter(|feature| feature.coords.haversine_distance(point) <= max_distance)
+        .collect()
+}
+
+/// Maps any longitude onto the range `[-180, 180)`.
+pub(crate) fn normalize_longitude(lon: f64) -> f64 {
+    (lon + 180.0).rem_euclid(360.0) - 180.0
+}
@@ -0,0 +1,99 @@
+//! Offline encoding and decoding of [geohashes](https://en.wikipedia.org/wiki/Geohash), such as
+//! `u281z7j5`.
+
+use crate::error::InvalidInputError;
+use crate::{BoundingBox, LatLon};
+
+const ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// The longest supported geohash, which corresponds to cells of a few centimeters.
+pub const MAX_PRECISION: usize = 12;
+
+/// Encodes `coords` into a geohash of `precision` characters (1 to 12).
+pub fn encode(coords: &LatLon, precision: usize) -> Result<String, InvalidInputError> {
+    coords.validate()?;
+    if precision == 0 || precision > MAX_PRECISION {
+        return Err(InvalidInputError::new(&format!(
+            "geohash precision must be between 1 and {}, got {}",
+            MAX_PRECISION, precision
+        )));
+    }
+
+    let (mut lat_range, mut lon_range) = ((-90.0, 90.0), (-180.0, 180.0));
+    let mut hash = String::with_capacity(precision);
+    let mut even_bit = true;
+    let (mut bits, mut value) = (0, 0usize);
+    while hash.len() < precision {
+        let (range, coordinate) = if even_bit {
+            (&mut lon_range, coords.lon)
+        } else {
+            (&mut lat_range, coords.lat)
+        };
+        let middle = (range.0 + range.1) / 2.0;
+        value <<= 1;
+        if coordinate >= middle {
+            value |= 1;
+            range.0 = middle;
+        } else {
+            range.1 = middle;
+        }
+        even_bit = !even_bit;
+        bits += 1;
+        if bits == 5 {
+            hash.push(ALPHABET[value] as char);
+            bits = 0;
+            value = 0;
+        }
+    }
+    Ok(hash)
+}
+
+/// Decodes a geohash into the cell it describes.
+pub fn decode(hash: &str) -> Result<BoundingBox, InvalidInputError> {
+    let hash = hash.trim();
+    if hash.is_empty() || hash.len() > MAX_PRECISION {
+        return Err(invalid(hash));
+    }
+    let (mut lat_range, mut lon_range) = ((-90.0, 90.0), (-180.0, 180.0));
+    let mut even_bit = true;
+    for c in hash.chars() {
+        let value = ALPHABET
+            .iter()
+            .position(|&digit| digit as char == c.to_ascii_lowercase())
+            .ok_or_else(|| invalid(hash))?;
+        for shift in (0..5).rev() {
+            let range: &mut (f64, f64) = if even_bit {
+                &mut lon_range
+            } else {
+                &mut lat_range
+            };
+            let middle = (range.0 + range.1) / 2.0;
+            if value >> shift & 1 == 1 {
+                range.0 = middle;
+            } else {
+                range.1 = middle;
+            }
+            even_bit = !even_bit;
+        }
+    }
+    Ok(BoundingBox {
+        south_west: LatLon::new(lat_range.0, lon_range.0),
+        north_east: LatLon::new(lat_range.1, lon_range.1),
+    })
+}
+
+impl LatLon {
+    /// The geohash of this coordinate with `precision` characters.
+    pub fn to_geohash(&self, precision: usize) -> Result<String, InvalidInputError> {
+        encode(self, precision)
+    }
+
+    /// The center of the cell described by `hash`.
+    pub fn from_geohash(hash: &str) -> Result<LatLon, InvalidInputError> {
+        decode(hash).map(|cell| cell.center())
+    }
+}
+
+fn invalid(hash: &str) -> InvalidInputError {
+    InvalidInputError::new(&format!("'{}' is not a valid geohash", hash))
+}
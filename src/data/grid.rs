@@ -0,0 +1,460 @@
+//! Offline conversion between [`LatLon`] and UTM or MGRS grid references on the WGS84 ellipsoid.
+//!
+//! Both grids are defined between 80°S and 84°N; the polar UPS grids are not supported.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::InvalidInputError;
+use crate::LatLon;
+
+const WGS84_A: f64 = 6_378_137.0;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+const K0: f64 = 0.9996;
+const FALSE_EASTING: f64 = 500_000.0;
+const FALSE_NORTHING_SOUTH: f64 = 10_000_000.0;
+
+const LAT_BANDS: &[u8; 20] = b"CDEFGHJKLMNPQRSTUVWX";
+const COLUMN_LETTERS: [&[u8; 8]; 3] = [b"ABCDEFGH", b"JKLMNPQR", b"STUVWXYZ"];
+const ROW_LETTERS: &[u8; 20] = b"ABCDEFGHJKLMNPQRSTUV";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hemisphere {
+    North,
+    South,
+}
+
+/// A UTM coordinate, e.g. `32U 691607 5334760`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Utm {
+    /// Longitude zone from 1 to 60.
+    pub zone: u8,
+    /// Latitude band letter from `C` to `X`.
+    pub band: char,
+    pub easting: f64,
+    pub northing: f64,
+}
+
+/// An MGRS grid reference, e.g. `32U PU 91845 32618`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mgrs {
+    pub zone: u8,
+    pub band: char,
+    /// Column letter of the 100 km square.
+    pub column: char,
+    /// Row letter of the 100 km square.
+    pub row: char,
+    /// Easting within the 100 km square in meters, truncated to the precision.
+    pub easting: u32,
+    /// Northing within the 100 km square in meters, truncated to the precision.
+    pub northing: u32,
+    /// Number of digits per axis, from 0 (100 km) to 5 (1 m).
+    pub precision: u8,
+}
+
+impl LatLon {
+    /// Converts the coordinate to UTM in its regular zone, including the exceptions around Norway
+    /// and Svalbard.
+    pub fn to_utm(&self) -> Result<Utm, InvalidInputError> {
+        self.validate()?;
+        check_utm_latitude(self.lat)?;
+        self.to_utm_zone(zone_for(self))
+    }
+
+    /// Converts the coordinate to UTM in the given `zone`, which is useful to express points close
+    /// to a zone border in the neighbouring zone.
+    pub fn to_utm_zone(&self, zone: u8) -> Result<Utm, InvalidInputError> {
+        self.validate()?;
+        check_utm_latitude(self.lat)?;
+        check_zone(zone)?;
+        let (easting, northing) = project(self, zone);
+        Ok(Utm {
+            zone,
+            band: band_for(self.lat),
+            easting,
+            northing,
+        })
+    }
+
+    /// Converts the coordinate to an MGRS reference with `precision` digits per axis (0-5).
+    pub fn to_mgrs(&self, precision: u8) -> Result<Mgrs, InvalidInputError> {
+        self.to_utm()?.to_mgrs(precision)
+    }
+}
+
+impl Utm {
+    pub fn hemisphere(&self) -> Hemisphere {
+        if self.band >= 'N' {
+            Hemisphere::North
+        } else {
+            Hemisphere::South
+        }
+    }
+
+    /// Converts the coordinate back to latitude and longitude.
+    pub fn to_latlon(&self) -> LatLon {
+        unproject(self.zone, self.hemisphere(), self.easting, self.northing)
+    }
+
+    /// Converts the coordinate to an MGRS reference with `precision` digits per axis (0-5).
+    pub fn to_mgrs(&self, precision: u8) -> Result<Mgrs, InvalidInputError> {
+        check_zone(self.zone)?;
+        check_band(self.band)?;
+        if precision > 5 {
+            return Err(InvalidInputError::new(&format!(
+                "MGRS precision must be between 0 and 5, got {}",
+                precision
+            )));
+        }
+
+        let set = (self.zone as usize - 1) % 3;
+        let column_index = (self.easting / 100_000.0).floor() as usize;
+        let column = match column_index {
+            1..=8 => COLUMN_LETTERS[set][column_index - 1] as char,
+            _ => {
+                return Err(InvalidInputError::new(&format!(
+                    "easting {} is outside of zone {}",
+                    self.easting, self.zone
+                )))
+            }
+        };
+        let row_offset = if self.zone % 2 == 0 { 5 } else { 0 };
+        let row_index = ((self.northing / 100_000.0).floor() as usize + row_offset) % 20;
+        let row = ROW_LETTERS[row_index] as char;
+
+        let resolution = 10u32.pow(5 - precision as u32);
+        let truncate = |value: f64| (value.rem_euclid(100_000.0) as u32) / resolution * resolution;
+        Ok(Mgrs {
+            zone: self.zone,
+            band: self.band,
+            column,
+            row,
+            easting: truncate(self.easting),
+            northing: truncate(self.northing),
+            precision,
+        })
+    }
+}
+
+impl Mgrs {
+    /// Converts the reference to UTM, using the south-west corner of the referenced square.
+    pub fn to_utm(&self) -> Result<Utm, InvalidInputError> {
+        check_zone(self.zone)?;
+        check_band(self.band)?;
+        let set = (self.zone as usize - 1) % 3;
+        let column_index = COLUMN_LETTERS[set]
+            .iter()
+            .position(|&c| c as char == self.column)
+            .ok_or_else(|| self.invalid())?;
+        let row_index = ROW_LETTERS
+            .iter()
+            .position(|&c| c as char == self.row)
+            .ok_or_else(|| self.invalid())?;
+        let row_offset = if self.zone % 2 == 0 { 5 } else { 0 };
+
+        let easting = (column_index + 1) as f64 * 100_000.0 + self.easting as f64;
+        let mut northing =
+            ((row_index + 20 - row_offset) % 20) as f64 * 100_000.0 + self.northing as f64;
+
+        // Row letters repeat every 2000 km, so move north until the latitude band is reached.
+        let band_index = LAT_BANDS
+            .iter()
+            .position(|&c| c as char == self.band)
+            .unwrap_or(0);
+        let band_south = band_index as f64 * 8.0 - 80.0;
+        let band_start = LatLon::new(band_south, central_meridian(self.zone));
+        let band_northing = (project(&band_start, self.zone).1 / 100_000.0).floor() * 100_000.0;
+        while northing < band_northing {
+            northing += 2_000_000.0;
+        }
+
+        Ok(Utm {
+            zone: self.zone,
+            band: self.band,
+            easting,
+            northing,
+        })
+    }
+
+    /// Converts the reference to the center of the referenced square.
+    pub fn to_latlon(&self) -> Result<LatLon, InvalidInputError> {
+        let half = 10u32.pow(5 - self.precision.min(5) as u32) as f64 / 2.0;
+        let mut utm = self.to_utm()?;
+        utm.easting += half;
+        utm.northing += half;
+        Ok(utm.to_latlon())
+    }
+
+    fn invalid(&self) -> InvalidInputError {
+        InvalidInputError::new(&format!("'{}' is not a valid MGRS reference", self))
+    }
+}
+
+impl From<Utm> for LatLon {
+    fn from(utm: Utm) -> Self {
+        utm.to_latlon()
+    }
+}
+
+impl TryFrom<Mgrs> for LatLon {
+    type Error = InvalidInputError;
+
+    /// Converts the reference to the center of the referenced square, see [`Mgrs::to_latlon`].
+    fn try_from(mgrs: Mgrs) -> Result<Self, Self::Error> {
+        mgrs.to_latlon()
+    }
+}
+
+impl fmt::Display for Utm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{} {:.0} {:.0}",
+            self.zone, self.band, self.easting, self.northing
+        )
+    }
+}
+
+impl fmt::Display for Mgrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{} {}{}", self.zone, self.band, self.column, self.row)?;
+        if self.precision > 0 {
+            let divisor = 10u32.pow(5 - self.precision.min(5) as u32);
+            let width = self.precision as usize;
+            write!(
+                f,
+                " {:0width$} {:0width$}",
+                self.easting / divisor,
+                self.northing / divisor,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Utm {
+    type Err = InvalidInputError;
+
+    /// Parses `<zone><band> <easting> <northing>`, e.g. `32U 691607 5334760`. The letter is always
+    /// read as a latitude band, not as a hemisphere.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidInputError::new(&format!("'{}' is not a UTM coordinate", s));
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        if parts.len() != 3 {
+            return Err(invalid());
+        }
+        let (zone, band) = parse_zone_band(parts[0]).ok_or_else(invalid)?;
+        let easting = parts[1].parse::<f64>().map_err(|_| invalid())?;
+        let northing = parts[2].parse::<f64>().map_err(|_| invalid())?;
+        if !easting.is_finite() || !northing.is_finite() {
+            return Err(invalid());
+        }
+        Ok(Utm {
+            zone,
+            band,
+            easting,
+            northing,
+        })
+    }
+}
+
+impl FromStr for Mgrs {
+    type Err = InvalidInputError;
+
+    /// Parses references with or without spaces, e.g. `32U PU 91845 32618` or `32UPU9184532618`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidInputError::new(&format!("'{}' is not an MGRS reference", s));
+        let compact: String = s
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_uppercase();
+        if !compact.is_ascii() {
+            return Err(invalid());
+        }
+
+        let zone_length = compact.chars().take_while(char::is_ascii_digit).count();
+        if zone_length == 0 || zone_length > 2 || compact.len() < zone_length + 3 {
+            return Err(invalid());
+        }
+        let (zone, band) = parse_zone_band(&compact[..zone_length + 1]).ok_or_else(invalid)?;
+        let mut letters = compact[zone_length + 1..].chars();
+        let column = letters.next().ok_or_else(invalid)?;
+        let row = letters.next().ok_or_else(invalid)?;
+        let digits = &compact[zone_length + 3..];
+        if digits.len() % 2 == 1 || digits.len() > 10 || !digits.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let precision = (digits.len() / 2) as u8;
+        let scale = 10u32.pow(5 - precision as u32);
+        let (easting, northing) = digits.split_at(digits.len() / 2);
+        let parse = |value: &str| value.parse::<u32>().map(|v| v * scale).unwrap_or(0);
+        let mgrs = Mgrs {
+            zone,
+            band,
+            column,
+            row,
+            easting: parse(easting),
+            northing: parse(northing),
+            precision,
+        };
+        mgrs.to_utm()?;
+        Ok(mgrs)
+    }
+}
+
+fn parse_zone_band(text: &str) -> Option<(u8, char)> {
+    let band = text.chars().last()?;
+    let zone = text[..text.len() - band.len_utf8()].parse::<u8>().ok()?;
+    let band = band.to_ascii_uppercase();
+    if check_zone(zone).is_err() || check_band(band).is_err() {
+        return None;
+    }
+    Some((zone, band))
+}
+
+fn check_zone(zone: u8) -> Result<(), InvalidInputError> {
+    if (1..=60).contains(&zone) {
+        Ok(())
+    } else {
+        Err(InvalidInputError::new(&format!(
+            "UTM zone must be between 1 and 60, got {}",
+            zone
+        )))
+    }
+}
+
+fn check_band(band: char) -> Result<(), InvalidInputError> {
+    if LAT_BANDS.contains(&(band as u8)) && band.is_ascii() {
+        Ok(())
+    } else {
+        Err(InvalidInputError::new(&format!(
+            "'{}' is not a latitude band",
+            band
+        )))
+    }
+}
+
+fn check_utm_latitude(lat: f64) -> Result<(), InvalidInputError> {
+    if (-80.0..=84.0).contains(&lat) {
+        Ok(())
+    } else {
+        Err(InvalidInputError::new(&format!(
+            "latitude {} is outside of the UTM grid (80°S to 84°N)",
+            lat
+        )))
+    }
+}
+
+fn zone_for(coords: &LatLon) -> u8 {
+    let lon = crate::geo::normalize_longitude(coords.lon);
+    let mut zone = ((lon + 180.0) / 6.0).floor() as u8 + 1;
+    // Southwest Norway
+    if (56.0..64.0).contains(&coords.lat) && (3.0..12.0).contains(&lon) {
+        zone = 32;
+    }
+    // Svalbard
+    if (72.0..=84.0).contains(&coords.lat) && (0.0..42.0).contains(&lon) {
+        zone = match lon {
+            _ if lon < 9.0 => 31,
+            _ if lon < 21.0 => 33,
+            _ if lon < 33.0 => 35,
+            _ => 37,
+        };
+    }
+    zone.min(60)
+}
+
+fn band_for(lat: f64) -> char {
+    let index = (((lat + 80.0) / 8.0).floor() as usize).min(LAT_BANDS.len() - 1);
+    LAT_BANDS[index] as char
+}
+
+fn central_meridian(zone: u8) -> f64 {
+    zone as f64 * 6.0 - 183.0
+}
+
+fn eccentricity_squared() -> f64 {
+    WGS84_F * (2.0 - WGS84_F)
+}
+
+/// Transverse Mercator projection after Snyder, "Map Projections: A Working Manual", p. 61.
+fn project(coords: &LatLon, zone: u8) -> (f64, f64) {
+    let e2 = eccentricity_squared();
+    let ep2 = e2 / (1.0 - e2);
+    let phi = coords.lat.to_radians();
+    let d_lambda =
+        crate::geo::normalize_longitude(coords.lon - central_meridian(zone)).to_radians();
+
+    let n = WGS84_A / (1.0 - e2 * phi.sin().powi(2)).sqrt();
+    let t = phi.tan().powi(2);
+    let c = ep2 * phi.cos().powi(2);
+    let a = phi.cos() * d_lambda;
+    let m = WGS84_A
+        * ((1.0 - e2 / 4.0 - 3.0 * e2.powi(2) / 64.0 - 5.0 * e2.powi(3) / 256.0) * phi
+            - (3.0 * e2 / 8.0 + 3.0 * e2.powi(2) / 32.0 + 45.0 * e2.powi(3) / 1024.0)
+                * (2.0 * phi).sin()
+            + (15.0 * e2.powi(2) / 256.0 + 45.0 * e2.powi(3) / 1024.0) * (4.0 * phi).sin()
+            - (35.0 * e2.powi(3) / 3072.0) * (6.0 * phi).sin());
+
+    let easting = K0
+        * n
+        * (a + (1.0 - t + c) * a.powi(3) / 6.0
+            + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0)
+        + FALSE_EASTING;
+    let mut northing = K0
+        * (m + n
+            * phi.tan()
+            * (a * a / 2.0
+                + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+                + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0));
+    if coords.lat < 0.0 {
+        northing += FALSE_NORTHING_SOUTH;
+    }
+    (easting, northing)
+}
+
+/// Inverse of [`project`].
+fn unproject(zone: u8, hemisphere: Hemisphere, easting: f64, northing: f64) -> LatLon {
+    let e2 = eccentricity_squared();
+    let ep2 = e2 / (1.0 - e2);
+    let northing = match hemisphere {
+        Hemisphere::North => northing,
+        Hemisphere::South => northing - FALSE_NORTHING_SOUTH,
+    };
+
+    let m = northing / K0;
+    let mu = m / (WGS84_A * (1.0 - e2 / 4.0 - 3.0 * e2.powi(2) / 64.0 - 5.0 * e2.powi(3) / 256.0));
+    let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+    let phi1 = mu
+        + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+        + (21.0 * e1 * e1 / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+        + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+        + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+
+    let n1 = WGS84_A / (1.0 - e2 * phi1.sin().powi(2)).sqrt();
+    let t1 = phi1.tan().powi(2);
+    let c1 = ep2 * phi1.cos().powi(2);
+    let r1 = WGS84_A * (1.0 - e2) / (1.0 - e2 * phi1.sin().powi(2)).powf(1.5);
+    let d = (easting - FALSE_EASTING) / (n1 * K0);
+
+    let phi = phi1
+        - (n1 * phi1.tan() / r1)
+            * (d * d / 2.0
+                - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1 * c1 - 9.0 * ep2) * d.powi(4) / 24.0
+                + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1 * t1 - 252.0 * ep2 - 3.0 * c1 * c1)
+                    * d.powi(6)
+                    / 720.0);
+    let lambda = (d - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+        + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1 * c1 + 8.0 * ep2 + 24.0 * t1 * t1) * d.powi(5)
+            / 120.0)
+        / phi1.cos();
+
+    LatLon::new(
+        phi.to_degrees(),
+        crate::geo::normalize_longitude(central_meridian(zone) + lambda.to_degrees()),
+    )
+}
@@ -1,4 +1,6 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use crate::data::PhotonFeature;
 
 #[derive(Debug, Deserialize)]
 pub struct PhotonFeatureCollection {
@@ -11,20 +13,20 @@ impl PhotonFeatureCollection {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct PhotonFeatureRaw {
     pub geometry: Geometry,
     pub r#type: String,
     pub properties: Properties,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Geometry {
     pub coordinates: Vec<f64>,
     pub r#type: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Properties {
     pub osm_id: u64,
     pub osm_type: String,
@@ -46,3 +48,41 @@ pub struct Properties {
     pub street: Option<String>,
     pub housenumber: Option<String>,
 }
+
+impl From<&PhotonFeature> for PhotonFeatureRaw {
+    fn from(feature: &PhotonFeature) -> Self {
+        PhotonFeatureRaw {
+            geometry: Geometry {
+                coordinates: vec![feature.coords.lon, feature.coords.lat],
+                r#type: String::from("Point"),
+            },
+            r#type: String::from("Feature"),
+            properties: Properties {
+                osm_id: feature.osm_id,
+                osm_type: String::from(feature.osm_type.code()),
+                osm_key: feature.osm_key.clone(),
+                osm_value: feature.osm_value.clone(),
+                r#type: feature.r#type.clone(),
+                extent: feature.extent.map(|extent| {
+                    vec![
+                        extent.south_west.lon,
+                        extent.north_east.lat,
+                        extent.north_east.lon,
+                        extent.south_west.lat,
+                    ]
+                }),
+                name: feature.name.clone(),
+                country: feature.country.clone(),
+                countrycode: feature.country_iso_code.clone(),
+                state: feature.state.clone(),
+                county: feature.county.clone(),
+                city: feature.city.clone(),
+                locality: None,
+                postcode: feature.postcode.clone(),
+                district: feature.district.clone(),
+                street: feature.street.clone(),
+                housenumber: feature.house_number.clone(),
+            },
+        }
+    }
+}
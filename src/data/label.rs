@@ -0,0 +1,297 @@
+//! Display labels for features, formatted according to the address conventions of the feature's
+//! country.
+//!
+//! Templates consist of lines with placeholders such as `{house_number} {street}`. Placeholders
+//! without a value are left out together with the separator in front of them, brackets around
+//! them and text after the last placeholder of the line. Empty lines are dropped. Available
+//! placeholders are `name`, `street`, `house_number`, `postcode`, `city`, `district`, `county`,
+//! `state`, `country` and `country_code`.
+
+use std::collections::HashMap;
+
+use crate::error::InvalidInputError;
+use crate::PhotonFeature;
+
+const PLACEHOLDERS: [&str; 10] = [
+    "name",
+    "street",
+    "house_number",
+    "postcode",
+    "city",
+    "district",
+    "county",
+    "state",
+    "country",
+    "country_code",
+];
+
+/// Street before house number, postcode before city. Used for most of continental Europe and as
+/// fallback for countries without a template.
+const DEFAULT_TEMPLATE: &str = "{name}\n{street} {house_number}\n{postcode} {city}\n{country}";
+
+const BUILT_IN_TEMPLATES: &[(&[&str], &str)] = &[
+    (
+        &["FR", "LU", "MC"],
+        "{name}\n{house_number} {street}\n{postcode} {city}\n{country}",
+    ),
+    (
+        &["GB", "IE"],
+        "{name}\n{house_number} {street}\n{city}\n{postcode}\n{country}",
+    ),
+    (
+        &["US", "CA", "AU"],
+        "{name}\n{house_number} {street}\n{city}, {state} {postcode}\n{country}",
+    ),
+    (
+        &["NZ"],
+        "{name}\n{house_number} {street}\n{district}\n{city} {postcode}\n{country}",
+    ),
+    (
+        &["BR"],
+        "{name}\n{street}, {house_number}\n{district}\n{city} - {state}\n{postcode}\n{country}",
+    ),
+    (
+        &["IT"],
+        "{name}\n{street} {house_number}\n{postcode} {city} {county}\n{country}",
+    ),
+    (
+        &["ES"],
+        "{name}\n{street}, {house_number}\n{postcode} {city}\n{country}",
+    ),
+];
+
+/// Brackets that are left out together with the placeholder they enclose.
+const OPENING_BRACKETS: [char; 2] = ['(', '['];
+const CLOSING_BRACKETS: [char; 2] = [')', ']'];
+
+thread_local! {
+    /// The formatter used by [`PhotonFeature::label`], created on first use in each thread.
+    static DEFAULT_FORMATTER: AddressFormatter = AddressFormatter::default();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    /// A placeholder with the text that is only rendered together with its value.
+    Placeholder {
+        name: String,
+        prefix: String,
+        suffix: String,
+    },
+}
+
+/// A parsed address template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressTemplate {
+    lines: Vec<Vec<Segment>>,
+}
+
+impl AddressTemplate {
+    /// Parses a template. Lines are separated by `\n`, placeholders are written as `{street}`.
+    pub fn parse(template: &str) -> Result<Self, InvalidInputError> {
+        let lines = template
+            .lines()
+            .map(parse_line)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AddressTemplate { lines })
+    }
+
+    fn render(&self, values: &HashMap<&str, String>) -> Vec<String> {
+        let mut rendered: Vec<String> = Vec::new();
+        for line in &self.lines {
+            let text = render_line(line, values);
+            if !text.is_empty() && !rendered.contains(&text) {
+                rendered.push(text);
+            }
+        }
+        rendered
+    }
+}
+
+/// Formats features into one-line or multi-line labels. This struct implements a builder pattern,
+/// so custom templates can be easily added.
+#[derive(Debug, Clone)]
+pub struct AddressFormatter {
+    templates: HashMap<String, AddressTemplate>,
+    default_template: AddressTemplate,
+}
+
+impl Default for AddressFormatter {
+    fn default() -> Self {
+        let mut templates = HashMap::new();
+        for (countries, template) in BUILT_IN_TEMPLATES {
+            let template = AddressTemplate::parse(template).expect("built-in template is valid");
+            for country in countries.iter() {
+                templates.insert(country.to_string(), template.clone());
+            }
+        }
+        AddressFormatter {
+            templates,
+            default_template: AddressTemplate::parse(DEFAULT_TEMPLATE)
+                .expect("built-in template is valid"),
+        }
+    }
+}
+
+impl AddressFormatter {
+    /// Construct a new `AddressFormatter` with the built-in templates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `template` for features whose `country_iso_code` is `country_code`.
+    pub fn template(mut self, country_code: &str, template: AddressTemplate) -> Self {
+        self.templates.insert(country_code.to_uppercase(), template);
+        self
+    }
+
+    /// Use `template` for features of countries without a specific template.
+    pub fn default_template(mut self, template: AddressTemplate) -> Self {
+        self.default_template = template;
+        self
+    }
+
+    /// The non-empty lines of the label.
+    pub fn lines(&self, feature: &PhotonFeature) -> Vec<String> {
+        let template = feature
+            .country_iso_code
+            .as_ref()
+            .and_then(|code| self.templates.get(&code.to_uppercase()))
+            .unwrap_or(&self.default_template);
+        template.render(&label_values(feature))
+    }
+
+    /// The label on a single line, with lines separated by `, `.
+    pub fn one_line(&self, feature: &PhotonFeature) -> String {
+        self.lines(feature).join(", ")
+    }
+
+    /// The label with lines separated by `\n`.
+    pub fn multi_line(&self, feature: &PhotonFeature) -> String {
+        self.lines(feature).join("\n")
+    }
+}
+
+impl PhotonFeature {
+    /// A one-line label using the built-in templates of [`AddressFormatter`].
+    pub fn label(&self) -> String {
+        DEFAULT_FORMATTER.with(|formatter| formatter.one_line(self))
+    }
+}
+
+fn parse_line(line: &str) -> Result<Vec<Segment>, InvalidInputError> {
+    let mut segments = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find('{') {
+        let literal = &rest[..start];
+        let prefix_start = literal.trim_end_matches(&OPENING_BRACKETS[..]).len();
+        if prefix_start > 0 {
+            segments.push(Segment::Literal(literal[..prefix_start].to_string()));
+        }
+        let end = rest[start..].find('}').ok_or_else(|| {
+            InvalidInputError::new(&format!("unclosed placeholder in template line '{}'", line))
+        })? + start;
+        let name = rest[start + 1..end].trim();
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(InvalidInputError::new(&format!(
+                "unknown placeholder '{{{}}}'",
+                name
+            )));
+        }
+        let after = &rest[end + 1..];
+        let suffix_end = after.len() - after.trim_start_matches(&CLOSING_BRACKETS[..]).len();
+        segments.push(Segment::Placeholder {
+            name: name.to_string(),
+            prefix: literal[prefix_start..].to_string(),
+            suffix: after[..suffix_end].to_string(),
+        });
+        rest = &after[suffix_end..];
+    }
+    match segments.last_mut() {
+        Some(Segment::Placeholder { suffix, .. }) => suffix.push_str(rest),
+        _ if !rest.is_empty() => segments.push(Segment::Literal(rest.to_string())),
+        _ => {}
+    }
+    Ok(segments)
+}
+
+/// Renders a line, dropping the separator in front of placeholders without a value.
+fn render_line(line: &[Segment], values: &HashMap<&str, String>) -> String {
+    let mut leading = String::new();
+    let mut text = String::new();
+    let mut separator = String::new();
+    let mut seen_placeholder = false;
+
+    for segment in line {
+        match segment {
+            Segment::Literal(literal) if !seen_placeholder => leading.push_str(literal),
+            Segment::Literal(literal) => separator.push_str(literal),
+            Segment::Placeholder {
+                name,
+                prefix,
+                suffix,
+            } => {
+                seen_placeholder = true;
+                if let Some(value) = values.get(name.as_str()) {
+                    if !text.is_empty() {
+                        text.push_str(&separator);
+                    }
+                    text.push_str(prefix);
+                    text.push_str(value);
+                    text.push_str(suffix);
+                }
+                separator.clear();
+            }
+        }
+    }
+    if text.is_empty() {
+        return text;
+    }
+    format!("{}{}", leading, text).trim().to_string()
+}
+
+/// The values of all placeholders, with parts that would repeat another part removed.
+fn label_values(feature: &PhotonFeature) -> HashMap<&'static str, String> {
+    let mut values: HashMap<&'static str, String> = HashMap::new();
+    let parts = [
+        ("name", &feature.name),
+        ("street", &feature.street),
+        ("house_number", &feature.house_number),
+        ("postcode", &feature.postcode),
+        ("city", &feature.city),
+        ("district", &feature.district),
+        ("county", &feature.county),
+        ("state", &feature.state),
+        ("country", &feature.country),
+        ("country_code", &feature.country_iso_code),
+    ];
+    for (key, value) in parts.iter() {
+        if let Some(value) = value.as_ref().map(|v| v.trim()).filter(|v| !v.is_empty()) {
+            values.insert(key, value.to_string());
+        }
+    }
+
+    let same = |a: &str, b: &str| a.to_lowercase() == b.to_lowercase();
+    if let Some(name) = values.get("name").cloned() {
+        let street = values.get("street").cloned().unwrap_or_default();
+        let number = values.get("house_number").cloned().unwrap_or_default();
+        let repeats_street = same(&name, &street)
+            || same(&name, &format!("{} {}", street, number))
+            || same(&name, &format!("{} {}", number, street));
+        let repeats_area = ["district", "city", "county", "state", "country"]
+            .iter()
+            .any(|key| values.get(key).map_or(false, |value| same(&name, value)));
+        if repeats_street || repeats_area {
+            values.remove("name");
+        }
+    }
+    // City states like Berlin or Vienna repeat their name as state and sometimes as county.
+    for key in ["district", "county", "state"] {
+        if let (Some(value), Some(city)) = (values.get(key), values.get("city")) {
+            if same(value, city) {
+                values.remove(key);
+            }
+        }
+    }
+    values
+}
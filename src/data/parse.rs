@@ -0,0 +1,276 @@
+//! Parsing of coordinates that users type or paste into a search field.
+//!
+//! Supported are decimal degrees (`48.137, 11.575`, `48.137N 11.575E`), degrees, minutes and
+//! seconds (`48°08'14"N 11°34'31"E`), degrees and decimal minutes (`N 48° 08.233 E 011° 34.517`)
+//! and `geo:` URIs (`geo:48.137,11.575`).
+
+use std::str::FromStr;
+
+use crate::error::InvalidInputError;
+use crate::LatLon;
+
+/// Parses a coordinate in one of the supported formats. Without hemisphere letters, the latitude
+/// is expected first.
+pub fn parse_coordinates(input: &str) -> Result<LatLon, InvalidInputError> {
+    let input = input.trim();
+    if let Some(scheme) = input.get(..4) {
+        if scheme.eq_ignore_ascii_case("geo:") {
+            return parse_geo_uri(&input[4..]);
+        }
+    }
+
+    let tokens = tokenize(input)?;
+    let groups = split_groups(&tokens);
+    if groups.len() != 2 {
+        return Err(invalid(input));
+    }
+
+    let first = groups[0].value().ok_or_else(|| invalid(input))?;
+    let second = groups[1].value().ok_or_else(|| invalid(input))?;
+    let (lat, lon) = match (groups[0].axis(), groups[1].axis()) {
+        (Axis::Unknown, Axis::Unknown) | (Axis::Lat, Axis::Lon) => (first, second),
+        (Axis::Lon, Axis::Lat) => (second, first),
+        _ => return Err(invalid(input)),
+    };
+    LatLon::try_new(lat, lon)
+}
+
+/// Whether `input` looks like a coordinate rather than a text query.
+///
+/// Besides being parseable, the input needs a decimal point, a separator such as a comma, a unit
+/// or a hemisphere letter, or be a `geo:` URI. Two bare integers such as `7 11` are more likely a
+/// house number and a postcode than a position.
+pub fn is_coordinate(input: &str) -> bool {
+    if parse_coordinates(input).is_err() {
+        return false;
+    }
+    let input = input.trim();
+    let is_geo_uri = input
+        .get(..4)
+        .map_or(false, |scheme| scheme.eq_ignore_ascii_case("geo:"));
+    is_geo_uri
+        || tokenize(input).map_or(false, |tokens| {
+            tokens.iter().any(|token| match token {
+                Token::Number { fraction, .. } => *fraction,
+                Token::Unit(_) | Token::Hemisphere(_) | Token::Separator => true,
+            })
+        })
+}
+
+impl FromStr for LatLon {
+    type Err = InvalidInputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_coordinates(s)
+    }
+}
+
+fn parse_geo_uri(path: &str) -> Result<LatLon, InvalidInputError> {
+    // geo:lat,lon[,alt][;crs=...;u=...][?query]
+    let end = path.find([';', '?']).unwrap_or(path.len());
+    let mut values = path[..end]
+        .split(',')
+        .map(|value| value.trim().parse::<f64>());
+    match (values.next(), values.next(), values.next(), values.next()) {
+        (Some(Ok(lat)), Some(Ok(lon)), None | Some(Ok(_)), None) => LatLon::try_new(lat, lon),
+        _ => Err(invalid(path)),
+    }
+}
+
+fn invalid(input: &str) -> InvalidInputError {
+    InvalidInputError::new(&format!("'{}' is not a coordinate", input))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+    Degrees,
+    Minutes,
+    Seconds,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Number {
+        value: f64,
+        negative: bool,
+        fraction: bool,
+    },
+    Unit(Unit),
+    Hemisphere(char),
+    Separator,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, InvalidInputError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            ',' | ';' | '/' => {
+                tokens.push(Token::Separator);
+                i += 1;
+            }
+            '°' | 'º' | '˚' => {
+                tokens.push(Token::Unit(Unit::Degrees));
+                i += 1;
+            }
+            '\'' | '′' | '’' => {
+                // Two single quotes are commonly typed instead of a double quote.
+                if chars.get(i + 1) == Some(&'\'') {
+                    tokens.push(Token::Unit(Unit::Seconds));
+                    i += 2;
+                } else {
+                    tokens.push(Token::Unit(Unit::Minutes));
+                    i += 1;
+                }
+            }
+            '"' | '″' | '”' => {
+                tokens.push(Token::Unit(Unit::Seconds));
+                i += 1;
+            }
+            '+' | '-' | '0'..='9' | '.' => {
+                let start = i;
+                if c == '+' || c == '-' {
+                    i += 1;
+                }
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text.parse::<f64>().map_err(|_| invalid(input))?;
+                tokens.push(Token::Number {
+                    value: value.abs(),
+                    negative: text.starts_with('-'),
+                    fraction: text.contains('.'),
+                });
+            }
+            _ if c.is_alphabetic() => {
+                let start = i;
+                while i < chars.len() && chars[i].is_alphabetic() {
+                    i += 1;
+                }
+                match chars[start..i] {
+                    [letter] if "NSEWnsew".contains(letter) => {
+                        tokens.push(Token::Hemisphere(letter.to_ascii_uppercase()))
+                    }
+                    _ => return Err(invalid(input)),
+                }
+            }
+            _ => return Err(invalid(input)),
+        }
+    }
+    Ok(tokens)
+}
+
+enum Axis {
+    Lat,
+    Lon,
+    Unknown,
+}
+
+#[derive(Default)]
+struct Group {
+    parts: Vec<(f64, bool, bool, Option<Unit>)>,
+    hemisphere: Option<char>,
+}
+
+impl Group {
+    fn axis(&self) -> Axis {
+        match self.hemisphere {
+            Some('N') | Some('S') => Axis::Lat,
+            Some('E') | Some('W') => Axis::Lon,
+            _ => Axis::Unknown,
+        }
+    }
+
+    /// Combines degrees, minutes and seconds into decimal degrees. Parts without a unit are
+    /// assigned one by their position.
+    fn value(&self) -> Option<f64> {
+        if self.parts.is_empty() || self.parts.len() > 3 {
+            return None;
+        }
+        let mut total = 0.0;
+        let mut negative = false;
+        let mut expected = [Unit::Degrees, Unit::Minutes, Unit::Seconds].iter();
+
+        for (index, (value, sign, fraction, unit)) in self.parts.iter().enumerate() {
+            let position = *expected.next()?;
+            let unit = unit.unwrap_or(position);
+            let last = index == self.parts.len() - 1;
+            if unit != position || (*fraction && !last) || (*sign && index > 0) {
+                return None;
+            }
+            match unit {
+                Unit::Degrees => {
+                    negative = *sign;
+                    total += value;
+                }
+                Unit::Minutes | Unit::Seconds if *value >= 60.0 => return None,
+                Unit::Minutes => total += value / 60.0,
+                Unit::Seconds => total += value / 3600.0,
+            }
+        }
+
+        if let Some('S') | Some('W') = self.hemisphere {
+            if negative {
+                return None; // a sign and a hemisphere contradict each other
+            }
+            negative = true;
+        }
+        Some(if negative { -total } else { total })
+    }
+}
+
+fn split_groups(tokens: &[Token]) -> Vec<Group> {
+    let prefix_hemispheres = matches!(tokens.first(), Some(Token::Hemisphere(_)));
+    // Without any structure, a plain list of numbers like "48.137 11.575" is one value each.
+    let unstructured = tokens
+        .iter()
+        .all(|token| matches!(token, Token::Number { .. }));
+
+    let mut groups = Vec::new();
+    let mut current = Group::default();
+
+    for (i, token) in tokens.iter().enumerate() {
+        match *token {
+            Token::Hemisphere(hemisphere) if prefix_hemispheres => {
+                if !current.parts.is_empty() || current.hemisphere.is_some() {
+                    groups.push(std::mem::take(&mut current));
+                }
+                current.hemisphere = Some(hemisphere);
+            }
+            Token::Hemisphere(hemisphere) => {
+                current.hemisphere = Some(hemisphere);
+                groups.push(std::mem::take(&mut current));
+            }
+            Token::Separator => {
+                if !current.parts.is_empty() {
+                    groups.push(std::mem::take(&mut current));
+                }
+            }
+            Token::Number {
+                value,
+                negative,
+                fraction,
+            } => {
+                let unit = match tokens.get(i + 1) {
+                    Some(Token::Unit(unit)) => Some(*unit),
+                    _ => None,
+                };
+                let starts_new_value = unstructured || unit == Some(Unit::Degrees);
+                if starts_new_value && !current.parts.is_empty() {
+                    groups.push(std::mem::take(&mut current));
+                }
+                current.parts.push((value, negative, fraction, unit));
+            }
+            Token::Unit(_) => {}
+        }
+    }
+    if !current.parts.is_empty() {
+        groups.push(current);
+    }
+    groups
+}
@@ -0,0 +1,281 @@
+//! Offline encoding and decoding of [Open Location Codes](https://github.com/google/open-location-code)
+//! (Plus Codes), such as `8FWH4HPG+R2` (full) or `4HPG+R2` (short).
+
+use crate::error::InvalidInputError;
+use crate::{BoundingBox, LatLon, PhotonFeature};
+
+const ALPHABET: &[u8; 20] = b"23456789CFGHJMPQRVWX";
+const ENCODING_BASE: i64 = 20;
+const SEPARATOR: char = '+';
+const SEPARATOR_POSITION: usize = 8;
+const PADDING: char = '0';
+
+const PAIR_CODE_LENGTH: usize = 10;
+const MAX_DIGIT_COUNT: usize = 15;
+const GRID_COLUMNS: i64 = 4;
+const GRID_ROWS: i64 = 5;
+const GRID_CODE_LENGTH: u32 = (MAX_DIGIT_COUNT - PAIR_CODE_LENGTH) as u32;
+
+const PAIR_PRECISION: i64 = 8000; // ENCODING_BASE^3
+const PAIR_FIRST_PLACE_VALUE: i64 = 160_000; // ENCODING_BASE^4
+const FINAL_LAT_PRECISION: i64 = PAIR_PRECISION * 3125; // GRID_ROWS^GRID_CODE_LENGTH
+const FINAL_LON_PRECISION: i64 = PAIR_PRECISION * 1024; // GRID_COLUMNS^GRID_CODE_LENGTH
+
+/// Code length used by [`LatLon::to_plus_code`], which corresponds to an area of roughly 14 by 14
+/// meters.
+pub const DEFAULT_CODE_LENGTH: usize = 10;
+
+/// The area described by a Plus Code.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CodeArea {
+    pub bounds: BoundingBox,
+    pub code_length: usize,
+}
+
+impl CodeArea {
+    /// The center of the area, which is the canonical coordinate of a code.
+    pub fn center(&self) -> LatLon {
+        LatLon::new(
+            ((self.bounds.south_west.lat + self.bounds.north_east.lat) / 2.0).min(90.0),
+            ((self.bounds.south_west.lon + self.bounds.north_east.lon) / 2.0).min(180.0),
+        )
+    }
+}
+
+/// Encodes `coords` into a full Plus Code with `code_length` digits (excluding the `+`).
+///
+/// Valid lengths are 2, 4, 6, 8 and anything from 10 to 15.
+pub fn encode(coords: &LatLon, code_length: usize) -> Result<String, InvalidInputError> {
+    coords.validate()?;
+    if code_length < 2 || (code_length < PAIR_CODE_LENGTH && code_length % 2 == 1) {
+        return Err(InvalidInputError::new(&format!(
+            "invalid Plus Code length {}",
+            code_length
+        )));
+    }
+    let code_length = code_length.min(MAX_DIGIT_COUNT);
+
+    let mut lat = coords.lat;
+    if lat >= 90.0 {
+        lat -= latitude_precision(code_length);
+    }
+    let lon = crate::geo::normalize_longitude(coords.lon);
+
+    let mut lat_value = round_to(6, (lat + 90.0) * FINAL_LAT_PRECISION as f64).floor() as i64;
+    let mut lon_value = round_to(6, (lon + 180.0) * FINAL_LON_PRECISION as f64).floor() as i64;
+
+    let mut digits = Vec::with_capacity(MAX_DIGIT_COUNT);
+    if code_length > PAIR_CODE_LENGTH {
+        for _ in 0..GRID_CODE_LENGTH {
+            let index = (lat_value % GRID_ROWS) * GRID_COLUMNS + lon_value % GRID_COLUMNS;
+            digits.push(ALPHABET[index as usize]);
+            lat_value /= GRID_ROWS;
+            lon_value /= GRID_COLUMNS;
+        }
+    } else {
+        lat_value /= GRID_ROWS.pow(GRID_CODE_LENGTH);
+        lon_value /= GRID_COLUMNS.pow(GRID_CODE_LENGTH);
+    }
+    for _ in 0..PAIR_CODE_LENGTH / 2 {
+        digits.push(ALPHABET[(lon_value % ENCODING_BASE) as usize]);
+        digits.push(ALPHABET[(lat_value % ENCODING_BASE) as usize]);
+        lat_value /= ENCODING_BASE;
+        lon_value /= ENCODING_BASE;
+    }
+    digits.reverse();
+
+    let mut code: String = digits[..code_length].iter().map(|&c| c as char).collect();
+    if code_length < SEPARATOR_POSITION {
+        code.extend(std::iter::repeat(PADDING).take(SEPARATOR_POSITION - code_length));
+    }
+    code.insert(SEPARATOR_POSITION, SEPARATOR);
+    Ok(code)
+}
+
+/// Decodes a full Plus Code into the area it describes.
+pub fn decode(code: &str) -> Result<CodeArea, InvalidInputError> {
+    let code = code.trim();
+    if !is_full(code) {
+        return Err(invalid(code));
+    }
+    let digits: Vec<i64> = code
+        .chars()
+        .filter(|&c| c != SEPARATOR && c != PADDING)
+        .take(MAX_DIGIT_COUNT)
+        .map(|c| digit_value(c).unwrap_or(0))
+        .collect();
+
+    let mut lat = -90 * PAIR_PRECISION;
+    let mut lon = -180 * PAIR_PRECISION;
+    let pair_digits = digits.len().min(PAIR_CODE_LENGTH);
+    let mut place_value = PAIR_FIRST_PLACE_VALUE;
+    for i in (0..pair_digits).step_by(2) {
+        lat += digits[i] * place_value;
+        lon += digits[i + 1] * place_value;
+        if i < pair_digits - 2 {
+            place_value /= ENCODING_BASE;
+        }
+    }
+    let mut lat_precision = place_value as f64 / PAIR_PRECISION as f64;
+    let mut lon_precision = lat_precision;
+
+    let (mut grid_lat, mut grid_lon) = (0, 0);
+    if digits.len() > PAIR_CODE_LENGTH {
+        let mut row_value = GRID_ROWS.pow(GRID_CODE_LENGTH - 1);
+        let mut column_value = GRID_COLUMNS.pow(GRID_CODE_LENGTH - 1);
+        for (i, digit) in digits.iter().enumerate().skip(PAIR_CODE_LENGTH) {
+            grid_lat += digit / GRID_COLUMNS * row_value;
+            grid_lon += digit % GRID_COLUMNS * column_value;
+            if i < digits.len() - 1 {
+                row_value /= GRID_ROWS;
+                column_value /= GRID_COLUMNS;
+            }
+        }
+        lat_precision = row_value as f64 / FINAL_LAT_PRECISION as f64;
+        lon_precision = column_value as f64 / FINAL_LON_PRECISION as f64;
+    }
+
+    let south = lat as f64 / PAIR_PRECISION as f64 + grid_lat as f64 / FINAL_LAT_PRECISION as f64;
+    let west = lon as f64 / PAIR_PRECISION as f64 + grid_lon as f64 / FINAL_LON_PRECISION as f64;
+    Ok(CodeArea {
+        bounds: BoundingBox {
+            south_west: LatLon::new(round_to(14, south), round_to(14, west)),
+            north_east: LatLon::new(
+                round_to(14, south + lat_precision),
+                round_to(14, west + lon_precision),
+            ),
+        },
+        code_length: digits.len(),
+    })
+}
+
+/// Whether `code` is a syntactically valid full or short Plus Code.
+pub fn is_valid(code: &str) -> bool {
+    let code = code.trim();
+    let separator = match code.find(SEPARATOR) {
+        Some(position) => position,
+        None => return false,
+    };
+    if code.rfind(SEPARATOR) != Some(separator)
+        || separator > SEPARATOR_POSITION
+        || separator % 2 == 1
+        || code.len() - separator == 2
+    {
+        return false;
+    }
+
+    if let Some(padding) = code.find(PADDING) {
+        // Padding must follow an even number of digits, come in pairs and end the code.
+        let padding_end = code.rfind(PADDING).unwrap_or(padding) + 1;
+        if padding == 0
+            || padding % 2 == 1
+            || separator < SEPARATOR_POSITION
+            || code[padding..padding_end].chars().any(|c| c != PADDING)
+            || (padding_end - padding) % 2 == 1
+            || padding_end != separator
+            || code.len() > separator + 1
+        {
+            return false;
+        }
+    }
+
+    code.chars()
+        .all(|c| c == SEPARATOR || c == PADDING || digit_value(c).is_some())
+}
+
+/// Whether `code` is a valid short code, i.e. one that lacks leading digits and needs a reference
+/// location to be recovered.
+pub fn is_short(code: &str) -> bool {
+    is_valid(code) && code.trim().find(SEPARATOR) < Some(SEPARATOR_POSITION)
+}
+
+/// Whether `code` is a valid full code that describes a location on its own.
+pub fn is_full(code: &str) -> bool {
+    if !is_valid(code) || is_short(code) {
+        return false;
+    }
+    let mut chars = code.trim().chars();
+    let first_lat = chars.next().and_then(digit_value).unwrap_or(0) * ENCODING_BASE;
+    let first_lon = chars.next().and_then(digit_value).unwrap_or(0) * ENCODING_BASE;
+    first_lat < 180 && first_lon < 360
+}
+
+/// Recovers the full code from a short `code` by choosing the matching area that is closest to
+/// `reference`. Full codes are returned unchanged.
+pub fn recover_nearest(code: &str, reference: &LatLon) -> Result<String, InvalidInputError> {
+    let code = code.trim().to_uppercase();
+    if is_full(&code) {
+        return Ok(code);
+    }
+    if !is_short(&code) {
+        return Err(invalid(&code));
+    }
+    reference.validate()?;
+
+    let padding_length = SEPARATOR_POSITION - code.find(SEPARATOR).unwrap_or(0);
+    let resolution = 20f64.powf(2.0 - padding_length as f64 / 2.0);
+    let half_resolution = resolution / 2.0;
+
+    let prefix = encode(reference, DEFAULT_CODE_LENGTH)?;
+    let area = decode(&(prefix[..padding_length].to_string() + &code))?;
+    let mut center = area.center();
+
+    if reference.lat + half_resolution < center.lat && center.lat - resolution >= -90.0 {
+        center.lat -= resolution;
+    } else if reference.lat - half_resolution > center.lat && center.lat + resolution <= 90.0 {
+        center.lat += resolution;
+    }
+    if reference.lon + half_resolution < center.lon {
+        center.lon -= resolution;
+    } else if reference.lon - half_resolution > center.lon {
+        center.lon += resolution;
+    }
+    center.lon = crate::geo::normalize_longitude(center.lon);
+
+    encode(&center, area.code_length)
+}
+
+impl LatLon {
+    /// The Plus Code of this coordinate with the default length of 10 digits.
+    pub fn to_plus_code(&self) -> Result<String, InvalidInputError> {
+        encode(self, DEFAULT_CODE_LENGTH)
+    }
+
+    /// The center of the area described by the full Plus Code `code`.
+    pub fn from_plus_code(code: &str) -> Result<LatLon, InvalidInputError> {
+        decode(code).map(|area| area.center())
+    }
+}
+
+impl PhotonFeature {
+    /// The Plus Code of the feature's coordinates with the default length of 10 digits.
+    pub fn plus_code(&self) -> Option<String> {
+        self.coords.to_plus_code().ok()
+    }
+}
+
+fn digit_value(c: char) -> Option<i64> {
+    let c = c.to_ascii_uppercase();
+    ALPHABET
+        .iter()
+        .position(|&digit| digit as char == c)
+        .map(|index| index as i64)
+}
+
+fn latitude_precision(code_length: usize) -> f64 {
+    if code_length <= PAIR_CODE_LENGTH {
+        20f64.powi(2 - code_length as i32 / 2)
+    } else {
+        20f64.powi(-3) / (GRID_ROWS as f64).powi(code_length as i32 - PAIR_CODE_LENGTH as i32)
+    }
+}
+
+/// Rounds to a number of decimals to absorb floating point errors.
+fn round_to(decimals: i32, value: f64) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+fn invalid(code: &str) -> InvalidInputError {
+    InvalidInputError::new(&format!("'{}' is not a valid Plus Code", code))
+}
@@ -0,0 +1,567 @@
+//! Confidence scoring for search results.
+//!
+//! Photon ranks its results but does not say how well they match. [`score`] compares a query with
+//! the address components of a feature and returns a confidence between `0` and `1` together with
+//! a breakdown per field, so callers can decide whether the top result can be trusted.
+//!
+//! Strings are compared after normalisation: case, diacritics and punctuation are ignored and
+//! common abbreviations such as `str.` are expanded. House numbers and postcodes have to agree
+//! exactly for a full match. Results that are coarser than the query, e.g. a street for a query
+//! with a house number, are penalised.
+
+use crate::data::address::StructuredAddress;
+use crate::{FeatureType, PhotonFeature};
+
+/// Weights of the fields when scoring a [`StructuredAddress`].
+const WEIGHTS: [(AddressField, f64); 7] = [
+    (AddressField::Name, 0.15),
+    (AddressField::Street, 0.3),
+    (AddressField::HouseNumber, 0.2),
+    (AddressField::Postcode, 0.2),
+    (AddressField::City, 0.2),
+    (AddressField::State, 0.05),
+    (AddressField::Country, 0.05),
+];
+
+/// Factor applied per level of granularity a result is coarser than the query.
+const GRANULARITY_PENALTY: f64 = 0.6;
+
+/// Factor applied to results without a known granularity, such as POIs.
+const UNKNOWN_GRANULARITY: f64 = 0.9;
+
+/// Minimum similarity for two words of a free-text query to be considered the same.
+const TOKEN_THRESHOLD: f64 = 0.85;
+
+const ABBREVIATIONS: [(&str, &str); 6] = [
+    ("str", "strasse"),
+    ("ave", "avenue"),
+    ("rd", "road"),
+    ("blvd", "boulevard"),
+    ("hwy", "highway"),
+    ("ln", "lane"),
+];
+
+/// The query to compare results with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchQuery {
+    /// The free-text query that was sent to Photon.
+    Text(String),
+    /// A query split into address components.
+    Structured(StructuredAddress),
+}
+
+impl From<&str> for MatchQuery {
+    fn from(query: &str) -> Self {
+        MatchQuery::Text(query.to_string())
+    }
+}
+
+impl From<String> for MatchQuery {
+    fn from(query: String) -> Self {
+        MatchQuery::Text(query)
+    }
+}
+
+impl From<StructuredAddress> for MatchQuery {
+    fn from(address: StructuredAddress) -> Self {
+        MatchQuery::Structured(address)
+    }
+}
+
+impl From<&StructuredAddress> for MatchQuery {
+    fn from(address: &StructuredAddress) -> Self {
+        MatchQuery::Structured(address.clone())
+    }
+}
+
+/// The address components that are compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressField {
+    Name,
+    Street,
+    HouseNumber,
+    Postcode,
+    City,
+    State,
+    Country,
+}
+
+/// How well a single field of the result matches the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMatch {
+    pub field: AddressField,
+    /// The part of the query that was compared with this field.
+    pub query: String,
+    /// The value of the field in the result, if any.
+    pub feature: Option<String>,
+    /// Similarity between `0` (no match) and `1` (exact match after normalisation).
+    pub similarity: f64,
+}
+
+/// The confidence that a result matches the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchScore {
+    /// Overall confidence between `0` and `1`.
+    pub confidence: f64,
+    /// Factor between `0` and `1` that was applied because the result is coarser than the query.
+    pub granularity: f64,
+    /// The compared fields.
+    pub fields: Vec<FieldMatch>,
+}
+
+impl MatchScore {
+    /// The match of `field`, if it was compared.
+    pub fn field(&self, field: AddressField) -> Option<&FieldMatch> {
+        self.fields.iter().find(|m| m.field == field)
+    }
+}
+
+/// Scores how well `feature` matches `query`.
+pub fn score(query: &MatchQuery, feature: &PhotonFeature) -> MatchScore {
+    match query {
+        MatchQuery::Text(text) => score_text(text, feature),
+        MatchQuery::Structured(address) => score_structured(address, feature),
+    }
+}
+
+/// Scores all `features` and sorts them by descending confidence. Results with equal confidence
+/// keep Photon's order.
+pub fn rank(query: &MatchQuery, features: Vec<PhotonFeature>) -> Vec<(PhotonFeature, MatchScore)> {
+    let mut scored: Vec<(PhotonFeature, MatchScore)> = features
+        .into_iter()
+        .map(|feature| {
+            let score = score(query, &feature);
+            (feature, score)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.confidence.total_cmp(&a.1.confidence));
+    scored
+}
+
+impl PhotonFeature {
+    /// Scores how well this feature matches `query`. See [`score`].
+    pub fn match_score(&self, query: impl Into<MatchQuery>) -> MatchScore {
+        score(&query.into(), self)
+    }
+}
+
+fn score_structured(address: &StructuredAddress, feature: &PhotonFeature) -> MatchScore {
+    let mut fields = Vec::new();
+    let mut total = 0.0;
+    let mut total_weight = 0.0;
+    for (field, weight) in WEIGHTS.iter() {
+        let query = match structured_value(address, *field) {
+            Some(query) => query,
+            None => continue,
+        };
+        let (feature_value, similarity) = compare_field(*field, query, feature);
+        total += weight * similarity;
+        total_weight += weight;
+        fields.push(FieldMatch {
+            field: *field,
+            query: query.to_string(),
+            feature: feature_value,
+            similarity,
+        });
+    }
+
+    let expected = if address.house_number.is_some() {
+        Some(FeatureType::House)
+    } else if address.street.is_some() {
+        Some(FeatureType::Street)
+    } else if address.city.is_some() || address.postcode.is_some() {
+        Some(FeatureType::City)
+    } else if address.state.is_some() {
+        Some(FeatureType::State)
+    } else if address.country.is_some() {
+        Some(FeatureType::Country)
+    } else {
+        None
+    };
+    let granularity = granularity_factor(expected, &feature.feature_type());
+    let confidence = if total_weight > 0.0 {
+        total / total_weight * granularity
+    } else {
+        0.0
+    };
+    MatchScore {
+        confidence,
+        granularity,
+        fields,
+    }
+}
+
+fn score_text(text: &str, feature: &PhotonFeature) -> MatchScore {
+    let query_tokens = tokens(text);
+    let mut matched = vec![0.0f64; query_tokens.len()];
+    let mut fields = Vec::new();
+
+    for (field, _) in WEIGHTS.iter() {
+        let value = match text_field_value(*field, feature) {
+            Some(value) => value,
+            None => continue,
+        };
+        let value_tokens = tokens(&value);
+        if value_tokens.is_empty() {
+            continue;
+        }
+        let mut similarity = 0.0;
+        let mut query_parts = Vec::new();
+        for value_token in &value_tokens {
+            let best = query_tokens
+                .iter()
+                .enumerate()
+                .map(|(i, query_token)| (i, token_similarity(query_token, value_token)))
+                .filter(|(_, s)| *s >= TOKEN_THRESHOLD)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((i, s)) = best {
+                similarity += s;
+                matched[i] = matched[i].max(s);
+                if !query_parts.contains(&query_tokens[i]) {
+                    query_parts.push(query_tokens[i].clone());
+                }
+            }
+        }
+        fields.push(FieldMatch {
+            field: *field,
+            query: query_parts.join(" "),
+            feature: Some(value),
+            similarity: similarity / value_tokens.len() as f64,
+        });
+    }
+
+    // Words of the query that look like a house number ask for a house, unless they are the
+    // postcode of the result.
+    let postcode = feature.postcode.as_deref().map(tokens).unwrap_or_default();
+    let expected = if query_tokens
+        .iter()
+        .any(|token| token.chars().any(|c| c.is_ascii_digit()) && !postcode.contains(token))
+    {
+        Some(FeatureType::House)
+    } else {
+        None
+    };
+    let granularity = granularity_factor(expected, &feature.feature_type());
+    let confidence = if query_tokens.is_empty() {
+        0.0
+    } else {
+        matched.iter().sum::<f64>() / query_tokens.len() as f64 * granularity
+    };
+    MatchScore {
+        confidence,
+        granularity,
+        fields,
+    }
+}
+
+fn structured_value(address: &StructuredAddress, field: AddressField) -> Option<&str> {
+    let value = match field {
+        AddressField::Name => &address.name,
+        AddressField::Street => &address.street,
+        AddressField::HouseNumber => &address.house_number,
+        AddressField::Postcode => &address.postcode,
+        AddressField::City => &address.city,
+        AddressField::State => &address.state,
+        AddressField::Country => &address.country,
+    };
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// The values of `feature` a field of the query can match. Streets and places keep their name in
+/// `name` rather than in `street` or `city`.
+fn candidates(field: AddressField, feature: &PhotonFeature) -> Vec<&str> {
+    let feature_type = feature.feature_type();
+    let name_if = |matches: bool| feature.name.as_ref().filter(|_| matches);
+    let values = match field {
+        AddressField::Name => vec![feature.name.as_ref()],
+        AddressField::Street => vec![
+            feature.street.as_ref(),
+            name_if(feature_type == FeatureType::Street),
+        ],
+        AddressField::HouseNumber => vec![feature.house_number.as_ref()],
+        AddressField::Postcode => vec![feature.postcode.as_ref()],
+        AddressField::City => vec![
+            feature.city.as_ref(),
+            feature.district.as_ref(),
+            feature.county.as_ref(),
+            name_if(matches!(
+                feature_type,
+                FeatureType::Locality | FeatureType::District | FeatureType::City
+            )),
+        ],
+        AddressField::State => vec![
+            feature.state.as_ref(),
+            name_if(feature_type == FeatureType::State),
+        ],
+        AddressField::Country => vec![
+            feature.country.as_ref(),
+            feature.country_iso_code.as_ref(),
+            name_if(feature_type == FeatureType::Country),
+        ],
+    };
+    values
+        .into_iter()
+        .flatten()
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// Compares `query` with the best matching candidate of `feature`.
+fn compare_field(
+    field: AddressField,
+    query: &str,
+    feature: &PhotonFeature,
+) -> (Option<String>, f64) {
+    let mut best: (Option<String>, f64) = (None, 0.0);
+    for candidate in candidates(field, feature) {
+        let similarity = match field {
+            AddressField::HouseNumber => house_number_similarity(query, candidate),
+            AddressField::Postcode => postcode_similarity(query, candidate),
+            // ISO codes are only compared with each other.
+            AddressField::Country if candidate.len() == 2 || query.trim().len() == 2 => {
+                if candidate.eq_ignore_ascii_case(query.trim()) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            _ => text_similarity(query, candidate),
+        };
+        if best.0.is_none() || similarity > best.1 {
+            best = (Some(candidate.to_string()), similarity);
+        }
+    }
+    best
+}
+
+/// The value of `feature` a free-text query is compared with. Fields with several candidates are
+/// joined, so e.g. a query containing the district still counts as matching.
+fn text_field_value(field: AddressField, feature: &PhotonFeature) -> Option<String> {
+    if field == AddressField::Name {
+        // The name of streets and places is already compared as street or city.
+        let feature_type = feature.feature_type();
+        if feature_type != FeatureType::House && !matches!(feature_type, FeatureType::Other(_)) {
+            return None;
+        }
+    }
+    let mut values: Vec<&str> = Vec::new();
+    for value in candidates(field, feature) {
+        let already_covered = values
+            .iter()
+            .any(|other| normalize(other) == normalize(value));
+        if !already_covered {
+            values.push(value);
+        }
+    }
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(" "))
+    }
+}
+
+fn granularity_factor(expected: Option<FeatureType>, actual: &FeatureType) -> f64 {
+    let expected = match expected {
+        Some(expected) => expected,
+        None => return 1.0,
+    };
+    if let FeatureType::Other(_) = actual {
+        return UNKNOWN_GRANULARITY;
+    }
+    let levels = level(actual).saturating_sub(level(&expected));
+    GRANULARITY_PENALTY.powi(levels as i32)
+}
+
+fn level(feature_type: &FeatureType) -> u8 {
+    match feature_type {
+        FeatureType::Other(_) | FeatureType::House => 0,
+        FeatureType::Street => 1,
+        FeatureType::Locality => 2,
+        FeatureType::District => 3,
+        FeatureType::City => 4,
+        FeatureType::County => 5,
+        FeatureType::State => 6,
+        FeatureType::Country => 7,
+    }
+}
+
+/// Similarity of two house numbers. Numbers that only differ in their suffix, like `12` and `12a`,
+/// partially match.
+fn house_number_similarity(a: &str, b: &str) -> f64 {
+    let normalize_number = |s: &str| -> String {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let (a, b) = (normalize_number(a), normalize_number(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+    let number = |s: &str| -> String { s.chars().take_while(char::is_ascii_digit).collect() };
+    let (number_a, number_b) = (number(&a), number(&b));
+    if !number_a.is_empty() && number_a == number_b {
+        0.7
+    } else {
+        0.0
+    }
+}
+
+/// Similarity of two postcodes. Postcodes with a common prefix, which usually means they belong
+/// to neighbouring areas, partially match.
+fn postcode_similarity(a: &str, b: &str) -> f64 {
+    let normalize_postcode = |s: &str| -> Vec<char> {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_uppercase)
+            .collect()
+    };
+    let (a, b) = (normalize_postcode(a), normalize_postcode(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+    let common = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+    0.5 * common as f64 / a.len().max(b.len()) as f64
+}
+
+/// Similarity of two strings after normalisation, tolerant to differently split words such as
+/// `Hauptstraße` and `Haupt Straße`.
+fn text_similarity(a: &str, b: &str) -> f64 {
+    let (a_tokens, b_tokens) = (tokens(a), tokens(b));
+    if a_tokens.is_empty() || b_tokens.is_empty() {
+        return 0.0;
+    }
+    let joined = jaro_winkler(&a_tokens.concat(), &b_tokens.concat());
+    let directional = |from: &[String], to: &[String]| -> f64 {
+        from.iter()
+            .map(|token| {
+                to.iter()
+                    .map(|other| token_similarity(token, other))
+                    .fold(0.0, f64::max)
+            })
+            .sum::<f64>()
+            / from.len() as f64
+    };
+    let by_token = (directional(&a_tokens, &b_tokens) + directional(&b_tokens, &a_tokens)) / 2.0;
+    joined.max(by_token)
+}
+
+/// Similarity of two normalised words. Words containing digits have to match exactly.
+fn token_similarity(a: &str, b: &str) -> f64 {
+    if a.chars().any(|c| c.is_ascii_digit()) || b.chars().any(|c| c.is_ascii_digit()) {
+        house_number_similarity(a, b)
+    } else {
+        jaro_winkler(a, b)
+    }
+}
+
+/// Lowercases `s`, folds diacritics, removes punctuation and expands common abbreviations.
+fn normalize(s: &str) -> String {
+    tokens(s).join(" ")
+}
+
+fn tokens(s: &str) -> Vec<String> {
+    let mut folded = String::with_capacity(s.len());
+    for c in s.chars().flat_map(char::to_lowercase) {
+        match c {
+            'ß' => folded.push_str("ss"),
+            'ä' => folded.push_str("ae"),
+            'ö' => folded.push_str("oe"),
+            'ü' => folded.push_str("ue"),
+            'æ' => folded.push_str("ae"),
+            'œ' => folded.push_str("oe"),
+            'ø' => folded.push('o'),
+            'ł' => folded.push('l'),
+            c if c.is_alphanumeric() => folded.push(fold_diacritic(c)),
+            _ => folded.push(' '),
+        }
+    }
+    folded
+        .split_whitespace()
+        .map(|token| {
+            for (short, long) in ABBREVIATIONS.iter() {
+                if token == *short {
+                    return long.to_string();
+                }
+            }
+            // German street names are commonly abbreviated as a suffix, e.g. `Hauptstr.`
+            match token.strip_suffix("str") {
+                Some(stem) if !stem.is_empty() => format!("{}strasse", stem),
+                _ => token.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn fold_diacritic(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'č' => 'c',
+        'ď' | 'đ' => 'd',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => 'e',
+        'ğ' => 'g',
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => 'i',
+        'ñ' | 'ń' | 'ň' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ō' | 'ő' => 'o',
+        'ř' => 'r',
+        'ś' | 'š' | 'ş' => 's',
+        'ť' | 'ţ' => 't',
+        'ù' | 'ú' | 'û' | 'ū' | 'ů' | 'ű' | 'ų' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        c => c,
+    }
+}
+
+/// Jaro-Winkler similarity between `0` and `1`.
+fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0;
+    for (i, ca) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        for j in start..end {
+            if !b_matched[j] && b[j] == *ca {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let a_sequence = a.iter().zip(&a_matched).filter(|(_, &m)| m).map(|(c, _)| c);
+    let b_sequence = b.iter().zip(&b_matched).filter(|(_, &m)| m).map(|(c, _)| c);
+    let transpositions = a_sequence.zip(b_sequence).filter(|(x, y)| x != y).count() / 2;
+
+    let m = matches as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+    let prefix = a
+        .iter()
+        .zip(b.iter())
+        .take(4)
+        .take_while(|(x, y)| x == y)
+        .count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
@@ -0,0 +1,362 @@
+//! Conversion of coordinates and bounding boxes to and from
+//! [Well-known text](https://en.wikipedia.org/wiki/Well-known_text_representation_of_geometry),
+//! e.g. for PostGIS.
+//!
+//! Coordinates are written as `POINT(lon lat)` and bounding boxes as `POLYGON`, or as a
+//! `MULTIPOLYGON` of its two halves if the box crosses the antimeridian. Bounding boxes can also be
+//! written in the `ENVELOPE(min_lon, max_lon, max_lat, min_lat)` notation of Elasticsearch. The
+//! EWKT variants prefix the geometry with its SRID, e.g. `SRID=4326;POINT(11.575 48.137)`. As all
+//! coordinates of this crate are WGS 84, only SRID 4326 is accepted.
+
+use crate::error::InvalidInputError;
+use crate::{BoundingBox, LatLon};
+
+/// The SRID of WGS 84, the reference system of all coordinates of this crate.
+pub const WGS84_SRID: u32 = 4326;
+
+/// The deepest nesting of parentheses that is parsed, that of a `MULTIPOLYGON`.
+const MAX_DEPTH: usize = 3;
+
+impl LatLon {
+    /// This coordinate as WKT, e.g. `POINT(11.575 48.137)`.
+    pub fn to_wkt(&self) -> String {
+        format!("POINT({})", position(self.lon, self.lat))
+    }
+
+    /// This coordinate as EWKT, e.g. `SRID=4326;POINT(11.575 48.137)`.
+    pub fn to_ewkt(&self) -> String {
+        ewkt(&self.to_wkt())
+    }
+
+    /// Parses a WKT point such as `POINT(11.575 48.137)`.
+    pub fn from_wkt(wkt: &str) -> Result<LatLon, InvalidInputError> {
+        point(&Geometry::parse(wkt, false)?)
+    }
+
+    /// Parses an EWKT point such as `SRID=4326;POINT(11.575 48.137)`. Points without an SRID are
+    /// taken as WGS 84.
+    pub fn from_ewkt(ewkt: &str) -> Result<LatLon, InvalidInputError> {
+        point(&Geometry::parse(ewkt, true)?)
+    }
+}
+
+impl BoundingBox {
+    /// This box as a WKT `POLYGON`, or as a `MULTIPOLYGON` of its two halves if it crosses the
+    /// antimeridian.
+    pub fn to_wkt(&self) -> String {
+        if self.crosses_antimeridian() {
+            let east = BoundingBox {
+                south_west: self.south_west,
+                north_east: LatLon::new(self.north_east.lat, 180.0),
+            };
+            let west = BoundingBox {
+                south_west: LatLon::new(self.south_west.lat, -180.0),
+                north_east: self.north_east,
+            };
+            format!("MULTIPOLYGON({},{})", rectangle(&east), rectangle(&west))
+        } else {
+            format!("POLYGON{}", rectangle(self))
+        }
+    }
+
+    /// This box as EWKT, see [`BoundingBox::to_wkt`].
+    pub fn to_ewkt(&self) -> String {
+        ewkt(&self.to_wkt())
+    }
+
+    /// This box as `ENVELOPE(min_lon, max_lon, max_lat, min_lat)`, the notation of Elasticsearch.
+    pub fn to_envelope_wkt(&self) -> String {
+        format!(
+            "ENVELOPE({}, {}, {}, {})",
+            self.south_west.lon, self.north_east.lon, self.north_east.lat, self.south_west.lat
+        )
+    }
+
+    /// Parses a WKT rectangle: a `POLYGON`, a `MULTIPOLYGON` of the two halves of a box that
+    /// crosses the antimeridian, or an `ENVELOPE`.
+    pub fn from_wkt(wkt: &str) -> Result<BoundingBox, InvalidInputError> {
+        bounding_box(&Geometry::parse(wkt, false)?)
+    }
+
+    /// Parses an EWKT rectangle, see [`BoundingBox::from_wkt`]. Geometries without an SRID are
+    /// taken as WGS 84.
+    pub fn from_ewkt(ewkt: &str) -> Result<BoundingBox, InvalidInputError> {
+        bounding_box(&Geometry::parse(ewkt, true)?)
+    }
+}
+
+fn ewkt(wkt: &str) -> String {
+    format!("SRID={};{}", WGS84_SRID, wkt)
+}
+
+fn position(x: f64, y: f64) -> String {
+    format!("{} {}", x, y)
+}
+
+/// The ring of `bbox`, in parentheses.
+fn rectangle(bbox: &BoundingBox) -> String {
+    let positions: Vec<String> = bbox.ring().iter().map(|(x, y)| position(*x, *y)).collect();
+    format!("(({}))", positions.join(", "))
+}
+
+/// A parenthesized part of a geometry: either a position or a list of parts.
+#[derive(Debug)]
+enum Node {
+    Position(Vec<f64>),
+    List(Vec<Node>),
+}
+
+impl Node {
+    fn list(&self) -> Option<&[Node]> {
+        match self {
+            Node::List(nodes) => Some(nodes),
+            Node::Position(_) => None,
+        }
+    }
+
+    fn position(&self) -> Option<&[f64]> {
+        match self {
+            Node::Position(values) => Some(values),
+            Node::List(_) => None,
+        }
+    }
+}
+
+/// A geometry as written in WKT, before it is interpreted.
+struct Geometry<'a> {
+    input: &'a str,
+    keyword: String,
+    body: Node,
+}
+
+impl<'a> Geometry<'a> {
+    fn parse(input: &'a str, extended: bool) -> Result<Self, InvalidInputError> {
+        let mut rest = input.trim();
+        let has_srid = rest
+            .get(..5)
+            .map_or(false, |prefix| prefix.eq_ignore_ascii_case("SRID="));
+        if has_srid {
+            if !extended {
+                return Err(invalid(input, "an SRID is only allowed in EWKT"));
+            }
+            let (srid, geometry) = rest[5..]
+                .split_once(';')
+                .ok_or_else(|| invalid(input, "the SRID is not followed by ';'"))?;
+            let srid: u32 = srid
+                .trim()
+                .parse()
+                .map_err(|_| invalid(input, "the SRID is not a number"))?;
+            if srid != WGS84_SRID {
+                return Err(InvalidInputError::new(&format!(
+                    "SRID {} is not supported, coordinates must be WGS 84 (SRID {})",
+                    srid, WGS84_SRID
+                )));
+            }
+            rest = geometry.trim_start();
+        }
+
+        let keyword_end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let keyword = rest[..keyword_end].to_ascii_uppercase();
+        let mut parser = Parser {
+            input,
+            chars: rest[keyword_end..].trim_start().chars().peekable(),
+        };
+        if parser.chars.peek() != Some(&'(') {
+            return Err(invalid(input, "expected '(' after the geometry type"));
+        }
+        let body = parser.node(1)?;
+        if parser.chars.any(|c| !c.is_whitespace()) {
+            return Err(invalid(input, "unexpected text after the geometry"));
+        }
+        Ok(Geometry {
+            input,
+            keyword,
+            body,
+        })
+    }
+
+    fn invalid(&self, reason: &str) -> InvalidInputError {
+        invalid(self.input, reason)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().map_or(false, |c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    /// Parses a parenthesized list of positions or nested lists, which is nested `depth` levels
+    /// deep.
+    fn node(&mut self, depth: usize) -> Result<Node, InvalidInputError> {
+        if depth > MAX_DEPTH {
+            return Err(invalid(self.input, "too many nested parentheses"));
+        }
+        self.chars.next();
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.chars.peek() == Some(&'(') {
+                items.push(self.node(depth + 1)?);
+            } else {
+                items.push(Node::Position(self.numbers()?));
+            }
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(')') => break,
+                _ => return Err(invalid(self.input, "expected ',' or ')'")),
+            }
+        }
+        Ok(Node::List(items))
+    }
+
+    /// Parses the numbers of a position, separated by whitespace.
+    fn numbers(&mut self) -> Result<Vec<f64>, InvalidInputError> {
+        let mut numbers = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let mut number = String::new();
+            while let Some(c) = self
+                .chars
+                .peek()
+                .filter(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E'))
+            {
+                number.push(*c);
+                self.chars.next();
+            }
+            if number.is_empty() {
+                break;
+            }
+            let value: f64 = number
+                .parse()
+                .map_err(|_| invalid(self.input, &format!("'{}' is not a number", number)))?;
+            numbers.push(value);
+        }
+        if numbers.is_empty() {
+            return Err(invalid(self.input, "expected a number"));
+        }
+        Ok(numbers)
+    }
+}
+
+fn point(geometry: &Geometry) -> Result<LatLon, InvalidInputError> {
+    if geometry.keyword != "POINT" {
+        return Err(geometry.invalid("expected a POINT"));
+    }
+    match geometry.body.list() {
+        Some([node]) => coordinate(geometry, node),
+        _ => Err(geometry.invalid("a POINT has exactly one position")),
+    }
+}
+
+fn coordinate(geometry: &Geometry, node: &Node) -> Result<LatLon, InvalidInputError> {
+    match node.position() {
+        Some([lon, lat]) => LatLon::try_new(*lat, *lon),
+        Some(_) => Err(geometry.invalid("only positions with two coordinates are supported")),
+        None => Err(geometry.invalid("expected a position")),
+    }
+}
+
+fn bounding_box(geometry: &Geometry) -> Result<BoundingBox, InvalidInputError> {
+    let not_a_box = || geometry.invalid("the geometry is not a bounding box");
+    let body = geometry.body.list().ok_or_else(not_a_box)?;
+    match geometry.keyword.as_str() {
+        "ENVELOPE" => {
+            let values = body
+                .iter()
+                .map(|node| match node.position() {
+                    Some([value]) => Ok(*value),
+                    _ => Err(geometry.invalid("an ENVELOPE has four single numbers")),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            match values.as_slice() {
+                [min_lon, max_lon, max_lat, min_lat] => BoundingBox::try_new(
+                    LatLon::try_new(*min_lat, *min_lon)?,
+                    LatLon::try_new(*max_lat, *max_lon)?,
+                ),
+                _ => Err(geometry.invalid("an ENVELOPE has four single numbers")),
+            }
+        }
+        "POLYGON" => rectangle_of(geometry, body),
+        "MULTIPOLYGON" => {
+            let rectangles = body
+                .iter()
+                .map(|polygon| rectangle_of(geometry, polygon.list().ok_or_else(not_a_box)?))
+                .collect::<Result<Vec<_>, _>>()?;
+            match rectangles.as_slice() {
+                [rectangle] => Ok(*rectangle),
+                // The two halves of a box that crosses the antimeridian, in any order.
+                [first, second] => {
+                    let (east, west) = if first.north_east.lon == 180.0 {
+                        (first, second)
+                    } else {
+                        (second, first)
+                    };
+                    let is_split = east.north_east.lon == 180.0
+                        && west.south_west.lon == -180.0
+                        && east.south_west.lat == west.south_west.lat
+                        && east.north_east.lat == west.north_east.lat;
+                    if !is_split {
+                        return Err(not_a_box());
+                    }
+                    BoundingBox::try_new(east.south_west, west.north_east)
+                }
+                _ => Err(not_a_box()),
+            }
+        }
+        _ => Err(geometry.invalid("expected a POLYGON, MULTIPOLYGON or ENVELOPE")),
+    }
+}
+
+/// The box of a polygon that is an axis-aligned rectangle without holes.
+fn rectangle_of(geometry: &Geometry, rings: &[Node]) -> Result<BoundingBox, InvalidInputError> {
+    let not_a_box = || geometry.invalid("the polygon is not a rectangle");
+    let ring = match rings {
+        [ring] => ring.list().ok_or_else(not_a_box)?,
+        _ => return Err(geometry.invalid("a bounding box has exactly one ring")),
+    };
+    let corners = ring
+        .iter()
+        .map(|node| coordinate(geometry, node))
+        .collect::<Result<Vec<_>, _>>()?;
+    if corners.len() != 5 || corners[0] != corners[4] {
+        return Err(not_a_box());
+    }
+    let (mut south_west, mut north_east) = (corners[0], corners[0]);
+    for corner in &corners {
+        south_west = LatLon::new(
+            south_west.lat.min(corner.lat),
+            south_west.lon.min(corner.lon),
+        );
+        north_east = LatLon::new(
+            north_east.lat.max(corner.lat),
+            north_east.lon.max(corner.lon),
+        );
+    }
+    let on_corner = |corner: &LatLon| {
+        (corner.lat == south_west.lat || corner.lat == north_east.lat)
+            && (corner.lon == south_west.lon || corner.lon == north_east.lon)
+    };
+    // All positions are corners of the rectangle, and the ring runs along its edges.
+    let is_rectangle = corners.iter().all(on_corner)
+        && corners
+            .windows(2)
+            .all(|pair| pair[0].lat == pair[1].lat || pair[0].lon == pair[1].lon);
+    if !is_rectangle {
+        return Err(not_a_box());
+    }
+    BoundingBox::try_new(south_west, north_east)
+}
+
+fn invalid(input: &str, reason: &str) -> InvalidInputError {
+    InvalidInputError::new(&format!("'{}' is not valid WKT: {}", input.trim(), reason))
+}
@@ -1,6 +1,6 @@
 use std::{error::Error, fmt::Display};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PhotonError {
     pub message: String,
 }
@@ -20,3 +20,25 @@ impl Display for PhotonError {
 }
 
 impl Error for PhotonError {}
+
+/// Error for arguments that are rejected locally, before any request is sent to the API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidInputError {
+    pub message: String,
+}
+
+impl InvalidInputError {
+    pub fn new(message: &str) -> Self {
+        InvalidInputError {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for InvalidInputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid input: {}", &self.message)
+    }
+}
+
+impl Error for InvalidInputError {}
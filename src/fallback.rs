@@ -0,0 +1,212 @@
+//! Progressively coarser retries for addresses that cannot be found as a whole. See
+//! [`Client::geocode_with_fallback`](crate::PhotonApiClient::geocode_with_fallback).
+
+use crate::data::address::StructuredAddress;
+use crate::data::filter::{ForwardFilter, PhotonLayer};
+use crate::data::scoring::{AddressField, MatchScore};
+use crate::data::PhotonFeature;
+
+/// One step of a [`FallbackStrategy`]: the address components that are searched for and the
+/// layers results are restricted to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FallbackLevel {
+    pub name: String,
+    pub fields: Vec<AddressField>,
+    pub layers: Vec<PhotonLayer>,
+}
+
+impl FallbackLevel {
+    /// Construct a custom level. The first of `fields` is required: the level is skipped for
+    /// addresses without it.
+    pub fn new(name: &str, fields: &[AddressField], layers: &[PhotonLayer]) -> Self {
+        FallbackLevel {
+            name: name.to_string(),
+            fields: fields.to_vec(),
+            layers: layers.to_vec(),
+        }
+    }
+
+    /// The complete address, restricted to houses.
+    pub fn full() -> Self {
+        Self::new(
+            "full",
+            &[
+                AddressField::HouseNumber,
+                AddressField::Street,
+                AddressField::Postcode,
+                AddressField::City,
+                AddressField::Country,
+            ],
+            &[PhotonLayer::House],
+        )
+    }
+
+    /// Street, postcode and city, restricted to streets.
+    pub fn without_house_number() -> Self {
+        Self::new(
+            "without_house_number",
+            &[
+                AddressField::Street,
+                AddressField::Postcode,
+                AddressField::City,
+                AddressField::Country,
+            ],
+            &[PhotonLayer::Street],
+        )
+    }
+
+    /// Street and city, restricted to streets. Postcodes that do not match the street are a common
+    /// reason for failing searches.
+    pub fn street() -> Self {
+        Self::new(
+            "street",
+            &[
+                AddressField::Street,
+                AddressField::City,
+                AddressField::Country,
+            ],
+            &[PhotonLayer::Street],
+        )
+    }
+
+    /// Postcode and city, restricted to cities and their parts.
+    pub fn postcode_and_city() -> Self {
+        Self::new(
+            "postcode_and_city",
+            &[
+                AddressField::Postcode,
+                AddressField::City,
+                AddressField::Country,
+            ],
+            &[
+                PhotonLayer::City,
+                PhotonLayer::District,
+                PhotonLayer::Locality,
+            ],
+        )
+    }
+
+    /// The city alone, in its country.
+    pub fn city() -> Self {
+        Self::new(
+            "city",
+            &[AddressField::City, AddressField::Country],
+            &[PhotonLayer::City],
+        )
+    }
+
+    /// The part of `address` that is searched for on this level, or `None` if the address lacks
+    /// the required component.
+    pub fn address(&self, address: &StructuredAddress) -> Option<StructuredAddress> {
+        let value = |field: &AddressField| {
+            let value = match field {
+                AddressField::Name => &address.name,
+                AddressField::Street => &address.street,
+                AddressField::HouseNumber => &address.house_number,
+                AddressField::Postcode => &address.postcode,
+                AddressField::City => &address.city,
+                AddressField::State => &address.state,
+                AddressField::Country => &address.country,
+            };
+            value.clone().filter(|v| !v.trim().is_empty())
+        };
+        value(self.fields.first()?)?;
+
+        let mut reduced = StructuredAddress::new();
+        for field in &self.fields {
+            let value = value(field);
+            match field {
+                AddressField::Name => reduced.name = value,
+                AddressField::Street => reduced.street = value,
+                AddressField::HouseNumber => reduced.house_number = value,
+                AddressField::Postcode => reduced.postcode = value,
+                AddressField::City => reduced.city = value,
+                AddressField::State => reduced.state = value,
+                AddressField::Country => reduced.country = value,
+            }
+        }
+        Some(reduced)
+    }
+}
+
+/// The levels tried by [`Client::geocode_with_fallback`](crate::PhotonApiClient::geocode_with_fallback).
+/// This struct implements a builder pattern, so strategies can be easily constructed.
+#[derive(Debug, Clone)]
+pub struct FallbackStrategy {
+    pub levels: Vec<FallbackLevel>,
+    pub filter: ForwardFilter,
+    pub min_confidence: f64,
+}
+
+impl Default for FallbackStrategy {
+    fn default() -> Self {
+        FallbackStrategy {
+            levels: vec![
+                FallbackLevel::full(),
+                FallbackLevel::without_house_number(),
+                FallbackLevel::street(),
+                FallbackLevel::postcode_and_city(),
+                FallbackLevel::city(),
+            ],
+            filter: ForwardFilter::new().limit(5),
+            min_confidence: 0.5,
+        }
+    }
+}
+
+impl FallbackStrategy {
+    /// Construct a new `FallbackStrategy` that tries the full address, the address without house
+    /// number, street and city, postcode and city and finally the city alone. Every level keeps
+    /// the country of the address, so `Paris, France` does not fall back to Paris, Texas. Results
+    /// are accepted with a [confidence](crate::scoring) of at least `0.5`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the levels with `levels`, which are tried in order.
+    pub fn levels(mut self, levels: Vec<FallbackLevel>) -> Self {
+        self.levels = levels;
+        self
+    }
+
+    /// Options for every search. The layers are replaced by the layers of each level.
+    pub fn filter(mut self, filter: ForwardFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// The minimum confidence of a result, compared with the searched part of the address.
+    pub fn min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// The levels that apply to `address` with the query for each, in order. Levels whose query
+    /// does not differ from the previous one are left out.
+    pub fn plan(&self, address: &StructuredAddress) -> Vec<(usize, StructuredAddress)> {
+        let mut plan: Vec<(usize, StructuredAddress)> = Vec::new();
+        for (index, level) in self.levels.iter().enumerate() {
+            if let Some(reduced) = level.address(address) {
+                let repeated = plan.last().map_or(false, |(previous, query)| {
+                    *query == reduced && self.levels[*previous].layers == level.layers
+                });
+                if !repeated {
+                    plan.push((index, reduced));
+                }
+            }
+        }
+        plan
+    }
+}
+
+/// The result of [`Client::geocode_with_fallback`](crate::PhotonApiClient::geocode_with_fallback).
+#[derive(Debug, Clone)]
+pub struct FallbackMatch {
+    pub feature: PhotonFeature,
+    /// The level that produced the result.
+    pub level: FallbackLevel,
+    /// The position of `level` in the strategy, `0` meaning the address was found as a whole.
+    pub level_index: usize,
+    /// The score of the result, compared with the searched part of the address.
+    pub score: MatchScore,
+}
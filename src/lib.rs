@@ -3,8 +3,30 @@
 mod api;
 mod data;
 
+pub mod admin;
+pub mod autocomplete;
+pub mod batch;
+pub mod bulk;
 pub mod error;
+pub mod fallback;
+pub mod ndjson;
+pub mod track;
 
 pub use api::Client as PhotonApiClient;
+pub use data::address;
+pub use data::address::StructuredAddress;
+#[cfg(feature = "arrow")]
+pub use data::arrow;
+pub use data::category;
+pub use data::dedupe;
+pub use data::export;
 pub use data::filter;
-pub use data::{BoundingBox, LatLon, PhotonFeature};
+pub use data::geo;
+pub use data::geohash;
+pub use data::grid;
+pub use data::label;
+pub use data::parse;
+pub use data::pluscode;
+pub use data::scoring;
+pub use data::wkt;
+pub use data::{BoundingBox, FeatureType, LatLon, LongitudeHandling, OsmType, PhotonFeature};
@@ -0,0 +1,378 @@
+//! Geocoding of newline-delimited JSON (NDJSON) streams. See
+//! [`Client::geocode_ndjson`](crate::PhotonApiClient::geocode_ndjson).
+//!
+//! Every input line is a JSON object with either a free-text `query` or the address fields
+//! `name`, `street`, `house_number`, `postcode`, `city`, `state` and `country`. The filter of the
+//! stream can be overridden per line with `lang`, `limit`, `layer` (a list of layer names),
+//! `bbox` (`[min_lon, min_lat, max_lon, max_lat]`) and `bias` (`{"lat": …, "lon": …}`). Any JSON
+//! value in `id` is copied to the result, other fields are rejected:
+//!
+//! ```json
+//! {"id": 1, "query": "Marienplatz 8, München", "lang": "de"}
+//! {"id": 2, "street": "Unter den Linden 1", "city": "Berlin", "layer": ["house"]}
+//! ```
+//!
+//! For every query, one result line is written, in the order of the input. It holds the `line`
+//! number of the query, its `id`, the `query` text that was searched, the best match as a GeoJSON
+//! `feature` with its `label` and `confidence`, and an `error` with `kind` and `message` if the
+//! line could not be geocoded. Empty lines are skipped.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Condvar, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::batch::{self, BatchError, BatchErrorKind, BatchOptions, BatchResult};
+use crate::data::address::StructuredAddress;
+use crate::data::filter::{ForwardFilter, PhotonLayer};
+use crate::data::json::PhotonFeatureRaw;
+use crate::data::scoring::MatchQuery;
+use crate::data::{BoundingBox, LatLon};
+use crate::error::InvalidInputError;
+
+/// The number of lines per thread that may be read ahead of the last written result.
+const LINES_PER_THREAD: usize = 4;
+
+/// How many queries of a stream were geocoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NdjsonStats {
+    /// The number of non-empty input lines.
+    pub queries: usize,
+    pub matched: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NdjsonQuery {
+    id: Option<serde_json::Value>,
+    query: Option<String>,
+    name: Option<String>,
+    street: Option<String>,
+    house_number: Option<String>,
+    postcode: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+    country: Option<String>,
+    lang: Option<String>,
+    limit: Option<u64>,
+    layer: Option<Vec<String>>,
+    bbox: Option<[f64; 4]>,
+    bias: Option<Bias>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Bias {
+    lat: f64,
+    lon: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct NdjsonResult {
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<serde_json::Value>,
+    query: Option<String>,
+    feature: Option<PhotonFeatureRaw>,
+    label: Option<String>,
+    confidence: Option<f64>,
+    error: Option<BatchError>,
+}
+
+impl NdjsonQuery {
+    fn match_query(&self) -> Result<MatchQuery, InvalidInputError> {
+        let fields = [
+            &self.name,
+            &self.street,
+            &self.house_number,
+            &self.postcode,
+            &self.city,
+            &self.state,
+            &self.country,
+        ];
+        let has_address = fields.iter().any(|field| field.is_some());
+        match &self.query {
+            Some(_) if has_address => Err(InvalidInputError::new(
+                "a query cannot have both a free-text query and address fields",
+            )),
+            Some(text) => Ok(MatchQuery::Text(text.clone())),
+            None => {
+                let mut address = StructuredAddress::new();
+                address.name = self.name.clone();
+                address.street = self.street.clone();
+                address.house_number = self.house_number.clone();
+                address.postcode = self.postcode.clone();
+                address.city = self.city.clone();
+                address.state = self.state.clone();
+                address.country = self.country.clone();
+                Ok(MatchQuery::Structured(address))
+            }
+        }
+    }
+
+    /// `filter` with the overrides of this query.
+    fn filter(&self, filter: &ForwardFilter) -> Result<ForwardFilter, InvalidInputError> {
+        let mut filter = filter.clone();
+        if let Some(lang) = &self.lang {
+            filter = filter.language(lang);
+        }
+        if let Some(limit) = self.limit {
+            filter = filter.limit(limit);
+        }
+        if let Some(layers) = &self.layer {
+            let layers = layers
+                .iter()
+                .map(|layer| layer.parse::<PhotonLayer>())
+                .collect::<Result<_, _>>()?;
+            filter = filter.layer(layers);
+        }
+        if let Some([min_lon, min_lat, max_lon, max_lat]) = self.bbox {
+            filter = filter.bounding_box(BoundingBox {
+                south_west: LatLon::new(min_lat, min_lon),
+                north_east: LatLon::new(max_lat, max_lon),
+            });
+        }
+        if let Some(bias) = &self.bias {
+            filter = filter.location_bias(LatLon::new(bias.lat, bias.lon), None, None);
+        }
+        filter.validate()?;
+        Ok(filter)
+    }
+}
+
+/// Geocodes one input line and renders its result line.
+fn geocode_line<F>(
+    number: usize,
+    line: &str,
+    filter: &ForwardFilter,
+    search: &F,
+) -> (String, BatchResult)
+where
+    F: Fn(&MatchQuery, &ForwardFilter) -> BatchResult,
+{
+    let mut result = NdjsonResult {
+        line: number,
+        id: None,
+        query: None,
+        feature: None,
+        label: None,
+        confidence: None,
+        error: None,
+    };
+    let outcome = serde_json::from_str::<NdjsonQuery>(line)
+        .map_err(|error| {
+            BatchError::from(InvalidInputError::new(&format!(
+                "line {} is not a valid query: {}",
+                number, error
+            )))
+        })
+        .and_then(|query| {
+            result.id = query.id.clone();
+            let match_query = query.match_query()?;
+            result.query = batch::query_text(&match_query).ok();
+            let filter = query.filter(filter)?;
+            search(&match_query, &filter)
+        });
+    match &outcome {
+        Ok(Some(best)) => {
+            result.feature = Some(PhotonFeatureRaw::from(&best.feature));
+            result.label = Some(best.feature.label());
+            result.confidence = Some(best.score.confidence);
+        }
+        Ok(None) => {}
+        Err(error) => result.error = Some(error.clone()),
+    }
+    let rendered = serde_json::to_string(&result).unwrap_or_else(|error| {
+        // Only non-finite numbers cannot be serialized, which Photon never returns.
+        error_line(
+            number,
+            &BatchError::new(BatchErrorKind::Response, &error.to_string()),
+        )
+    });
+    (rendered, outcome)
+}
+
+/// Like [`geocode_line`], but a panic while geocoding is reported as the error of the line. In
+/// the multi-threaded stream, the result of a panicked worker would never arrive otherwise.
+fn geocode_line_or_error<F>(
+    number: usize,
+    line: &str,
+    filter: &ForwardFilter,
+    search: &F,
+) -> (String, BatchResult)
+where
+    F: Fn(&MatchQuery, &ForwardFilter) -> BatchResult,
+{
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        geocode_line(number, line, filter, search)
+    }))
+    .unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        let error = BatchError::new(
+            BatchErrorKind::Response,
+            &format!("geocoding line {} panicked: {}", number, message),
+        );
+        (error_line(number, &error), Err(error))
+    })
+}
+
+/// A result line with only the line number and `error`.
+fn error_line(number: usize, error: &BatchError) -> String {
+    format!(
+        "{{\"line\":{},\"error\":{}}}",
+        number,
+        serde_json::to_string(error).unwrap_or_default()
+    )
+}
+
+/// Geocodes the NDJSON queries of `input` with `search` and writes one result line per query to
+/// `output`, in the order of the input.
+///
+/// At most a few lines per thread are held in memory at any time. Every result is flushed as soon
+/// as all results before it have been written, so the stream can be consumed interactively.
+pub(crate) fn geocode_stream<R, W, F>(
+    input: R,
+    mut output: W,
+    options: &BatchOptions,
+    search: F,
+) -> Result<NdjsonStats, Box<dyn Error>>
+where
+    R: BufRead,
+    W: Write + Send,
+    F: Fn(&MatchQuery, &ForwardFilter) -> BatchResult + Sync,
+{
+    let mut stats = NdjsonStats::default();
+    let mut count = |result: &BatchResult| {
+        stats.queries += 1;
+        match result {
+            Ok(Some(_)) => stats.matched += 1,
+            Ok(None) => {}
+            Err(_) => stats.failed += 1,
+        }
+    };
+
+    let threads = options.threads.max(1);
+    if threads == 1 {
+        for (index, line) in input.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (rendered, result) =
+                geocode_line_or_error(index + 1, &line, &options.filter, &search);
+            writeln!(output, "{}", rendered)?;
+            output.flush()?;
+            count(&result);
+        }
+        return Ok(stats);
+    }
+
+    // The reader hands lines to the workers and waits while too many lines are in flight. The
+    // writer puts the results back into input order and frees a slot for every written line.
+    let window = threads * LINES_PER_THREAD;
+    let slots = (Mutex::new((0usize, false)), Condvar::new());
+    let release = |lines: usize, stop: bool| {
+        let mut state = slots
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.0 -= lines;
+        state.1 |= stop;
+        slots.1.notify_all();
+    };
+
+    let (job_sender, jobs) = mpsc::channel::<(usize, usize, String)>();
+    let (result_sender, results) = mpsc::channel::<(usize, String, BatchResult)>();
+    let jobs = Mutex::new(jobs);
+    let streamed: Result<(), Box<dyn Error>> = std::thread::scope(|scope| {
+        for _ in 0..threads {
+            let (jobs, result_sender, search) = (&jobs, result_sender.clone(), &search);
+            scope.spawn(move || loop {
+                let job = jobs
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .recv();
+                let (sequence, number, line) = match job {
+                    Ok(job) => job,
+                    Err(_) => break,
+                };
+                let (rendered, result) =
+                    geocode_line_or_error(number, &line, &options.filter, search);
+                if result_sender.send((sequence, rendered, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(result_sender);
+
+        let writer = scope.spawn(|| {
+            let write = || -> io::Result<()> {
+                let mut pending = BTreeMap::new();
+                let mut next = 0;
+                for (sequence, rendered, result) in results {
+                    pending.insert(sequence, (rendered, result));
+                    let mut written = 0;
+                    while let Some((rendered, result)) = pending.remove(&next) {
+                        writeln!(output, "{}", rendered)?;
+                        count(&result);
+                        next += 1;
+                        written += 1;
+                    }
+                    if written > 0 {
+                        output.flush()?;
+                        release(written, false);
+                    }
+                }
+                Ok(())
+            };
+            let written = write();
+            // Stop the reader if writing failed, so it does not wait for free slots forever.
+            release(0, written.is_err());
+            written
+        });
+
+        let read = || -> Result<(), Box<dyn Error>> {
+            let mut sequence = 0;
+            for (index, line) in input.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let mut state = slots
+                    .0
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                while state.0 >= window && !state.1 {
+                    state = slots
+                        .1
+                        .wait(state)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner());
+                }
+                if state.1 {
+                    break;
+                }
+                state.0 += 1;
+                drop(state);
+                job_sender.send((sequence, index + 1, line))?;
+                sequence += 1;
+            }
+            Ok(())
+        };
+        let read = read();
+        drop(job_sender);
+        let written = writer.join().expect("writer thread panicked");
+        read?;
+        written?;
+        Ok(())
+    });
+    streamed?;
+    Ok(stats)
+}
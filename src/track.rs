@@ -0,0 +1,265 @@
+//! Annotation of GPS tracks with the streets and towns they pass through. See
+//! [`Client::annotate_track`](crate::PhotonApiClient::annotate_track).
+//!
+//! Instead of looking up every track point, the track is sampled: a point is looked up when the
+//! track has changed its heading noticeably, or has gone on straight for a long distance. Runs of
+//! samples with the same street and city are then compressed into segments.
+
+use std::error::Error;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::data::filter::ReverseFilter;
+use crate::data::{FeatureType, LatLon, PhotonFeature};
+use crate::error::InvalidInputError;
+
+/// Configuration for [`Client::annotate_track`](crate::PhotonApiClient::annotate_track). This
+/// struct implements a builder pattern, so options can be easily constructed.
+#[derive(Debug, Clone)]
+pub struct TrackOptions {
+    /// Minimum distance between samples in meters.
+    pub min_distance: f64,
+    /// Maximum distance between samples in meters.
+    pub max_distance: f64,
+    /// Change of heading in degrees that triggers a sample.
+    pub heading_change: f64,
+    pub filter: Option<ReverseFilter>,
+}
+
+impl Default for TrackOptions {
+    fn default() -> Self {
+        TrackOptions {
+            min_distance: 25.0,
+            max_distance: 250.0,
+            heading_change: 30.0,
+            filter: None,
+        }
+    }
+}
+
+impl TrackOptions {
+    /// Construct new `TrackOptions` that sample at least every 250 meters, and after turns of 30°
+    /// or more once the track has gone on for 25 meters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn min_distance(mut self, meters: f64) -> Self {
+        self.min_distance = meters;
+        self
+    }
+
+    pub fn max_distance(mut self, meters: f64) -> Self {
+        self.max_distance = meters;
+        self
+    }
+
+    pub fn heading_change(mut self, degrees: f64) -> Self {
+        self.heading_change = degrees;
+        self
+    }
+
+    /// Options for every reverse search. The limit is always 1.
+    pub fn filter(mut self, filter: ReverseFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Checks the distances and the filter. This is done automatically before every annotation.
+    pub fn validate(&self) -> Result<(), InvalidInputError> {
+        if self.min_distance.is_nan() || self.min_distance < 0.0 {
+            return Err(InvalidInputError::new(&format!(
+                "min_distance must not be negative, got {}",
+                self.min_distance
+            )));
+        }
+        if self.max_distance.is_nan() || self.max_distance < self.min_distance {
+            return Err(InvalidInputError::new(&format!(
+                "max_distance must be at least min_distance ({}), got {}",
+                self.min_distance, self.max_distance
+            )));
+        }
+        if self.heading_change.is_nan() || self.heading_change < 0.0 {
+            return Err(InvalidInputError::new(&format!(
+                "heading_change must not be negative, got {}",
+                self.heading_change
+            )));
+        }
+        match &self.filter {
+            Some(filter) => filter.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A part of a track on one street in one city.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackSegment {
+    /// Indices of the track points in this segment.
+    pub points: Range<usize>,
+    pub street: Option<String>,
+    pub city: Option<String>,
+    /// Length of the segment in meters, up to the first point of the next segment.
+    pub distance: f64,
+}
+
+/// The indices of the points of `track` that should be looked up. The first and the last point
+/// are always included.
+pub fn sample(track: &[LatLon], options: &TrackOptions) -> Vec<usize> {
+    let mut samples = Vec::new();
+    if track.is_empty() {
+        return samples;
+    }
+    samples.push(0);
+
+    let mut distance = 0.0;
+    let mut heading: Option<f64> = None;
+    for i in 1..track.len() {
+        let step = track[i - 1].haversine_distance(&track[i]);
+        distance += step;
+        if step > 0.0 {
+            let bearing = track[i - 1].initial_bearing(&track[i]);
+            let reference = *heading.get_or_insert(bearing);
+            let turned = angle_between(reference, bearing) >= options.heading_change;
+            if distance >= options.max_distance
+                || (turned && distance >= options.min_distance)
+                || i == track.len() - 1
+            {
+                samples.push(i);
+                distance = 0.0;
+                heading = Some(bearing);
+            }
+        } else if i == track.len() - 1 {
+            samples.push(i);
+        }
+    }
+    samples
+}
+
+/// Builds segments from the features found at sample points, merging consecutive samples with
+/// the same street and city. `samples` must be sorted by their point index.
+pub fn segments(track: &[LatLon], samples: &[(usize, Option<PhotonFeature>)]) -> Vec<TrackSegment> {
+    let mut segments: Vec<TrackSegment> = Vec::new();
+    for (position, (start, feature)) in samples.iter().enumerate() {
+        let end = samples
+            .get(position + 1)
+            .map_or(track.len(), |(next, _)| *next);
+        if *start >= end {
+            continue;
+        }
+        let last = end.min(track.len() - 1);
+        let distance: f64 = (*start..last)
+            .map(|i| track[i].haversine_distance(&track[i + 1]))
+            .sum();
+        let (street, city) = match feature {
+            Some(feature) => (street_of(feature), city_of(feature)),
+            None => (None, None),
+        };
+
+        match segments.last_mut() {
+            Some(previous) if previous.street == street && previous.city == city => {
+                previous.points.end = end;
+                previous.distance += distance;
+            }
+            _ => segments.push(TrackSegment {
+                points: *start..end,
+                street,
+                city,
+                distance,
+            }),
+        }
+    }
+    segments
+}
+
+/// Reads the track and route points of a GPX file.
+pub fn read_gpx(path: impl AsRef<Path>) -> Result<Vec<LatLon>, Box<dyn Error>> {
+    Ok(parse_gpx(&fs::read_to_string(path)?)?)
+}
+
+/// Parses the track points (`trkpt`) of a GPX document, or its route points (`rtept`) if it has
+/// no track.
+pub fn parse_gpx(gpx: &str) -> Result<Vec<LatLon>, InvalidInputError> {
+    let track = parse_points(gpx, "trkpt")?;
+    if !track.is_empty() {
+        return Ok(track);
+    }
+    parse_points(gpx, "rtept")
+}
+
+fn parse_points(gpx: &str, tag: &str) -> Result<Vec<LatLon>, InvalidInputError> {
+    let open = format!("<{}", tag);
+    let mut points = Vec::new();
+    let mut rest = gpx;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        // Skip longer tag names that start with the same characters.
+        if !rest.starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/') {
+            continue;
+        }
+        let end = rest
+            .find('>')
+            .ok_or_else(|| InvalidInputError::new(&format!("unclosed <{}> element", tag)))?;
+        let attributes = &rest[..end];
+        let coordinate = |name: &str| {
+            attribute(attributes, name)
+                .and_then(|value| value.trim().parse::<f64>().ok())
+                .ok_or_else(|| {
+                    InvalidInputError::new(&format!("<{}> without valid {} attribute", tag, name))
+                })
+        };
+        points.push(LatLon::try_new(coordinate("lat")?, coordinate("lon")?)?);
+        rest = &rest[end..];
+    }
+    Ok(points)
+}
+
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attributes;
+    while let Some(position) = rest.find(name) {
+        let preceded_by_space = rest[..position]
+            .chars()
+            .last()
+            .map_or(true, char::is_whitespace);
+        let after = rest[position + name.len()..].trim_start();
+        if preceded_by_space {
+            if let Some(value) = after.strip_prefix('=') {
+                let value = value.trim_start();
+                let quote = value.chars().next()?;
+                if quote == '"' || quote == '\'' {
+                    let value = &value[1..];
+                    return value.find(quote).map(|end| &value[..end]);
+                }
+            }
+        }
+        rest = &rest[position + name.len()..];
+    }
+    None
+}
+
+/// The smallest angle between two bearings in degrees.
+fn angle_between(a: f64, b: f64) -> f64 {
+    let difference = (a - b).abs() % 360.0;
+    difference.min(360.0 - difference)
+}
+
+fn street_of(feature: &PhotonFeature) -> Option<String> {
+    feature.street.clone().or_else(|| {
+        feature
+            .name
+            .clone()
+            .filter(|_| feature.feature_type() == FeatureType::Street)
+    })
+}
+
+fn city_of(feature: &PhotonFeature) -> Option<String> {
+    feature.city.clone().or_else(|| {
+        feature.name.clone().filter(|_| {
+            matches!(
+                feature.feature_type(),
+                FeatureType::City | FeatureType::Locality
+            )
+        })
+    })
+}
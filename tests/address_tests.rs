@@ -0,0 +1,149 @@
+use photon_geocoding::address::parse_address;
+use photon_geocoding::StructuredAddress;
+
+fn parse(input: &str) -> StructuredAddress {
+    parse_address(input).unwrap_or_else(|e| panic!("{}: {}", input, e))
+}
+
+#[test]
+fn parses_german_addresses() {
+    let expected = StructuredAddress::new()
+        .street("Marienplatz")
+        .house_number("8")
+        .postcode("80331")
+        .city("München");
+    assert_eq!(parse("Marienplatz 8, 80331 München"), expected);
+    assert_eq!(parse("Marienplatz 8 80331 München"), expected);
+    assert_eq!(
+        parse("Marienplatz 8, 80331 München, Deutschland"),
+        expected.clone().country("DE")
+    );
+    assert_eq!(
+        parse("Marienplatz 8, D-80331 München"),
+        expected.country("DE")
+    );
+
+    let address = parse("Deutsches Museum, Museumsinsel 1, 80538 München, Bayern");
+    assert_eq!(address.name.as_deref(), Some("Deutsches Museum"));
+    assert_eq!(address.street.as_deref(), Some("Museumsinsel"));
+    assert_eq!(address.house_number.as_deref(), Some("1"));
+    assert_eq!(address.state.as_deref(), Some("Bayern"));
+
+    let address = parse("Hauptstr. 12 a, 10115 Berlin");
+    assert_eq!(address.street.as_deref(), Some("Hauptstr."));
+    assert_eq!(address.house_number.as_deref(), Some("12a"));
+}
+
+#[test]
+fn parses_austrian_and_swiss_addresses() {
+    let address = parse("Stephansplatz 3, 1010 Wien, Österreich");
+    assert_eq!(
+        address,
+        StructuredAddress::new()
+            .street("Stephansplatz")
+            .house_number("3")
+            .postcode("1010")
+            .city("Wien")
+            .country("AT")
+    );
+    let address = parse("Bahnhofstrasse 45, CH-8001 Zürich");
+    assert_eq!(address.postcode.as_deref(), Some("8001"));
+    assert_eq!(address.city.as_deref(), Some("Zürich"));
+    assert_eq!(address.country.as_deref(), Some("CH"));
+}
+
+#[test]
+fn parses_french_addresses() {
+    let expected = StructuredAddress::new()
+        .street("rue de Rivoli")
+        .house_number("12")
+        .postcode("75001")
+        .city("Paris")
+        .country("FR");
+    assert_eq!(parse("12 rue de Rivoli, 75001 Paris, France"), expected);
+    assert_eq!(parse("12, rue de Rivoli, 75001 Paris France"), expected);
+    assert_eq!(
+        parse("12 bis rue de Rivoli, 75001 Paris")
+            .house_number
+            .as_deref(),
+        Some("12 bis")
+    );
+}
+
+#[test]
+fn parses_uk_addresses() {
+    let expected = StructuredAddress::new()
+        .street("Downing Street")
+        .house_number("10")
+        .postcode("SW1A 2AA")
+        .city("London")
+        .country("GB");
+    assert_eq!(parse("10 Downing Street, London SW1A 2AA"), expected);
+    assert_eq!(parse("10 Downing Street, London, SW1A2AA, UK"), expected);
+
+    let address = parse("Flat 2, 221B Baker Street, London NW1 6XE");
+    assert_eq!(address.name.as_deref(), Some("Flat 2"));
+    assert_eq!(address.street.as_deref(), Some("Baker Street"));
+    assert_eq!(address.house_number.as_deref(), Some("221B"));
+}
+
+#[test]
+fn parses_us_addresses() {
+    let address = parse("1600 Pennsylvania Avenue NW, Washington, DC 20500");
+    assert_eq!(
+        address,
+        StructuredAddress::new()
+            .street("Pennsylvania Avenue NW")
+            .house_number("1600")
+            .postcode("20500")
+            .city("Washington")
+            .state("DC")
+            .country("US")
+    );
+    let address = parse("1 Infinite Loop, Cupertino, CA 95014-2083, USA");
+    assert_eq!(address.postcode.as_deref(), Some("95014-2083"));
+    assert_eq!(address.state.as_deref(), Some("CA"));
+    assert_eq!(address.country.as_deref(), Some("US"));
+
+    let address = parse("Springfield, IL");
+    assert_eq!(address.city.as_deref(), Some("Springfield"));
+    assert_eq!(address.state.as_deref(), Some("IL"));
+}
+
+#[test]
+fn parses_partial_addresses() {
+    assert_eq!(parse("Berlin"), StructuredAddress::new().city("Berlin"));
+    assert_eq!(
+        parse("10115 Berlin"),
+        StructuredAddress::new().postcode("10115").city("Berlin")
+    );
+    assert_eq!(
+        parse("Marienplatz, München"),
+        StructuredAddress::new()
+            .street("Marienplatz")
+            .city("München")
+    );
+    assert_eq!(
+        parse("Marienplatz 8 München"),
+        StructuredAddress::new()
+            .street("Marienplatz")
+            .house_number("8")
+            .city("München")
+    );
+    assert_eq!(parse("Germany"), StructuredAddress::new().country("DE"));
+}
+
+#[test]
+fn rejects_empty_input() {
+    assert!(parse_address("").is_err());
+    assert!(parse_address(" , ").is_err());
+    assert!(" ".parse::<StructuredAddress>().is_err());
+}
+
+#[test]
+fn from_str_and_display() {
+    let address: StructuredAddress = "Marienplatz 8, 80331 München".parse().unwrap();
+    assert_eq!(address.to_string(), "Marienplatz 8, 80331 München");
+    assert!(StructuredAddress::new().is_empty());
+    assert!(!address.is_empty());
+}
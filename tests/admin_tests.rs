@@ -0,0 +1,106 @@
+mod common;
+
+use photon_geocoding::admin::{AdminCache, AdminHierarchy, ADMIN_LAYERS};
+use photon_geocoding::filter::PhotonLayer;
+use photon_geocoding::{LatLon, PhotonApiClient, PhotonFeature};
+
+// Nothing listens on this port, so any request that is actually sent fails with a transport error.
+const UNREACHABLE: &str = "http://127.0.0.1:9";
+
+fn area(name: &str, r#type: &str) -> PhotonFeature {
+    PhotonFeature {
+        name: Some(String::from(name)),
+        r#type: String::from(r#type),
+        ..common::feature(48.137, 11.575)
+    }
+}
+
+fn fill(cache: &AdminCache, coords: &LatLon) {
+    cache.insert(
+        PhotonLayer::Country,
+        coords,
+        Some(area("Deutschland", "country")),
+    );
+    cache.insert(PhotonLayer::State, coords, Some(area("Bayern", "state")));
+    cache.insert(PhotonLayer::County, coords, None);
+    cache.insert(PhotonLayer::City, coords, Some(area("München", "city")));
+    cache.insert(
+        PhotonLayer::District,
+        coords,
+        Some(area("Altstadt", "district")),
+    );
+}
+
+#[test]
+fn hierarchy_levels_are_ordered() {
+    let hierarchy = AdminHierarchy {
+        country: Some(area("Deutschland", "country")),
+        city: Some(area("München", "city")),
+        ..AdminHierarchy::default()
+    };
+    let levels: Vec<PhotonLayer> = hierarchy.levels().iter().map(|(layer, _)| *layer).collect();
+    assert_eq!(levels, [PhotonLayer::Country, PhotonLayer::City]);
+    assert!(hierarchy.get(PhotonLayer::State).is_none());
+    assert!(hierarchy.get(PhotonLayer::House).is_none());
+    assert_eq!(ADMIN_LAYERS.len(), 5);
+}
+
+#[test]
+fn cache_shares_levels_within_cells() {
+    let cache = AdminCache::new();
+    let coords = LatLon::new(48.1372, 11.5755);
+    fill(&cache, &coords);
+    assert_eq!(cache.len(), 5);
+
+    // About 150 meters away: same city cell, but a different district cell.
+    let nearby = LatLon::new(48.1372, 11.5775);
+    assert!(cache.get(PhotonLayer::City, &nearby).is_some());
+    assert!(cache.get(PhotonLayer::District, &nearby).is_none());
+    assert!(matches!(
+        cache.get(PhotonLayer::County, &nearby),
+        Some(None)
+    ));
+
+    let far = LatLon::new(52.52, 13.405);
+    assert!(cache.get(PhotonLayer::Country, &far).is_none());
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn cell_size_is_configurable() {
+    let cache = AdminCache::new()
+        .cell_size(PhotonLayer::Country, 10.0)
+        .cell_size(PhotonLayer::District, 0.0);
+    let coords = LatLon::new(48.1372, 11.5755);
+    fill(&cache, &coords);
+
+    assert!(cache
+        .get(PhotonLayer::Country, &LatLon::new(49.5, 12.5))
+        .is_some());
+    assert!(cache.get(PhotonLayer::District, &coords).is_none());
+    assert_eq!(cache.len(), 4);
+}
+
+#[test]
+fn cached_hierarchy_needs_no_requests() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let cache = AdminCache::new();
+    let coords = LatLon::new(48.1372, 11.5755);
+    fill(&cache, &coords);
+
+    let hierarchy = api.admin_hierarchy(coords, Some(&cache)).unwrap();
+    assert_eq!(hierarchy.state.unwrap().name.as_deref(), Some("Bayern"));
+    assert!(hierarchy.county.is_none());
+    assert_eq!(
+        hierarchy.district.unwrap().name.as_deref(),
+        Some("Altstadt")
+    );
+
+    // Without the cache, the request fails.
+    assert!(api.admin_hierarchy(coords, None).is_err());
+    assert!(api
+        .admin_hierarchy(LatLon::new(f64::NAN, 0.0), Some(&cache))
+        .is_err());
+}
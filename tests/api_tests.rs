@@ -94,6 +94,21 @@ mod forward_search {
         );
     }
 
+    #[test]
+    fn disabling_dedupe_returns_more_results() {
+        let api = PhotonApiClient::default();
+        let filter = ForwardFilter::new().layer(vec![PhotonLayer::Street]);
+        let deduped = api
+            .forward_search("Hauptstraße", Some(filter.clone()))
+            .unwrap();
+        let all = api
+            .forward_search("Hauptstraße", Some(filter.dedupe(false)))
+            .unwrap();
+
+        assert!(!all.is_empty());
+        assert!(all.len() >= deduped.len());
+    }
+
     #[test]
     fn uses_layers() {
         let api = PhotonApiClient::default();
@@ -114,8 +129,18 @@ mod forward_search {
 
 mod reverse_search {
     use photon_geocoding::filter::{PhotonLayer, ReverseFilter};
+    use photon_geocoding::grid::Mgrs;
     use photon_geocoding::{LatLon, PhotonApiClient};
 
+    #[test]
+    fn accepts_grid_references() {
+        let api = PhotonApiClient::default();
+        let mgrs: Mgrs = "32U PU 92300 33800".parse().unwrap();
+        let results = api.reverse_search(mgrs, None).unwrap();
+
+        assert!(!results.is_empty())
+    }
+
     #[test]
     fn gives_at_least_one_result_for_a_place() {
         let api = PhotonApiClient::default();
@@ -184,3 +209,234 @@ mod reverse_search {
         )
     }
 }
+
+mod smart_search {
+    use photon_geocoding::filter::ForwardFilter;
+    use photon_geocoding::PhotonApiClient;
+
+    #[test]
+    fn reverse_searches_coordinates() {
+        let api = PhotonApiClient::default();
+        let filter = ForwardFilter::new().language("DE");
+        let results = api
+            .smart_search("48°08'37\"N 11°35'16\"E", Some(filter))
+            .unwrap();
+
+        assert!(!results.is_empty());
+        assert_eq!(results.first().unwrap().city, Some(String::from("München")));
+    }
+
+    #[test]
+    fn forward_searches_text() {
+        let api = PhotonApiClient::default();
+        let results = api.smart_search("munich", None).unwrap();
+
+        assert!(!results.is_empty())
+    }
+}
+
+mod resolve_plus_code {
+    use photon_geocoding::{LatLon, PhotonApiClient};
+
+    #[test]
+    fn recovers_short_code_with_locality() {
+        let api = PhotonApiClient::default();
+        let coords = api.resolve_plus_code("4HPG+R2, München").unwrap();
+
+        assert!(coords.haversine_distance(&LatLon::new(48.137, 11.575)) < 10.0);
+    }
+}
+
+mod structured_search {
+    use photon_geocoding::address::parse_address;
+    use photon_geocoding::PhotonApiClient;
+
+    #[test]
+    fn finds_parsed_address() {
+        let api = PhotonApiClient::default();
+        let address = parse_address("Marienplatz 8, 80331 München, Deutschland").unwrap();
+        let results = api.structured_search(&address, None).unwrap();
+
+        assert!(!results.is_empty());
+        assert_eq!(
+            results.first().unwrap().postcode,
+            Some(String::from("80331"))
+        );
+    }
+}
+
+mod geocode_with_fallback {
+    use photon_geocoding::PhotonApiClient;
+
+    #[test]
+    fn falls_back_to_street() {
+        let api = PhotonApiClient::default();
+        let result = api
+            .geocode_with_fallback("Marienplatz 9999, 80331 München", None)
+            .unwrap()
+            .unwrap();
+
+        assert!(result.level_index > 0);
+        assert_eq!(result.feature.r#type, "street");
+    }
+}
+
+mod admin_hierarchy {
+    use photon_geocoding::admin::AdminCache;
+    use photon_geocoding::{LatLon, PhotonApiClient};
+
+    #[test]
+    fn resolves_levels_and_caches_them() {
+        let api = PhotonApiClient::default();
+        let cache = AdminCache::new();
+        let hierarchy = api
+            .admin_hierarchy(LatLon::new(48.1372, 11.5755), Some(&cache))
+            .unwrap();
+
+        let country = hierarchy.country.unwrap();
+        assert_eq!(country.country_iso_code, Some(String::from("DE")));
+        assert!(hierarchy.city.is_some());
+        assert_eq!(cache.len(), 5);
+
+        let nearby = api
+            .admin_hierarchy(LatLon::new(48.1373, 11.5756), Some(&cache))
+            .unwrap();
+        assert_eq!(nearby.country.unwrap().osm_id, country.osm_id);
+    }
+}
+
+mod bulk_reverse_search {
+    use photon_geocoding::bulk::{BulkReverseOptions, Grid};
+    use photon_geocoding::{LatLon, PhotonApiClient};
+
+    #[test]
+    fn resolves_points_per_cell() {
+        let api = PhotonApiClient::default();
+        let points = [
+            LatLon::new(48.13720, 11.57550),
+            LatLon::new(48.13721, 11.57551),
+            LatLon::new(52.52000, 13.40500),
+        ];
+        let options = BulkReverseOptions::new().grid(Grid::Geohash(7));
+        let result = api.bulk_reverse_search(&points, Some(options)).unwrap();
+
+        assert_eq!(result.stats.cells, 2);
+        assert_eq!(
+            result.points[0].as_ref().unwrap(),
+            result.points[1].as_ref().unwrap()
+        );
+        let munich = result.result(0).unwrap().unwrap();
+        let berlin = result.result(2).unwrap().unwrap();
+        assert_eq!(munich.city, Some(String::from("München")));
+        assert_eq!(berlin.city, Some(String::from("Berlin")));
+    }
+}
+
+mod annotate_track {
+    use photon_geocoding::filter::{PhotonLayer, ReverseFilter};
+    use photon_geocoding::track::TrackOptions;
+    use photon_geocoding::{LatLon, PhotonApiClient};
+
+    #[test]
+    fn labels_segments() {
+        let api = PhotonApiClient::default();
+        let start = LatLon::new(48.1374, 11.5755);
+        let track: Vec<LatLon> = (0..30)
+            .map(|i| start.destination(270.0, i as f64 * 10.0))
+            .collect();
+        let options =
+            TrackOptions::new().filter(ReverseFilter::new().layer(vec![PhotonLayer::Street]));
+        let segments = api.annotate_track(&track, Some(options)).unwrap();
+
+        assert!(!segments.is_empty());
+        assert_eq!(segments.first().unwrap().points.start, 0);
+        assert_eq!(segments.last().unwrap().points.end, track.len());
+        assert!(segments
+            .iter()
+            .all(|segment| segment.city == Some(String::from("München"))));
+    }
+}
+
+mod geocode_batch {
+    use photon_geocoding::scoring::MatchQuery;
+    use photon_geocoding::{PhotonApiClient, StructuredAddress};
+
+    #[test]
+    fn finds_best_matches() {
+        let api = PhotonApiClient::default();
+        let queries = [
+            MatchQuery::from("Marienplatz 8, München"),
+            MatchQuery::from(
+                StructuredAddress::new()
+                    .street("Unter den Linden")
+                    .city("Berlin"),
+            ),
+        ];
+        let results = api.geocode_batch(&queries, None).unwrap();
+
+        let munich = results[0].as_ref().unwrap().as_ref().unwrap();
+        let berlin = results[1].as_ref().unwrap().as_ref().unwrap();
+        assert_eq!(munich.feature.city, Some(String::from("München")));
+        assert!(munich.score.confidence > 0.8);
+        assert_eq!(berlin.feature.city, Some(String::from("Berlin")));
+    }
+}
+
+mod geocode_batch_checkpointed {
+    use photon_geocoding::batch::Checkpoint;
+    use photon_geocoding::scoring::MatchQuery;
+    use photon_geocoding::PhotonApiClient;
+
+    #[test]
+    fn resumes_matches() {
+        let api = PhotonApiClient::default();
+        let path = std::env::temp_dir().join(format!("photon-api-{}.ndjson", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let queries = [MatchQuery::from("Marienplatz 8, München")];
+
+        let mut checkpoint = Checkpoint::open(&path).unwrap();
+        let first = api
+            .geocode_batch_checkpointed(&queries, 0, &mut checkpoint, None)
+            .unwrap();
+        drop(checkpoint);
+        let mut checkpoint = Checkpoint::open(&path).unwrap();
+        let second = api
+            .geocode_batch_checkpointed(&queries, 0, &mut checkpoint, None)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(checkpoint.resumed(), 1);
+        let first = first[0].as_ref().unwrap().as_ref().unwrap();
+        let second = second[0].as_ref().unwrap().as_ref().unwrap();
+        assert_eq!(first.feature.osm_id, second.feature.osm_id);
+        assert_eq!(first.feature.coords, second.feature.coords);
+        assert_eq!(first.score, second.score);
+    }
+}
+
+mod geocode_ndjson {
+    use photon_geocoding::PhotonApiClient;
+
+    #[test]
+    fn streams_results() {
+        let api = PhotonApiClient::default();
+        let input = r#"{"id": "munich", "query": "Marienplatz 8, München"}
+{"id": "berlin", "street": "Unter den Linden", "city": "Berlin", "layer": ["street"]}
+"#;
+        let mut output = Vec::new();
+        let stats = api
+            .geocode_ndjson(input.as_bytes(), &mut output, None)
+            .unwrap();
+        assert_eq!(stats.matched, 2);
+
+        let output = String::from_utf8(output).unwrap();
+        let results: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(results[0]["id"], "munich");
+        assert_eq!(results[0]["feature"]["properties"]["city"], "München");
+        assert!(results[0]["confidence"].as_f64().unwrap() > 0.8);
+        assert_eq!(results[1]["feature"]["properties"]["type"], "street");
+    }
+}
@@ -0,0 +1,164 @@
+#![cfg(feature = "arrow")]
+
+use std::fs::File;
+
+use arrow_array::{Array, Float64Array, StringArray, UInt64Array};
+use arrow_schema::DataType;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use photon_geocoding::arrow::{self, ParquetWriter};
+use photon_geocoding::PhotonFeature;
+
+mod common;
+
+fn features() -> Vec<(&'static str, PhotonFeature)> {
+    vec![
+        ("q1", common::munich_house()),
+        ("q2", common::munich_city()),
+    ]
+}
+
+fn strings(batch: &arrow_array::RecordBatch, column: &str) -> Vec<Option<String>> {
+    let array = batch
+        .column_by_name(column)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    (0..array.len())
+        .map(|i| Some(array.value(i).to_string()).filter(|_| array.is_valid(i)))
+        .collect()
+}
+
+fn floats(batch: &arrow_array::RecordBatch, column: &str) -> Vec<Option<f64>> {
+    let array = batch
+        .column_by_name(column)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap();
+    (0..array.len())
+        .map(|i| Some(array.value(i)).filter(|_| array.is_valid(i)))
+        .collect()
+}
+
+#[test]
+fn schema_is_stable() {
+    let schema = arrow::schema();
+    let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "query",
+            "osm_id",
+            "osm_type",
+            "osm_key",
+            "osm_value",
+            "type",
+            "latitude",
+            "longitude",
+            "name",
+            "house_number",
+            "street",
+            "postcode",
+            "district",
+            "city",
+            "county",
+            "state",
+            "country",
+            "country_code",
+            "extent_min_lat",
+            "extent_min_lon",
+            "extent_max_lat",
+            "extent_max_lon",
+            "label",
+        ]
+    );
+
+    let field = |name: &str| schema.field_with_name(name).unwrap().clone();
+    assert_eq!(field("osm_id").data_type(), &DataType::UInt64);
+    assert!(!field("query").is_nullable());
+    assert!(!field("latitude").is_nullable());
+    assert!(field("street").is_nullable());
+    assert_eq!(field("extent_max_lon").data_type(), &DataType::Float64);
+    assert!(field("extent_max_lon").is_nullable());
+}
+
+#[test]
+fn converts_features() {
+    let features = features();
+    let batch =
+        arrow::to_record_batch(features.iter().map(|(query, feature)| (*query, feature))).unwrap();
+
+    assert_eq!(batch.num_rows(), 2);
+    assert_eq!(batch.schema(), arrow::schema());
+    assert_eq!(
+        strings(&batch, "query"),
+        [Some(String::from("q1")), Some(String::from("q2"))]
+    );
+    let osm_ids = batch
+        .column_by_name("osm_id")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<UInt64Array>()
+        .unwrap();
+    assert_eq!(osm_ids.values().to_vec(), [1, 62428]);
+    assert_eq!(
+        strings(&batch, "street"),
+        [Some(String::from("Marienplatz")), None]
+    );
+    assert_eq!(
+        strings(&batch, "country_code"),
+        [Some(String::from("DE")), None]
+    );
+    assert_eq!(strings(&batch, "osm_type")[0], Some(String::from("N")));
+    assert_eq!(floats(&batch, "latitude"), [Some(48.1373), Some(48.137)]);
+    assert_eq!(floats(&batch, "extent_min_lat"), [None, Some(48.06)]);
+    assert_eq!(floats(&batch, "extent_min_lon"), [None, Some(11.36)]);
+    assert_eq!(floats(&batch, "extent_max_lat"), [None, Some(48.25)]);
+    assert_eq!(floats(&batch, "extent_max_lon"), [None, Some(11.72)]);
+    assert!(strings(&batch, "label")[0]
+        .as_ref()
+        .unwrap()
+        .contains("Marienplatz 8"));
+}
+
+#[test]
+fn converts_empty_batches() {
+    let batch = arrow::to_record_batch(std::iter::empty()).unwrap();
+    assert_eq!(batch.num_rows(), 0);
+    assert_eq!(batch.num_columns(), arrow::schema().fields().len());
+}
+
+#[test]
+fn writes_parquet() {
+    let path = std::env::temp_dir().join(format!("photon-{}.parquet", std::process::id()));
+    let features = features();
+    let mut writer = ParquetWriter::create(&path).unwrap();
+    writer
+        .write(features.iter().map(|(query, feature)| (*query, feature)))
+        .unwrap();
+    writer
+        .write(features[..1].iter().map(|(_, feature)| ("q3", feature)))
+        .unwrap();
+    writer.close().unwrap();
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    let batches: Vec<_> = reader.map(|batch| batch.unwrap()).collect();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+    let batch = &batches[0];
+    assert_eq!(batch.schema().fields(), arrow::schema().fields());
+    assert_eq!(
+        strings(batch, "city"),
+        [
+            Some(String::from("München")),
+            None,
+            Some(String::from("München"))
+        ]
+    );
+    assert_eq!(floats(batch, "extent_max_lat"), [None, Some(48.25), None]);
+}
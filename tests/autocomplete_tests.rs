@@ -0,0 +1,134 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use photon_geocoding::autocomplete::{AutocompleteOptions, AutocompleteSession};
+use photon_geocoding::filter::ForwardFilter;
+use photon_geocoding::PhotonApiClient;
+
+mod common;
+
+// Nothing listens on this port, so requests fail immediately without network access.
+const UNREACHABLE: &str = "http://127.0.0.1:9";
+
+/// Two places in Munich, returned for every query.
+const PLACES: [&str; 2] = [
+    r#"{"geometry": {"coordinates": [11.5586, 48.1403], "type": "Point"}, "type": "Feature",
+     "properties": {"osm_id": 1, "osm_type": "N", "osm_key": "railway", "osm_value": "station",
+                    "type": "house", "name": "Hauptbahnhof", "city": "Munich"}}"#,
+    r#"{"geometry": {"coordinates": [11.5755, 48.1373], "type": "Point"}, "type": "Feature",
+     "properties": {"osm_id": 2, "osm_type": "W", "osm_key": "place", "osm_value": "square",
+                    "type": "street", "name": "Marienplatz", "city": "Munich"}}"#,
+];
+
+fn cached_session(url: &str, cache_size: usize) -> AutocompleteSession {
+    let options = AutocompleteOptions::new()
+        .debounce(Duration::from_millis(1))
+        .cache_size(cache_size)
+        .filter(ForwardFilter::new().limit(5));
+    AutocompleteSession::new(Arc::new(PhotonApiClient::new(url)), options)
+}
+
+fn names(session: &AutocompleteSession, query: &str) -> Vec<String> {
+    session.update(query);
+    let result = session.recv().unwrap();
+    assert_eq!(result.query, query);
+    result
+        .features
+        .unwrap()
+        .into_iter()
+        .filter_map(|feature| feature.name)
+        .collect()
+}
+
+#[test]
+fn short_queries_yield_empty_results_without_request() {
+    let client = Arc::new(PhotonApiClient::new(UNREACHABLE));
+    let session = AutocompleteSession::new(client, AutocompleteOptions::new().min_query_length(3));
+
+    session.update("mu");
+    let result = session.recv_timeout(Duration::from_secs(1)).unwrap();
+
+    assert_eq!(result.query, "mu");
+    assert!(result.features.unwrap().is_empty());
+}
+
+#[test]
+fn only_emits_results_for_latest_query() {
+    let client = Arc::new(PhotonApiClient::new(UNREACHABLE));
+    let options = AutocompleteOptions::new().debounce(Duration::from_millis(50));
+    let session = AutocompleteSession::new(client, options);
+
+    session.update("mun");
+    session.update("muni");
+    session.update("munich");
+
+    let result = session.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(result.query, "munich");
+    assert!(result.features.is_err());
+    assert!(session.recv_timeout(Duration::from_millis(200)).is_none());
+}
+
+#[test]
+fn recv_waits_for_latest_query() {
+    let client = Arc::new(PhotonApiClient::new(UNREACHABLE));
+    let options = AutocompleteOptions::new().debounce(Duration::from_millis(50));
+    let session = AutocompleteSession::new(client, options);
+
+    // Nothing was submitted, so there is nothing to wait for.
+    assert!(session.recv().is_none());
+
+    session.update("mu");
+    assert_eq!(session.recv().unwrap().query, "mu");
+
+    session.update("munich");
+    let result = session.recv().unwrap();
+    assert_eq!(result.query, "munich");
+    assert!(result.features.is_err());
+
+    // The result was handed out already, so a second call does not wait for another one.
+    assert!(session.recv().is_none());
+}
+
+#[test]
+fn reuses_complete_results_for_longer_queries() {
+    let (url, requests) = common::serve_json(common::feature_collection(&PLACES));
+    let session = cached_session(&url, 8);
+
+    assert_eq!(names(&session, "munich"), ["Hauptbahnhof", "Marienplatz"]);
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    // Fewer results than the limit are complete, so extensions are filtered locally by all terms.
+    assert_eq!(names(&session, "munich"), ["Hauptbahnhof", "Marienplatz"]);
+    assert_eq!(names(&session, "munich mari"), ["Marienplatz"]);
+    assert_eq!(names(&session, "Munich Haupt"), ["Hauptbahnhof"]);
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    // No cached result matches all terms, so the query is sent.
+    names(&session, "munich mari haupt");
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].contains("mari"));
+}
+
+#[test]
+fn evicts_oldest_queries() {
+    let (url, requests) = common::serve_json(common::feature_collection(&PLACES));
+    let session = cached_session(&url, 2);
+
+    names(&session, "munich");
+    names(&session, "berlin");
+    names(&session, "munich");
+    assert_eq!(requests.lock().unwrap().len(), 2);
+
+    // Caching a third query evicts the first one.
+    names(&session, "hamburg");
+    names(&session, "berlin");
+    assert_eq!(requests.lock().unwrap().len(), 3);
+    names(&session, "munich");
+    assert_eq!(requests.lock().unwrap().len(), 4);
+
+    let uncached = cached_session(&url, 0);
+    names(&uncached, "munich");
+    names(&uncached, "munich");
+    assert_eq!(requests.lock().unwrap().len(), 6);
+}
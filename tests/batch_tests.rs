@@ -0,0 +1,233 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use photon_geocoding::batch::{BatchErrorKind, BatchOptions, Checkpoint};
+use photon_geocoding::error::InvalidInputError;
+use photon_geocoding::filter::ForwardFilter;
+use photon_geocoding::scoring::MatchQuery;
+use photon_geocoding::{LatLon, PhotonApiClient, StructuredAddress};
+
+// Nothing listens on this port, so any request that is actually sent fails with a transport error.
+const UNREACHABLE: &str = "http://127.0.0.1:9";
+
+/// A path for the checkpoint of one test, which does not exist yet.
+fn journal(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "photon-checkpoint-{}-{}.ndjson",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn queries() -> Vec<MatchQuery> {
+    vec![
+        MatchQuery::from("Marienplatz 8, München"),
+        MatchQuery::from("   "),
+        MatchQuery::from(StructuredAddress::new().city("Berlin")),
+        MatchQuery::from(StructuredAddress::new()),
+    ]
+}
+
+#[test]
+fn reports_errors_per_query() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let results = api.geocode_batch(&queries(), None).unwrap();
+
+    assert_eq!(results.len(), 4);
+    assert_eq!(
+        results[0].as_ref().unwrap_err().kind,
+        BatchErrorKind::Transport
+    );
+    assert_eq!(
+        results[1].as_ref().unwrap_err().kind,
+        BatchErrorKind::InvalidInput
+    );
+    assert_eq!(
+        results[1].as_ref().unwrap_err().message,
+        "invalid input: query is empty"
+    );
+    assert!(results[2].is_err());
+    assert_ne!(
+        results[2].as_ref().unwrap_err().message,
+        "invalid input: query is empty"
+    );
+    assert_eq!(
+        results[3].as_ref().unwrap_err().message,
+        "invalid input: query is empty"
+    );
+}
+
+#[test]
+fn keeps_order_with_threads() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let queries: Vec<MatchQuery> = (0..10)
+        .map(|i| MatchQuery::from(if i % 3 == 0 { "" } else { "Berlin" }))
+        .collect();
+    let options = BatchOptions::new().threads(4);
+    let results = api.geocode_batch(&queries, Some(options)).unwrap();
+
+    assert_eq!(results.len(), 10);
+    for (i, result) in results.iter().enumerate() {
+        let is_empty = result.as_ref().unwrap_err().message == "invalid input: query is empty";
+        assert_eq!(is_empty, i % 3 == 0, "query {}", i);
+    }
+}
+
+#[test]
+fn rejects_invalid_filter() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let filter = ForwardFilter::new().location_bias(LatLon::new(95.0, 0.0), None, None);
+    let options = BatchOptions::new().filter(filter);
+    let error = api.geocode_batch(&queries(), Some(options)).unwrap_err();
+    assert!(error.downcast_ref::<InvalidInputError>().is_some());
+}
+
+#[test]
+fn default_options() {
+    let options = BatchOptions::new();
+    assert_eq!(options.filter.limit, Some(5));
+    assert_eq!(options.threads, 1);
+    assert!(PhotonApiClient::new(UNREACHABLE)
+        .geocode_batch(&[], None)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn error_kinds() {
+    assert!(BatchErrorKind::Transport.is_temporary());
+    assert!(BatchErrorKind::Status.is_temporary());
+    assert!(!BatchErrorKind::InvalidInput.is_temporary());
+    assert!(!BatchErrorKind::Response.is_temporary());
+    assert_eq!(BatchErrorKind::InvalidInput.to_string(), "invalid_input");
+}
+
+#[test]
+fn resumes_from_checkpoint() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let path = journal("resume");
+    let queries = queries();
+
+    let mut checkpoint = Checkpoint::open(&path).unwrap();
+    let first = api
+        .geocode_batch_checkpointed(&queries[..2], 0, &mut checkpoint, None)
+        .unwrap();
+    assert_eq!(checkpoint.resumed(), 0);
+    drop(checkpoint);
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+    // The restarted run takes the invalid row from the checkpoint, retries the row that failed
+    // with a transport error and searches the others.
+    let mut checkpoint = Checkpoint::open(&path).unwrap();
+    let second = api
+        .geocode_batch_checkpointed(&queries, 0, &mut checkpoint, None)
+        .unwrap();
+    assert_eq!(checkpoint.resumed(), 1);
+    assert_eq!(second.len(), 4);
+    assert_eq!(
+        second[0].as_ref().unwrap_err().kind,
+        BatchErrorKind::Transport
+    );
+    assert_eq!(
+        first[1].as_ref().unwrap_err(),
+        second[1].as_ref().unwrap_err()
+    );
+    assert_eq!(
+        second[3].as_ref().unwrap_err().kind,
+        BatchErrorKind::InvalidInput
+    );
+    drop(checkpoint);
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 5);
+
+    // The rows that still failed temporarily are retried again.
+    let mut checkpoint = Checkpoint::open(&path).unwrap();
+    api.geocode_batch_checkpointed(&queries, 0, &mut checkpoint, None)
+        .unwrap();
+    assert_eq!(checkpoint.resumed(), 2);
+    drop(checkpoint);
+    let journal = fs::read_to_string(&path).unwrap();
+    assert_eq!(journal.lines().count(), 7);
+
+    // Only rows that failed temporarily may appear again.
+    let row_1 = journal.lines().nth(1).unwrap();
+    fs::write(&path, format!("{}{}\n", journal, row_1)).unwrap();
+    let error = Checkpoint::open(&path).unwrap_err();
+    assert!(error.to_string().contains("rows are not ascending"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn discards_incomplete_last_entry() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let path = journal("incomplete");
+    let queries = queries();
+
+    let mut checkpoint = Checkpoint::open(&path).unwrap();
+    api.geocode_batch_checkpointed(&queries[..2], 0, &mut checkpoint, None)
+        .unwrap();
+    drop(checkpoint);
+    let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(b"{\"row\":2,\"query\":\"Ber").unwrap();
+    drop(file);
+
+    let mut checkpoint = Checkpoint::open(&path).unwrap();
+    let results = api
+        .geocode_batch_checkpointed(&queries, 0, &mut checkpoint, None)
+        .unwrap();
+    assert_eq!(checkpoint.resumed(), 1);
+    assert_eq!(
+        results[2].as_ref().unwrap_err().kind,
+        BatchErrorKind::Transport
+    );
+    drop(checkpoint);
+    let journal = fs::read_to_string(&path).unwrap();
+    assert_eq!(journal.lines().count(), 5);
+    assert!(journal.ends_with('\n'));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn rejects_checkpoint_of_other_input() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let path = journal("mismatch");
+
+    let mut checkpoint = Checkpoint::open(&path).unwrap();
+    api.geocode_batch_checkpointed(&queries(), 0, &mut checkpoint, None)
+        .unwrap();
+    drop(checkpoint);
+
+    let mut checkpoint = Checkpoint::open(&path).unwrap();
+    let other = [MatchQuery::from("Hamburg")];
+    let error = api
+        .geocode_batch_checkpointed(&other, 0, &mut checkpoint, None)
+        .unwrap_err();
+    assert!(error.to_string().contains("does not match the input"));
+
+    fs::write(&path, "not a journal\n{}\n").unwrap();
+    assert!(Checkpoint::open(&path).is_err());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn rejects_rows_out_of_order() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let path = journal("order");
+    let queries = queries();
+
+    let mut checkpoint = Checkpoint::open(&path).unwrap();
+    api.geocode_batch_checkpointed(&queries[2..], 2, &mut checkpoint, None)
+        .unwrap();
+    let error = api
+        .geocode_batch_checkpointed(&queries[..2], 0, &mut checkpoint, None)
+        .unwrap_err();
+    assert!(error.downcast_ref::<InvalidInputError>().is_some());
+    drop(checkpoint);
+
+    fs::remove_file(path).unwrap();
+}
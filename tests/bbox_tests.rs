@@ -0,0 +1,114 @@
+use photon_geocoding::{BoundingBox, LatLon, PhotonApiClient};
+
+mod common;
+
+fn bbox(south: f64, west: f64, north: f64, east: f64) -> BoundingBox {
+    BoundingBox::try_new(LatLon::new(south, west), LatLon::new(north, east)).unwrap()
+}
+
+#[test]
+fn rejects_invalid_corners() {
+    assert!(BoundingBox::try_new(LatLon::new(91.0, 0.0), LatLon::new(92.0, 1.0)).is_err());
+    assert!(BoundingBox::try_new(LatLon::new(0.0, f64::NAN), LatLon::new(1.0, 1.0)).is_err());
+    assert!(BoundingBox::try_new(LatLon::new(10.0, 0.0), LatLon::new(5.0, 1.0)).is_err());
+}
+
+#[test]
+fn contains_points_across_antimeridian() {
+    let pacific = bbox(-10.0, 170.0, 10.0, -170.0);
+
+    assert!(pacific.crosses_antimeridian());
+    assert_eq!(pacific.width_degrees(), 20.0);
+    assert!(pacific.contains(&LatLon::new(0.0, 175.0)));
+    assert!(pacific.contains(&LatLon::new(0.0, -175.0)));
+    assert!(!pacific.contains(&LatLon::new(0.0, 0.0)));
+    assert_eq!(pacific.center(), LatLon::new(0.0, 180.0));
+}
+
+#[test]
+fn intersects_and_unites_across_antimeridian() {
+    let west = bbox(0.0, 160.0, 10.0, 179.0);
+    let east = bbox(5.0, -179.0, 20.0, -160.0);
+    let pacific = bbox(-10.0, 170.0, 10.0, -170.0);
+
+    assert!(west.intersection(&east).is_none());
+    assert_eq!(
+        pacific.intersection(&east),
+        Some(bbox(5.0, -179.0, 10.0, -170.0))
+    );
+
+    let union = west.union(&east);
+    assert_eq!(union, bbox(0.0, 160.0, 20.0, -160.0));
+    assert!(union.contains_box(&west));
+    assert!(union.contains_box(&east));
+}
+
+#[test]
+fn circle_around_pole_covers_all_longitudes() {
+    let arctic = BoundingBox::from_center(LatLon::new(89.5, 10.0), 100_000.0).unwrap();
+
+    assert_eq!(arctic.north_east.lat, 90.0);
+    assert_eq!(arctic.width_degrees(), 360.0);
+}
+
+#[test]
+fn circle_box_contains_circle() {
+    let center = LatLon::new(48.137, 11.575);
+    let area = BoundingBox::from_center(center, 10_000.0).unwrap();
+
+    for bearing in [0.0, 45.0, 90.0, 135.0, 180.0, 225.0, 270.0, 315.0] {
+        assert!(area.contains(&center.destination(bearing, 9_999.0)));
+    }
+    assert!(!area.contains(&center.destination(0.0, 10_100.0)));
+    assert!((area.center().lat - center.lat).abs() < 1e-9);
+}
+
+#[test]
+fn expand_grows_area() {
+    let original = bbox(48.0, 11.0, 49.0, 12.0);
+    let expanded = original.expand(1_000.0);
+
+    assert!(expanded.contains_box(&original));
+    assert!(expanded.area() > original.area());
+    assert_eq!(
+        bbox(-10.0, -10.0, 89.99, 10.0)
+            .expand(10_000.0)
+            .width_degrees(),
+        360.0
+    );
+}
+
+/// Berlin as Photon returns it, with the extent as `[min_lon, max_lat, max_lon, min_lat]`.
+const BERLIN: &str = r#"{
+    "geometry": {"coordinates": [13.3888599, 52.5170365], "type": "Point"},
+    "type": "Feature",
+    "properties": {
+        "osm_id": 240109189, "osm_type": "N", "osm_key": "place", "osm_value": "city",
+        "type": "city", "extent": [13.088345, 52.6755087, 13.7611609, 52.3382448],
+        "name": "Berlin", "country": "Deutschland", "countrycode": "DE", "state": "Berlin"
+    }
+}"#;
+
+#[test]
+fn converts_photon_extents() {
+    let berlin = common::photon_feature(BERLIN);
+    let extent = berlin.extent.unwrap();
+
+    assert_eq!(extent.south_west, LatLon::new(52.3382448, 13.088345));
+    assert_eq!(extent.north_east, LatLon::new(52.6755087, 13.7611609));
+    assert!(extent.validate().is_ok());
+    assert!(!extent.crosses_antimeridian());
+    assert!(extent.contains(&berlin.coords));
+
+    // Written back in Photon's order.
+    let (url, _) = common::serve_json(common::feature_collection(&[BERLIN]));
+    let mut output = Vec::new();
+    PhotonApiClient::new(&url)
+        .geocode_ndjson(&br#"{"query": "Berlin"}"#[..], &mut output, None)
+        .unwrap();
+    let result: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(
+        result["feature"]["properties"]["extent"],
+        serde_json::json!([13.088345, 52.6755087, 13.7611609, 52.3382448])
+    );
+}
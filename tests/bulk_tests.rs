@@ -0,0 +1,85 @@
+use photon_geocoding::bulk::{BulkReverseOptions, Grid};
+use photon_geocoding::error::InvalidInputError;
+use photon_geocoding::filter::ReverseFilter;
+use photon_geocoding::{LatLon, PhotonApiClient};
+
+// Nothing listens on this port, so any request that is actually sent fails with a transport error.
+const UNREACHABLE: &str = "http://127.0.0.1:9";
+
+/// A track along a street in Munich with a point every 5 meters.
+fn track() -> Vec<LatLon> {
+    let start = LatLon::new(48.1372, 11.5755);
+    (0..40)
+        .map(|i| start.destination(90.0, i as f64 * 5.0))
+        .collect()
+}
+
+#[test]
+fn snaps_points_to_cells() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let points = track();
+    let options = BulkReverseOptions::new().grid(Grid::Meters(50.0));
+    let result = api.bulk_reverse_search(&points, Some(options)).unwrap();
+
+    // 200 meters of track cross four or five cells of 50 meters.
+    assert_eq!(result.stats.points, 40);
+    assert!(result.stats.cells >= 4 && result.stats.cells <= 5);
+    assert_eq!(result.stats.failed_requests, result.stats.cells);
+    assert!(result.stats.reuse_ratio() > 0.85);
+    assert!(result.stats.points_per_cell() >= 8.0);
+    assert_eq!(result.points.len(), 40);
+    assert!(result.points.iter().all(|cell| cell.is_ok()));
+    assert!(result.results().all(|result| result.is_err()));
+}
+
+#[test]
+fn smaller_cells_need_more_requests() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let points = track();
+    let cells = |grid| {
+        let options = BulkReverseOptions::new().grid(grid).threads(4);
+        api.bulk_reverse_search(&points, Some(options))
+            .unwrap()
+            .stats
+            .cells
+    };
+    assert!(cells(Grid::Meters(10.0)) > cells(Grid::Meters(100.0)));
+    assert!(cells(Grid::Geohash(8)) > cells(Grid::Geohash(6)));
+    assert_eq!(cells(Grid::Geohash(3)), 1);
+}
+
+#[test]
+fn max_error_follows_cell_size() {
+    assert!((Grid::Meters(100.0).max_error(0.0) - 70.71).abs() < 0.01);
+    let precision_7 = Grid::Geohash(7).max_error(48.0);
+    assert!(precision_7 > 50.0 && precision_7 < 100.0);
+    assert!(Grid::Geohash(8).max_error(48.0) < precision_7);
+}
+
+#[test]
+fn invalid_points_are_reported_per_point() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let points = [LatLon::new(48.0, 11.0), LatLon::new(f64::NAN, 11.0)];
+    let result = api.bulk_reverse_search(&points, None).unwrap();
+    assert_eq!(result.stats.invalid_points, 1);
+    assert_eq!(result.stats.cells, 1);
+    assert!(result
+        .result(1)
+        .unwrap_err()
+        .message
+        .contains("invalid input"));
+}
+
+#[test]
+fn invalid_options_are_rejected_before_request() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let points = track();
+    for options in [
+        BulkReverseOptions::new().grid(Grid::Meters(0.0)),
+        BulkReverseOptions::new().grid(Grid::Geohash(13)),
+        BulkReverseOptions::new().filter(ReverseFilter::new().radius(0)),
+    ] {
+        let error = api.bulk_reverse_search(&points, Some(options)).unwrap_err();
+        assert!(error.is::<InvalidInputError>());
+    }
+}
@@ -0,0 +1,97 @@
+use photon_geocoding::category::{Category, CategoryGroup};
+use photon_geocoding::filter::{ForwardFilter, ReverseFilter};
+
+#[test]
+fn classifies_osm_tags() {
+    assert_eq!(
+        Category::from_osm_tag("amenity", "restaurant"),
+        Some(Category::Restaurant)
+    );
+    assert_eq!(
+        Category::from_osm_tag("public_transport", "station"),
+        Some(Category::RailwayStation)
+    );
+    assert_eq!(Category::from_osm_tag("amenity", "bench"), None);
+    assert_eq!(Category::Peak.group(), CategoryGroup::Natural);
+    assert_eq!(
+        CategoryGroup::from_osm_key("aeroway"),
+        Some(CategoryGroup::Transport)
+    );
+}
+
+/// Whether Photon's `osm_tag` filter matches a feature tagged `key=value`: any include and no
+/// exclude must match.
+fn matches(filter: &[String], key: &str, value: &str) -> bool {
+    let tag = format!("{}:{}", key, value);
+    let (excludes, includes): (Vec<&String>, Vec<&String>) =
+        filter.iter().partition(|t| t.starts_with('!'));
+    let excluded = excludes.iter().any(|t| t[1..] == *key || t[1..] == *tag);
+    !excluded && includes.iter().any(|t| **t == *key || **t == *tag)
+}
+
+fn group_filter(groups: &[CategoryGroup]) -> Vec<String> {
+    ForwardFilter::new()
+        .category_groups(groups)
+        .osm_tag
+        .unwrap()
+}
+
+#[test]
+fn every_category_has_a_tag_of_its_group() {
+    let groups = [
+        CategoryGroup::Amenity,
+        CategoryGroup::Shop,
+        CategoryGroup::Transport,
+        CategoryGroup::Natural,
+        CategoryGroup::Tourism,
+        CategoryGroup::Leisure,
+        CategoryGroup::Place,
+        CategoryGroup::Road,
+        CategoryGroup::Building,
+    ];
+    for category in Category::ALL {
+        let group = category.group();
+        assert!(!category.osm_tags().is_empty(), "{:?}", category);
+        for (key, value) in category.osm_tags() {
+            assert_eq!(Category::from_osm_tag(key, value), Some(category));
+            assert!(
+                matches(&group_filter(&[group]), key, value),
+                "{:?} {}:{}",
+                group,
+                key,
+                value
+            );
+            for other in groups.iter().filter(|other| **other != group) {
+                assert!(
+                    !matches(&group_filter(&[*other]), key, value),
+                    "{:?} {}:{}",
+                    other,
+                    key,
+                    value
+                );
+                assert!(matches(&group_filter(&[*other, group]), key, value));
+            }
+        }
+    }
+}
+
+#[test]
+fn builds_osm_tag_filters() {
+    let filter = ForwardFilter::new()
+        .osm_tag(vec!["!highway"])
+        .categories(&[Category::RailwayStation])
+        .category_groups(&[CategoryGroup::Shop]);
+
+    assert_eq!(
+        filter.osm_tag,
+        Some(vec![
+            String::from("!highway"),
+            String::from("railway:station"),
+            String::from("public_transport:station"),
+            String::from("shop"),
+        ])
+    );
+
+    let reverse = ReverseFilter::from(filter);
+    assert_eq!(reverse.osm_tag.map(|tags| tags.len()), Some(4));
+}
@@ -0,0 +1,388 @@
+#![cfg(feature = "cli")]
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+
+use exif::experimental::Writer;
+use exif::{Field, In, Rational, Tag, Value};
+
+// Nothing listens on this port, so any request that is actually sent fails with a transport error.
+const UNREACHABLE: &str = "http://127.0.0.1:9";
+
+/// An empty directory for the files of one test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("photon-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn ascii(tag: Tag, value: &str) -> Field {
+    Field {
+        tag,
+        ifd_num: In::PRIMARY,
+        value: Value::Ascii(vec![value.as_bytes().to_vec()]),
+    }
+}
+
+fn degrees(tag: Tag, degrees: u32, minutes: u32, seconds_100: u32) -> Field {
+    Field {
+        tag,
+        ifd_num: In::PRIMARY,
+        value: Value::Rational(vec![
+            Rational::from((degrees, 1)),
+            Rational::from((minutes, 1)),
+            Rational::from((seconds_100, 100)),
+        ]),
+    }
+}
+
+/// A TIFF file that only consists of EXIF data with a GPS position south-west of Rio de Janeiro.
+fn tiff_with_position() -> Vec<u8> {
+    tiff_with_latitude_seconds(3000)
+}
+
+/// Like [`tiff_with_position`], with the seconds of the latitude in hundredths. One second is
+/// about 31 meters.
+fn tiff_with_latitude_seconds(seconds_100: u32) -> Vec<u8> {
+    let fields = [
+        ascii(Tag::GPSLatitudeRef, "S"),
+        degrees(Tag::GPSLatitude, 22, 54, seconds_100),
+        ascii(Tag::GPSLongitudeRef, "W"),
+        degrees(Tag::GPSLongitude, 43, 12, 0),
+    ];
+    let mut writer = Writer::new();
+    for field in &fields {
+        writer.push_field(field);
+    }
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    writer.write(&mut buffer, false).unwrap();
+    buffer.into_inner()
+}
+
+fn photon(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_photon"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn photo_tags_writes_json_reports() {
+    let dir = temp_dir("json");
+    fs::create_dir(dir.join("album")).unwrap();
+    fs::write(dir.join("album").join("beach.TIF"), tiff_with_position()).unwrap();
+    fs::write(dir.join("scan.jpg"), b"not a photo").unwrap();
+    fs::write(dir.join("notes.txt"), b"ignored").unwrap();
+
+    let output = photon(&["--url", UNREACHABLE, "photo-tags", dir.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+    let summary = String::from_utf8_lossy(&output.stderr);
+    assert!(summary.contains("2 photos, 1 with GPS position, 1 without"));
+
+    let report = fs::read_to_string(dir.join("album").join("beach.TIF.json")).unwrap();
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(report["file"], "beach.TIF");
+    assert!((report["latitude"].as_f64().unwrap() + 22.9083).abs() < 1e-4);
+    assert!((report["longitude"].as_f64().unwrap() + 43.2).abs() < 1e-9);
+    assert!(report["label"].is_null());
+    assert!(report["error"].is_string());
+
+    let report = fs::read_to_string(dir.join("scan.jpg.json")).unwrap();
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert!(report["latitude"].is_null());
+    assert!(report["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("no EXIF data"));
+    assert!(!dir.join("notes.txt.json").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn photo_tags_writes_csv_reports() {
+    let dir = temp_dir("csv");
+    fs::write(dir.join("beach.tiff"), tiff_with_position()).unwrap();
+
+    let output = photon(&[
+        "--url",
+        UNREACHABLE,
+        "photo-tags",
+        dir.to_str().unwrap(),
+        "--format",
+        "csv",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let report = fs::read_to_string(dir.join("beach.tiff.csv")).unwrap();
+    let mut lines = report.lines();
+    assert_eq!(
+        lines.next(),
+        Some("file,latitude,longitude,label,street,house_number,postcode,city,state,country,osm_id,error")
+    );
+    assert!(lines.next().unwrap().starts_with("beach.tiff,-22.908"));
+    assert_eq!(lines.next(), None);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn photo_tags_looks_up_exact_positions_unless_cells_are_given() {
+    let dir = temp_dir("cells");
+    fs::write(dir.join("first.jpg"), tiff_with_latitude_seconds(3000)).unwrap();
+    fs::write(dir.join("again.jpg"), tiff_with_latitude_seconds(3000)).unwrap();
+    fs::write(dir.join("nearby.jpg"), tiff_with_latitude_seconds(3050)).unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&dir, dir.join("loop")).unwrap();
+
+    let summary = |extra: &[&str]| {
+        let mut args = vec!["--url", UNREACHABLE, "photo-tags", dir.to_str().unwrap()];
+        args.extend(extra);
+        let output = photon(&args);
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    };
+    let exact = summary(&[]);
+    assert!(
+        exact.contains("3 photos, 3 with GPS position, 0 without"),
+        "{}",
+        exact
+    );
+    assert!(exact.contains("2 lookups"), "{}", exact);
+    let shared = summary(&["--cell", "1000"]);
+    assert!(shared.contains("1 lookups"), "{}", shared);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn rejects_unknown_commands_and_options() {
+    let output = photon(&["geocode-everything"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown command"));
+
+    let output = photon(&["photo-tags", ".", "--format", "xml"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown format 'xml'"));
+}
+
+#[test]
+fn geocode_keeps_input_columns() {
+    let dir = temp_dir("geocode");
+    let input = dir.join("addresses.csv");
+    fs::write(
+        &input,
+        "id,street,city\n1,Marienplatz 8,München\n2,\"Unter den Linden 1, Mitte\",Berlin\n3,,\n",
+    )
+    .unwrap();
+    let output = dir.join("geocoded.csv");
+
+    let result = photon(&[
+        "--url",
+        UNREACHABLE,
+        "geocode",
+        input.to_str().unwrap(),
+        "--street",
+        "street",
+        "--city",
+        "city",
+        "-o",
+        output.to_str().unwrap(),
+    ]);
+    assert!(result.status.success(), "{:?}", result);
+    let summary = String::from_utf8_lossy(&result.stderr);
+    assert!(summary.contains("3 rows, 0 matched, 0 without match, 3 failed"));
+    assert!(summary.contains("row 4: invalid input: query is empty"));
+
+    let output = fs::read_to_string(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "id,street,city,latitude,longitude,label,osm_id,confidence",
+            "1,Marienplatz 8,München,,,,,",
+            "2,\"Unter den Linden 1, Mitte\",Berlin,,,,,",
+            "3,,,,,,,",
+        ]
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn geocode_reads_tsv() {
+    let dir = temp_dir("tsv");
+    let input = dir.join("places.tsv");
+    fs::write(&input, "name\tcountry\nEiffel Tower\tFR\n").unwrap();
+
+    let result = photon(&[
+        "--url",
+        UNREACHABLE,
+        "geocode",
+        input.to_str().unwrap(),
+        "--query",
+        "name",
+        "--query",
+        "country",
+    ]);
+    assert!(result.status.success(), "{:?}", result);
+    assert_eq!(
+        String::from_utf8_lossy(&result.stdout),
+        "name\tcountry\tlatitude\tlongitude\tlabel\tosm_id\tconfidence\nEiffel Tower\tFR\t\t\t\t\t\n"
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn geocode_rejects_invalid_mappings() {
+    let dir = temp_dir("mapping");
+    let input = dir.join("addresses.csv");
+    fs::write(&input, "street,city\nMarienplatz 8,München\n").unwrap();
+    let input = input.to_str().unwrap();
+
+    let output = photon(&["geocode", input, "--city", "town"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no column 'town' in the input"));
+
+    let output = photon(&["geocode", input, "--query", "street", "--city", "city"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be combined"));
+
+    let output = photon(&["geocode", input]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("map at least one column"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "geopackage")]
+#[test]
+fn geocode_creates_geopackage_layers() {
+    let dir = temp_dir("gpkg");
+    let input = dir.join("addresses.csv");
+    fs::write(&input, "street,city\nMarienplatz 8,München\n").unwrap();
+    let input = input.to_str().unwrap();
+    let gpkg = dir.join("addresses.gpkg");
+    let gpkg = gpkg.to_str().unwrap();
+
+    let output = photon(&["geocode", input, "--street", "street", "--extents"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--extents needs --gpkg"));
+
+    let output = photon(&[
+        "--url",
+        UNREACHABLE,
+        "geocode",
+        input,
+        "--street",
+        "street",
+        "--city",
+        "city",
+        "--gpkg",
+        gpkg,
+        "--extents",
+    ]);
+    assert!(output.status.success());
+    let db = rusqlite::Connection::open(gpkg).unwrap();
+    let layers: Vec<String> = db
+        .prepare("SELECT table_name FROM gpkg_contents ORDER BY table_name")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(layers, vec!["geocoded", "geocoded_extents"]);
+    // The only row failed, so there is nothing in the layers.
+    let count: i64 = db
+        .query_row("SELECT count(*) FROM geocoded", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 0);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn geocode_resumes_and_reports_failures() {
+    let dir = temp_dir("resume");
+    let input = dir.join("addresses.csv");
+    fs::write(&input, "id,address\n1,Marienplatz 8 München\n2,\n").unwrap();
+    let checkpoint = dir.join("job.checkpoint");
+    let failed = dir.join("failed.csv");
+    let args = [
+        "--url",
+        UNREACHABLE,
+        "geocode",
+        input.to_str().unwrap(),
+        "--query",
+        "address",
+        "--checkpoint",
+        checkpoint.to_str().unwrap(),
+        "--failed",
+        failed.to_str().unwrap(),
+    ];
+
+    let output = photon(&args);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("0 rows resumed"));
+    let report = fs::read_to_string(&failed).unwrap();
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "id,address,row,error_kind,error");
+    assert!(lines[1].starts_with("1,Marienplatz 8 München,2,transport,"));
+    assert_eq!(lines[2], "2,,3,invalid_input,invalid input: query is empty");
+
+    let output = photon(&args);
+    assert!(output.status.success(), "{:?}", output);
+    // The transport error is retried, the empty address is resumed.
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 rows resumed"));
+    assert_eq!(fs::read_to_string(&failed).unwrap(), report);
+
+    // The report can be geocoded on its own.
+    let output = photon(&[
+        "--url",
+        UNREACHABLE,
+        "geocode",
+        failed.to_str().unwrap(),
+        "--query",
+        "address",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2 rows, 0 matched"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn ndjson_streams_stdin_to_stdout() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_photon"))
+        .args(["--url", UNREACHABLE, "ndjson", "--threads", "2"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"{\"id\": 1, \"query\": \"Berlin\"}\n{\"id\": 2, \"city\": \"\"}\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let results: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["id"], 1);
+    assert_eq!(results[0]["error"]["kind"], "transport");
+    assert_eq!(results[1]["id"], 2);
+    assert_eq!(results[1]["error"]["kind"], "invalid_input");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2 queries, 0 matched"));
+}
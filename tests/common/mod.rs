@@ -0,0 +1,109 @@
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use photon_geocoding::{LatLon, OsmType, PhotonApiClient, PhotonFeature};
+
+/// A feature at `lat`/`lon` without any address fields.
+pub fn feature(lat: f64, lon: f64) -> PhotonFeature {
+    PhotonFeature {
+        coords: LatLon::new(lat, lon),
+        osm_id: 1,
+        osm_key: String::from("place"),
+        osm_type: OsmType::Node,
+        osm_value: String::from("house"),
+        r#type: String::from("house"),
+        extent: None,
+        name: None,
+        country: None,
+        country_iso_code: None,
+        state: None,
+        county: None,
+        city: None,
+        postcode: None,
+        district: None,
+        street: None,
+        house_number: None,
+    }
+}
+
+/// A house with a complete address in Munich.
+pub fn munich_house() -> PhotonFeature {
+    PhotonFeature {
+        street: Some(String::from("Marienplatz")),
+        house_number: Some(String::from("8")),
+        postcode: Some(String::from("80331")),
+        city: Some(String::from("München")),
+        state: Some(String::from("Bayern")),
+        country: Some(String::from("Deutschland")),
+        country_iso_code: Some(String::from("DE")),
+        ..feature(48.1373, 11.5755)
+    }
+}
+
+/// The city of Munich with its extent, read from a Photon response.
+pub fn munich_city() -> PhotonFeature {
+    photon_feature(
+        r#"{
+            "geometry": {"coordinates": [11.575, 48.137], "type": "Point"},
+            "type": "Feature",
+            "properties": {
+                "osm_id": 62428, "osm_type": "R", "osm_key": "place", "osm_value": "city",
+                "type": "city", "extent": [11.36, 48.25, 11.72, 48.06],
+                "name": "München", "country": "Deutschland"
+            }
+        }"#,
+    )
+}
+
+/// A Photon response with the given GeoJSON features.
+pub fn feature_collection(features: &[&str]) -> String {
+    format!(
+        r#"{{"type": "FeatureCollection", "features": [{}]}}"#,
+        features.join(",")
+    )
+}
+
+/// Answers every request on a local port with the JSON `body` and records the request line of
+/// each request. Returns the base URL of the server.
+pub fn serve_json(body: String) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&requests);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            log.lock().unwrap().push(line.trim().to_string());
+            // Skip the headers.
+            let mut header = String::new();
+            while reader.read_line(&mut header).unwrap() > 2 {
+                header.clear();
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    (url, requests)
+}
+
+/// Reads a GeoJSON feature in the format of Photon's responses the way the client does, by
+/// searching a local server that returns it.
+pub fn photon_feature(json: &str) -> PhotonFeature {
+    let (url, _) = serve_json(feature_collection(&[json]));
+    PhotonApiClient::new(&url)
+        .forward_search("fixture", None)
+        .unwrap()
+        .remove(0)
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use photon_geocoding::dedupe::{self, DedupeRule, Deduplicator};
+use photon_geocoding::{BoundingBox, LatLon, OsmType, PhotonFeature};
+
+fn place(osm_id: u64, name: &str, lat: f64, lon: f64) -> PhotonFeature {
+    PhotonFeature {
+        osm_id,
+        name: Some(String::from(name)),
+        city: Some(String::from("München")),
+        ..common::feature(lat, lon)
+    }
+}
+
+fn street_segment(osm_id: u64, lat: f64, lon: f64) -> PhotonFeature {
+    PhotonFeature {
+        osm_type: OsmType::Way,
+        osm_key: String::from("highway"),
+        osm_value: String::from("residential"),
+        r#type: String::from("street"),
+        extent: Some(BoundingBox {
+            south_west: LatLon::new(lat - 0.001, lon - 0.001),
+            north_east: LatLon::new(lat + 0.001, lon + 0.001),
+        }),
+        ..place(osm_id, "Leopoldstraße", lat, lon)
+    }
+}
+
+#[test]
+fn merges_same_osm_object() {
+    let features = vec![
+        place(1, "Rathaus", 48.137, 11.575),
+        place(2, "Frauenkirche", 48.138, 11.573),
+        place(1, "Neues Rathaus", 48.137, 11.576),
+    ];
+    let result = Deduplicator::new()
+        .rules(&[DedupeRule::SameOsmObject])
+        .dedupe(features);
+
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].name.as_deref(), Some("Rathaus"));
+    assert_eq!(result[1].name.as_deref(), Some("Frauenkirche"));
+    let extent = result[0].extent.unwrap();
+    assert!(extent.contains(&LatLon::new(48.137, 11.5755)));
+    assert_eq!(result[1].extent, None);
+}
+
+#[test]
+fn merges_same_name_nearby() {
+    let way = PhotonFeature {
+        osm_type: OsmType::Way,
+        ..place(1, "Hofbräuhaus", 48.1376, 11.5797)
+    };
+    let node = place(2, "hofbräuhaus ", 48.1377, 11.5798);
+    let far = place(3, "Hofbräuhaus", 48.2, 11.6);
+
+    let result = dedupe::dedupe(vec![way.clone(), node.clone(), far.clone()]);
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].osm_type, OsmType::Way);
+    assert_eq!(result[1].osm_id, 3);
+
+    let other_city = PhotonFeature {
+        city: Some(String::from("Berlin")),
+        ..node.clone()
+    };
+    assert_eq!(dedupe::dedupe(vec![way.clone(), other_city]).len(), 2);
+
+    let strict = Deduplicator::new().rules(&[DedupeRule::SameNameNearby { max_distance: 1.0 }]);
+    assert_eq!(strict.dedupe(vec![way, node]).len(), 2);
+}
+
+#[test]
+fn merges_street_segments_in_city() {
+    let features = vec![
+        street_segment(10, 48.16, 11.586),
+        place(5, "Leopoldstraße", 48.17, 11.586),
+        street_segment(11, 48.17, 11.586),
+        street_segment(12, 48.18, 11.587),
+    ];
+    let result = Deduplicator::new()
+        .rules(&[DedupeRule::SameStreetInCity])
+        .dedupe(features);
+
+    // The house-type feature named like the street is not a street.
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].osm_id, 10);
+    assert_eq!(result[1].osm_id, 5);
+    let extent = result[0].extent.unwrap();
+    assert!(extent.contains(&LatLon::new(48.1805, 11.5875)));
+    assert!(extent.contains(&LatLon::new(48.1595, 11.5855)));
+}
+
+#[test]
+fn groups_are_transitive() {
+    let deduplicator = Deduplicator::new().rules(&[DedupeRule::SameNameNearby {
+        max_distance: 150.0,
+    }]);
+    // Each feature is within 150 m of the next, but the first and the last are not.
+    let features = vec![
+        place(1, "Markt", 48.0, 11.0),
+        place(2, "Markt", 48.001, 11.0),
+        place(3, "Markt", 48.002, 11.0),
+    ];
+    assert!(!deduplicator.is_duplicate(&features[0], &features[2]));
+    let result = deduplicator.dedupe(features);
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].osm_id, 1);
+}
+
+#[test]
+fn keeps_features_without_names() {
+    let features = vec![
+        common::feature(48.0, 11.0),
+        PhotonFeature {
+            osm_id: 2,
+            ..common::feature(48.0, 11.0)
+        },
+    ];
+    assert_eq!(dedupe::dedupe(features).len(), 2);
+    assert!(dedupe::dedupe(Vec::new()).is_empty());
+}
+
+/// A segment of Leopoldstraße as returned by Photon, with the extent in Photon's
+/// `[min_lon, max_lat, max_lon, min_lat]` order.
+fn photon_segment(osm_id: u64, extent: [f64; 4]) -> PhotonFeature {
+    let json = format!(
+        r#"{{
+            "geometry": {{"coordinates": [{}, {}], "type": "Point"}},
+            "type": "Feature",
+            "properties": {{
+                "osm_id": {}, "osm_type": "W", "osm_key": "highway", "osm_value": "secondary",
+                "type": "street", "extent": {:?}, "name": "Leopoldstraße", "city": "München"
+            }}
+        }}"#,
+        (extent[0] + extent[2]) / 2.0,
+        (extent[1] + extent[3]) / 2.0,
+        osm_id,
+        extent
+    );
+    common::photon_feature(&json)
+}
+
+#[test]
+fn merges_extents_of_photon_responses() {
+    let features = vec![
+        photon_segment(20, [11.5851, 48.1632, 11.5866, 48.1589]),
+        photon_segment(21, [11.5862, 48.1724, 11.5873, 48.1632]),
+    ];
+    let result = dedupe::dedupe(features);
+
+    assert_eq!(result.len(), 1);
+    let extent = result[0].extent.unwrap();
+    assert_eq!(extent.south_west, LatLon::new(48.1589, 11.5851));
+    assert_eq!(extent.north_east, LatLon::new(48.1724, 11.5873));
+    assert!(extent.validate().is_ok());
+    assert!(extent.contains(&LatLon::new(48.16, 11.586)));
+    assert!(extent.contains(&LatLon::new(48.17, 11.587)));
+}
@@ -0,0 +1,112 @@
+use photon_geocoding::export::gpx::{self, GpxOptions};
+use photon_geocoding::export::kml::{self, KmlOptions};
+use photon_geocoding::label::{AddressFormatter, AddressTemplate};
+use photon_geocoding::{BoundingBox, LatLon};
+
+mod common;
+
+#[test]
+fn kml_has_placemarks_with_points() {
+    let kml = kml::to_kml(&[common::munich_house()], None);
+    assert!(kml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(kml.contains("<kml xmlns=\"http://www.opengis.net/kml/2.2\">"));
+    assert!(kml.contains("<name>Photon results</name>"));
+    assert!(kml.contains(
+        "  <Placemark>\n    <name>Marienplatz 8</name>\n    \
+         <description>Marienplatz 8\n80331 München\nDeutschland</description>\n"
+    ));
+    assert!(kml.contains("<Data name=\"postcode\"><value>80331</value></Data>"));
+    assert!(kml.contains("<Data name=\"osm_type\"><value>N</value></Data>"));
+    assert!(!kml.contains("<Data name=\"county\">"));
+    assert!(kml.contains("    <Point><coordinates>11.5755,48.1373</coordinates></Point>\n"));
+    assert!(!kml.contains("Polygon"));
+    assert!(kml.ends_with("</Document>\n</kml>\n"));
+}
+
+#[test]
+fn kml_draws_extents_as_polygons() {
+    let kml = kml::to_kml(
+        &[common::munich_city()],
+        Some(KmlOptions::new().name("Cities")),
+    );
+    assert!(kml.contains("<name>Cities</name>"));
+    assert!(kml.contains("<name>München</name>"));
+    assert!(kml.contains(
+        "    <MultiGeometry>\n      <Point><coordinates>11.575,48.137</coordinates></Point>\n      \
+         <Polygon><outerBoundaryIs><LinearRing><coordinates>11.36,48.06 11.72,48.06 11.72,48.25 \
+         11.36,48.25 11.36,48.06</coordinates></LinearRing></outerBoundaryIs></Polygon>\n    \
+         </MultiGeometry>\n"
+    ));
+}
+
+#[test]
+fn kml_leaves_out_extents_across_the_antimeridian() {
+    let mut fiji = common::munich_city();
+    fiji.extent = Some(BoundingBox {
+        south_west: LatLon::new(-21.0, 176.0),
+        north_east: LatLon::new(-12.0, -178.0),
+    });
+    let kml = kml::to_kml(&[fiji], None);
+    assert!(kml.contains("<Point>"));
+    assert!(!kml.contains("Polygon"));
+}
+
+#[test]
+fn escapes_xml() {
+    let mut feature = common::feature(1.0, 2.0);
+    feature.name = Some(String::from("Fish & Chips <\"Nelson's\">"));
+    let escaped = "Fish &amp; Chips &lt;&quot;Nelson&apos;s&quot;&gt;";
+    assert!(kml::to_kml(&[feature.clone()], None).contains(&format!("<name>{}</name>", escaped)));
+    assert!(gpx::to_gpx(&[feature], None).contains(&format!("<name>{}</name>", escaped)));
+}
+
+#[test]
+fn gpx_has_waypoints() {
+    let gpx = gpx::to_gpx(&[common::munich_house(), common::munich_city()], None);
+    assert!(gpx.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    assert!(gpx.contains(
+        "<gpx version=\"1.1\" creator=\"photon-geocoding\" \
+         xmlns=\"http://www.topografix.com/GPX/1/1\">"
+    ));
+    assert!(gpx.contains(
+        "  <wpt lat=\"48.1373\" lon=\"11.5755\">\n    <name>Marienplatz 8</name>\n    \
+         <desc>Marienplatz 8\n80331 München\nDeutschland</desc>\n    <type>house</type>\n  </wpt>\n"
+    ));
+    assert!(gpx.contains(
+        "  <wpt lat=\"48.137\" lon=\"11.575\">\n    <name>München</name>\n    \
+         <desc>München\nDeutschland</desc>\n    <type>city</type>\n  </wpt>\n"
+    ));
+    assert!(gpx.ends_with("</gpx>\n"));
+}
+
+#[test]
+fn gpx_uses_options() {
+    let formatter = AddressFormatter::new()
+        .template("DE", AddressTemplate::parse("{postcode} {city}").unwrap());
+    let options = GpxOptions::new().creator("Field app").formatter(formatter);
+    let gpx = gpx::to_gpx(&[common::munich_house()], Some(options));
+    assert!(gpx.contains("creator=\"Field app\""));
+    // Without a name, the first address line names the waypoint.
+    assert!(gpx.contains("<name>80331 München</name>"));
+    assert!(gpx.contains("<desc>80331 München</desc>"));
+}
+
+#[test]
+fn writes_files() {
+    let dir = std::env::temp_dir();
+    let kml_path = dir.join(format!("photon-{}.kml", std::process::id()));
+    let gpx_path = dir.join(format!("photon-{}.gpx", std::process::id()));
+    let features = [common::munich_house(), common::munich_city()];
+    kml::write_kml_file(&kml_path, &features, None).unwrap();
+    gpx::write_gpx_file(&gpx_path, &features, None).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&kml_path).unwrap(),
+        kml::to_kml(&features, None)
+    );
+    assert_eq!(
+        std::fs::read_to_string(&gpx_path).unwrap(),
+        gpx::to_gpx(&features, None)
+    );
+    std::fs::remove_file(kml_path).unwrap();
+    std::fs::remove_file(gpx_path).unwrap();
+}
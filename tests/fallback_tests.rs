@@ -0,0 +1,104 @@
+use photon_geocoding::address::parse_address;
+use photon_geocoding::error::InvalidInputError;
+use photon_geocoding::fallback::{FallbackLevel, FallbackStrategy};
+use photon_geocoding::filter::{ForwardFilter, PhotonLayer};
+use photon_geocoding::scoring::AddressField;
+use photon_geocoding::{PhotonApiClient, StructuredAddress};
+
+fn planned_queries(strategy: &FallbackStrategy, address: &str) -> Vec<(String, String)> {
+    strategy
+        .plan(&parse_address(address).unwrap())
+        .into_iter()
+        .map(|(index, query)| (strategy.levels[index].name.clone(), query.to_string()))
+        .collect()
+}
+
+#[test]
+fn default_strategy_gets_coarser() {
+    let queries = planned_queries(&FallbackStrategy::new(), "Musterstr. 12a, 80331 München");
+    let expected = [
+        ("full", "Musterstr. 12a, 80331 München"),
+        ("without_house_number", "Musterstr., 80331 München"),
+        ("street", "Musterstr., München"),
+        ("postcode_and_city", "80331 München"),
+        ("city", "München"),
+    ];
+    let expected: Vec<(String, String)> = expected
+        .iter()
+        .map(|(level, query)| (level.to_string(), query.to_string()))
+        .collect();
+    assert_eq!(queries, expected);
+}
+
+#[test]
+fn default_strategy_keeps_the_country() {
+    let queries = planned_queries(
+        &FallbackStrategy::new(),
+        "Rue de Rivoli 1, 75001 Paris, France",
+    );
+    let levels: Vec<&str> = queries.iter().map(|(level, _)| level.as_str()).collect();
+    assert_eq!(
+        levels,
+        [
+            "full",
+            "without_house_number",
+            "street",
+            "postcode_and_city",
+            "city"
+        ]
+    );
+    assert!(queries.iter().all(|(_, query)| query.ends_with(", FR")));
+    assert_eq!(queries[4].1, "Paris, FR");
+}
+
+#[test]
+fn levels_without_required_component_are_skipped() {
+    let queries = planned_queries(&FallbackStrategy::new(), "Marienplatz, München");
+    let levels: Vec<&str> = queries.iter().map(|(level, _)| level.as_str()).collect();
+    // The full address lacks a house number, and without a postcode the second level would repeat
+    // the street search.
+    assert_eq!(levels, ["without_house_number", "city"]);
+
+    let queries = planned_queries(&FallbackStrategy::new(), "10115 Berlin");
+    let levels: Vec<&str> = queries.iter().map(|(level, _)| level.as_str()).collect();
+    assert_eq!(levels, ["postcode_and_city", "city"]);
+}
+
+#[test]
+fn custom_levels() {
+    let strategy = FallbackStrategy::new().levels(vec![
+        FallbackLevel::street(),
+        FallbackLevel::new("country", &[AddressField::Country], &[PhotonLayer::Country]),
+    ]);
+    let queries = planned_queries(&strategy, "Marienplatz 8, 80331 München, Germany");
+    assert_eq!(
+        queries,
+        [
+            (
+                String::from("street"),
+                String::from("Marienplatz, München, DE")
+            ),
+            (String::from("country"), String::from("DE")),
+        ]
+    );
+
+    let level = FallbackLevel::full();
+    assert_eq!(
+        level.address(&StructuredAddress::new().city("München")),
+        None
+    );
+}
+
+#[test]
+fn invalid_input_is_rejected_before_request() {
+    // Nothing listens on this port, so any request that is actually sent fails with a transport
+    // error.
+    let api = PhotonApiClient::new("http://127.0.0.1:9");
+    let strategy = FallbackStrategy::new().filter(ForwardFilter::new().limit(0));
+
+    let empty = api.geocode_with_fallback(" ", None);
+    let invalid_filter = api.geocode_with_fallback("10115 Berlin", Some(strategy));
+
+    assert!(empty.unwrap_err().is::<InvalidInputError>());
+    assert!(invalid_filter.unwrap_err().is::<InvalidInputError>());
+}
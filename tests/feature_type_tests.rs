@@ -0,0 +1,48 @@
+use photon_geocoding::filter::PhotonLayer;
+use photon_geocoding::FeatureType;
+
+#[test]
+fn parses_layers_and_types() {
+    assert_eq!("City".parse::<PhotonLayer>(), Ok(PhotonLayer::City));
+    assert!("river".parse::<PhotonLayer>().is_err());
+    assert_eq!(FeatureType::from("street"), FeatureType::Street);
+    assert_eq!(
+        FeatureType::from("river"),
+        FeatureType::Other(String::from("river"))
+    );
+}
+
+#[test]
+fn converts_between_layers_and_types() {
+    assert_eq!(FeatureType::from(PhotonLayer::State), FeatureType::State);
+    assert_eq!(
+        PhotonLayer::try_from(FeatureType::District),
+        Ok(PhotonLayer::District)
+    );
+    assert!(PhotonLayer::try_from(FeatureType::Other(String::from("other"))).is_err());
+    assert_eq!(FeatureType::County.to_string(), "county");
+}
+
+#[test]
+fn orders_by_granularity() {
+    let mut types = vec![
+        FeatureType::Country,
+        FeatureType::House,
+        FeatureType::City,
+        FeatureType::Other(String::from("other")),
+        FeatureType::Street,
+    ];
+    types.sort();
+
+    assert_eq!(
+        types,
+        vec![
+            FeatureType::Other(String::from("other")),
+            FeatureType::House,
+            FeatureType::Street,
+            FeatureType::City,
+            FeatureType::Country,
+        ]
+    );
+    assert!(PhotonLayer::District < PhotonLayer::County);
+}
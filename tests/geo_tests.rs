@@ -0,0 +1,111 @@
+use photon_geocoding::geo::{filter_by_distance, sort_by_distance};
+use photon_geocoding::{LatLon, PhotonFeature};
+
+mod common;
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{} is not within {} of {}",
+        actual,
+        tolerance,
+        expected
+    );
+}
+
+#[test]
+fn haversine_and_vincenty_agree_roughly() {
+    let munich = LatLon::new(48.137, 11.575);
+    let berlin = LatLon::new(52.520, 13.405);
+
+    let haversine = munich.haversine_distance(&berlin);
+    let vincenty = munich.vincenty_distance(&berlin).unwrap();
+
+    assert_close(vincenty, 504_500.0, 1_000.0);
+    assert_close(haversine, vincenty, vincenty * 0.005);
+}
+
+#[test]
+fn vincenty_of_identical_points_is_zero() {
+    let point = LatLon::new(10.0, 20.0);
+    assert_eq!(point.vincenty_distance(&point), Some(0.0));
+}
+
+#[test]
+fn destination_inverts_bearing_and_distance() {
+    let start = LatLon::new(48.137, 11.575);
+    let target = LatLon::new(52.520, 13.405);
+
+    let bearing = start.initial_bearing(&target);
+    let distance = start.haversine_distance(&target);
+    let reached = start.destination(bearing, distance);
+
+    assert_close(reached.lat, target.lat, 1e-6);
+    assert_close(reached.lon, target.lon, 1e-6);
+}
+
+#[test]
+fn destination_wraps_across_antimeridian() {
+    let start = LatLon::new(0.0, 179.5);
+    let reached = start.destination(90.0, 111_195.0);
+
+    assert_close(reached.lon, -179.5, 1e-3);
+}
+
+#[test]
+fn midpoint_lies_halfway() {
+    let a = LatLon::new(0.0, 0.0);
+    let b = LatLon::new(0.0, 90.0);
+    let mid = a.midpoint(&b);
+
+    assert_close(mid.lat, 0.0, 1e-9);
+    assert_close(mid.lon, 45.0, 1e-9);
+    assert_close(a.initial_bearing(&b), 90.0, 1e-9);
+}
+
+fn with_id(osm_id: u64, lat: f64, lon: f64) -> PhotonFeature {
+    let mut feature = common::feature(lat, lon);
+    feature.osm_id = osm_id;
+    feature
+}
+
+fn ids(features: &[PhotonFeature]) -> Vec<u64> {
+    features.iter().map(|feature| feature.osm_id).collect()
+}
+
+#[test]
+fn sorts_by_distance() {
+    let munich = LatLon::new(48.137, 11.575);
+    let mut features = vec![
+        with_id(1, 52.520, 13.405),
+        with_id(2, 48.140, 11.580),
+        with_id(3, 48.400, 11.700),
+        // The same position as 2, which keeps the order of the input.
+        with_id(4, 48.140, 11.580),
+    ];
+    sort_by_distance(&mut features, &munich);
+    assert_eq!(ids(&features), vec![2, 4, 3, 1]);
+
+    let mut empty: Vec<PhotonFeature> = Vec::new();
+    sort_by_distance(&mut empty, &munich);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn filters_by_distance() {
+    let munich = LatLon::new(48.137, 11.575);
+    let features = vec![
+        with_id(1, 52.520, 13.405),
+        with_id(2, 48.140, 11.580),
+        with_id(3, 48.400, 11.700),
+    ];
+    let radius = features[2].coords.haversine_distance(&munich);
+
+    // A feature exactly at the radius is kept.
+    let kept = filter_by_distance(features.clone(), &munich, radius);
+    assert_eq!(ids(&kept), vec![2, 3]);
+    let kept = filter_by_distance(features.clone(), &munich, radius - 1.0);
+    assert_eq!(ids(&kept), vec![2]);
+    assert!(filter_by_distance(features, &munich, 0.0).is_empty());
+    assert!(filter_by_distance(Vec::new(), &munich, radius).is_empty());
+}
@@ -0,0 +1,35 @@
+use photon_geocoding::geohash::{decode, encode};
+use photon_geocoding::LatLon;
+
+#[test]
+fn encodes_known_hashes() {
+    let coords = LatLon::new(57.64911, 10.40744);
+    assert_eq!(encode(&coords, 11).unwrap(), "u4pruydqqvj");
+    assert_eq!(coords.to_geohash(5).unwrap(), "u4pru");
+    assert_eq!(encode(&LatLon::new(-90.0, -180.0), 3).unwrap(), "000");
+    assert_eq!(encode(&LatLon::new(90.0, 180.0), 3).unwrap(), "zzz");
+}
+
+#[test]
+fn decodes_cells() {
+    let cell = decode("ezs42").unwrap();
+    let center = cell.center();
+    assert!((center.lat - 42.605).abs() < 0.01);
+    assert!((center.lon - -5.603).abs() < 0.01);
+    assert!(cell.contains(&LatLon::new(42.6, -5.6)));
+    assert_eq!(decode("EZS42").unwrap(), cell);
+
+    let coords = LatLon::new(48.1372, 11.5755);
+    let decoded = LatLon::from_geohash(&coords.to_geohash(9).unwrap()).unwrap();
+    assert!(decoded.haversine_distance(&coords) < 5.0);
+}
+
+#[test]
+fn rejects_invalid_input() {
+    assert!(encode(&LatLon::new(48.0, 11.0), 0).is_err());
+    assert!(encode(&LatLon::new(48.0, 11.0), 13).is_err());
+    assert!(encode(&LatLon::new(91.0, 11.0), 5).is_err());
+    assert!(decode("").is_err());
+    assert!(decode("u4pa").is_err());
+    assert!(decode("u4pruydqqvjzz").is_err());
+}
@@ -0,0 +1,326 @@
+#![cfg(feature = "geopackage")]
+
+use std::path::PathBuf;
+
+use photon_geocoding::export::geopackage::{write_geopackage, GeoPackageOptions, GeoPackageWriter};
+use rusqlite::Connection;
+
+mod common;
+
+fn temp_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("photon-{}-{}.gpkg", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn f64_at(blob: &[u8], offset: usize) -> f64 {
+    f64::from_le_bytes(blob[offset..offset + 8].try_into().unwrap())
+}
+
+fn u32_at(blob: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(blob[offset..offset + 4].try_into().unwrap())
+}
+
+#[test]
+fn writes_geopackage_metadata() {
+    let path = temp_file("metadata");
+    write_geopackage(
+        &path,
+        &[common::munich_house(), common::munich_city()],
+        None,
+    )
+    .unwrap();
+
+    let db = Connection::open(&path).unwrap();
+    let application_id: i32 = db
+        .query_row("PRAGMA application_id", [], |row| row.get(0))
+        .unwrap();
+    let user_version: i32 = db
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(application_id, 0x4750_4B47);
+    assert_eq!(user_version, 10300);
+
+    let srs: Vec<i32> = db
+        .prepare("SELECT srs_id FROM gpkg_spatial_ref_sys ORDER BY srs_id")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(srs, vec![-1, 0, 4326]);
+
+    let contents: (String, i32, f64, f64, f64, f64) = db
+        .query_row(
+            "SELECT data_type, srs_id, min_x, min_y, max_x, max_y FROM gpkg_contents \
+             WHERE table_name = 'features'",
+            [],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        contents,
+        (
+            String::from("features"),
+            4326,
+            11.5750,
+            48.137,
+            11.5755,
+            48.1373
+        )
+    );
+
+    let column: (String, String, i32) = db
+        .query_row(
+            "SELECT column_name, geometry_type_name, srs_id FROM gpkg_geometry_columns \
+             WHERE table_name = 'features'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap();
+    assert_eq!(column, (String::from("geom"), String::from("POINT"), 4326));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn writes_points_and_attributes() {
+    let path = temp_file("points");
+    let mut writer = GeoPackageWriter::create(&path, None).unwrap();
+    writer
+        .add(&common::munich_house(), Some("Marienplatz 8"))
+        .unwrap();
+    writer.add(&common::munich_city(), None).unwrap();
+    writer.finish().unwrap();
+
+    let db = Connection::open(&path).unwrap();
+    let mut statement = db
+        .prepare(
+            "SELECT geom, query, osm_id, osm_type, name, street, house_number, country_code, label \
+             FROM features ORDER BY fid",
+        )
+        .unwrap();
+    type Row = (
+        Vec<u8>,
+        Option<String>,
+        i64,
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        String,
+    );
+    let rows: Vec<Row> = statement
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+                row.get(7)?,
+                row.get(8)?,
+            ))
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+
+    let (geom, query, osm_id, osm_type, name, street, house_number, country_code, label) = &rows[0];
+    // Header: magic, version 0, little endian without envelope, SRS 4326.
+    assert_eq!(&geom[..4], &[b'G', b'P', 0, 1]);
+    assert_eq!(u32_at(geom, 4), 4326);
+    // Little-endian WKB point with longitude first.
+    assert_eq!(geom.len(), 8 + 21);
+    assert_eq!(geom[8], 1);
+    assert_eq!(u32_at(geom, 9), 1);
+    assert_eq!(f64_at(geom, 13), 11.5755);
+    assert_eq!(f64_at(geom, 21), 48.1373);
+    assert_eq!(query.as_deref(), Some("Marienplatz 8"));
+    assert_eq!(*osm_id, 1);
+    assert_eq!(osm_type, "N");
+    assert_eq!(*name, None);
+    assert_eq!(street.as_deref(), Some("Marienplatz"));
+    assert_eq!(house_number.as_deref(), Some("8"));
+    assert_eq!(country_code.as_deref(), Some("DE"));
+    assert_eq!(label, &common::munich_house().label());
+
+    assert_eq!(rows[1].1, None);
+    assert_eq!(rows[1].2, 62428);
+    assert_eq!(rows[1].4.as_deref(), Some("München"));
+    assert_eq!(rows[1].5, None);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn writes_extent_layer() {
+    let path = temp_file("extents");
+    let options = GeoPackageOptions::new()
+        .layer("places")
+        .extent_layer("extents");
+    write_geopackage(
+        &path,
+        &[common::munich_house(), common::munich_city()],
+        Some(options),
+    )
+    .unwrap();
+
+    let db = Connection::open(&path).unwrap();
+    let points: i64 = db
+        .query_row("SELECT count(*) FROM places", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(points, 2);
+    let geometry_type: String = db
+        .query_row(
+            "SELECT geometry_type_name FROM gpkg_geometry_columns WHERE table_name = 'extents'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(geometry_type, "POLYGON");
+
+    // Only the city has an extent.
+    let extents: Vec<(Vec<u8>, i64)> = db
+        .prepare("SELECT geom, osm_id FROM extents")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(extents.len(), 1);
+    let (geom, osm_id) = &extents[0];
+    assert_eq!(*osm_id, 62428);
+    // Header with an envelope of min x, max x, min y, max y.
+    assert_eq!(&geom[..4], &[b'G', b'P', 0, 3]);
+    assert_eq!(
+        [
+            f64_at(geom, 8),
+            f64_at(geom, 16),
+            f64_at(geom, 24),
+            f64_at(geom, 32)
+        ],
+        [11.36, 11.72, 48.06, 48.25]
+    );
+    // Polygon with one closed ring of five points.
+    let wkb = &geom[40..];
+    assert_eq!(wkb.len(), 1 + 4 + 4 + 4 + 5 * 16);
+    assert_eq!(u32_at(wkb, 1), 3);
+    assert_eq!(u32_at(wkb, 5), 1);
+    assert_eq!(u32_at(wkb, 9), 5);
+    let ring: Vec<(f64, f64)> = (0..5)
+        .map(|i| (f64_at(wkb, 13 + i * 16), f64_at(wkb, 21 + i * 16)))
+        .collect();
+    assert_eq!(
+        ring,
+        vec![
+            (11.36, 48.06),
+            (11.72, 48.06),
+            (11.72, 48.25),
+            (11.36, 48.25),
+            (11.36, 48.06)
+        ]
+    );
+
+    let bounds: (f64, f64, f64, f64) = db
+        .query_row(
+            "SELECT min_x, min_y, max_x, max_y FROM gpkg_contents WHERE table_name = 'extents'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .unwrap();
+    assert_eq!(bounds, (11.36, 48.06, 11.72, 48.25));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn adds_layers_to_existing_file() {
+    let path = temp_file("existing");
+    write_geopackage(&path, &[common::munich_city()], None).unwrap();
+    write_geopackage(
+        &path,
+        &[common::munich_house()],
+        Some(GeoPackageOptions::new().layer("houses")),
+    )
+    .unwrap();
+
+    let error = write_geopackage(&path, &[common::munich_city()], None).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid input: layer 'features' already exists"
+    );
+
+    let db = Connection::open(&path).unwrap();
+    let layers: Vec<String> = db
+        .prepare("SELECT table_name FROM gpkg_contents ORDER BY table_name")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        layers,
+        vec![String::from("features"), String::from("houses")]
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn leaves_file_unchanged_without_finish() {
+    let path = temp_file("unfinished");
+    let db = Connection::open(&path).unwrap();
+    db.execute_batch("CREATE TABLE features (id INTEGER)")
+        .unwrap();
+    let header = |db: &Connection| -> (i32, i32, i64) {
+        let pragma = |name: &str| {
+            db.query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))
+                .unwrap()
+        };
+        let tables = db
+            .query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get(0))
+            .unwrap();
+        (pragma("application_id"), pragma("user_version"), tables)
+    };
+
+    // The layer exists as a plain table, so the file is not turned into a GeoPackage.
+    assert!(GeoPackageWriter::create(&path, None).is_err());
+    assert_eq!(header(&db), (0, 0, 1));
+
+    let mut writer =
+        GeoPackageWriter::create(&path, Some(GeoPackageOptions::new().layer("places"))).unwrap();
+    writer.add(&common::munich_city(), None).unwrap();
+    drop(writer);
+    assert_eq!(header(&db), (0, 0, 1));
+
+    drop(db);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rejects_invalid_layer_names() {
+    assert!(GeoPackageOptions::new().layer(" ").validate().is_err());
+    assert!(GeoPackageOptions::new()
+        .layer("gpkg_contents")
+        .validate()
+        .is_err());
+    assert!(GeoPackageOptions::new()
+        .extent_layer("features")
+        .validate()
+        .is_err());
+    assert!(GeoPackageOptions::new()
+        .layer("my \"layer\"")
+        .extent_layer("extents")
+        .validate()
+        .is_ok());
+}
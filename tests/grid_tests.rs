@@ -0,0 +1,128 @@
+use photon_geocoding::error::InvalidInputError;
+use photon_geocoding::grid::{Hemisphere, Mgrs, Utm};
+use photon_geocoding::{LatLon, PhotonApiClient};
+
+mod common;
+
+#[test]
+fn converts_to_utm() {
+    let eiffel_tower = LatLon::new(48.8583, 2.2945).to_utm().unwrap();
+
+    assert_eq!(eiffel_tower.zone, 31);
+    assert_eq!(eiffel_tower.band, 'U');
+    assert_eq!(eiffel_tower.to_string(), "31U 448252 5411944");
+
+    let origin = LatLon::new(0.0, 3.0).to_utm().unwrap();
+    assert!((origin.easting - 500_000.0).abs() < 1e-6);
+    assert!(origin.northing.abs() < 1e-6);
+}
+
+#[test]
+fn applies_zone_exceptions() {
+    assert_eq!(LatLon::new(60.0, 5.0).to_utm().unwrap().zone, 32);
+    assert_eq!(LatLon::new(78.0, 20.0).to_utm().unwrap().zone, 33);
+    assert_eq!(LatLon::new(48.0, 11.0).to_utm_zone(33).unwrap().zone, 33);
+    assert!(LatLon::new(85.0, 0.0).to_utm().is_err());
+}
+
+#[test]
+fn utm_roundtrips_in_both_hemispheres() {
+    for coords in [
+        LatLon::new(48.137, 11.575),
+        LatLon::new(-33.8568, 151.2153),
+        LatLon::new(-79.5, -179.9),
+    ] {
+        let utm = coords.to_utm().unwrap();
+        let parsed: Utm = utm.to_string().parse().unwrap();
+        let back = utm.to_latlon();
+
+        assert_eq!(utm.hemisphere() == Hemisphere::South, coords.lat < 0.0);
+        assert!(coords.haversine_distance(&back) < 0.01);
+        assert!(coords.haversine_distance(&parsed.to_latlon()) < 1.0);
+    }
+}
+
+#[test]
+fn converts_to_mgrs() {
+    let eiffel_tower = LatLon::new(48.8583, 2.2945);
+
+    assert_eq!(
+        eiffel_tower.to_mgrs(5).unwrap().to_string(),
+        "31U DQ 48251 11943"
+    );
+    assert_eq!(eiffel_tower.to_mgrs(2).unwrap().to_string(), "31U DQ 48 11");
+    assert_eq!(eiffel_tower.to_mgrs(0).unwrap().to_string(), "31U DQ");
+    assert!(eiffel_tower.to_mgrs(6).is_err());
+}
+
+#[test]
+fn mgrs_roundtrips() {
+    for coords in [
+        LatLon::new(48.137, 11.575),
+        LatLon::new(-33.8568, 151.2153),
+        LatLon::new(64.1466, -21.9426),
+        LatLon::new(-54.8019, -68.3030),
+    ] {
+        let mgrs = coords.to_mgrs(5).unwrap();
+        let parsed: Mgrs = mgrs.to_string().replace(' ', "").parse().unwrap();
+
+        assert_eq!(parsed, mgrs);
+        assert!(coords.haversine_distance(&LatLon::try_from(parsed).unwrap()) < 1.5);
+    }
+    assert!("31U DI 48251 11932".parse::<Mgrs>().is_err());
+    assert!("31U DQ 4825 11932".parse::<Mgrs>().is_err());
+}
+
+#[test]
+fn rejects_non_ascii_input_without_panicking() {
+    for input in [
+        "32ÜPU9184532618",
+        "32Ü PU 91845 32618",
+        "3Ü",
+        "32UPÜ9184532618",
+        "32UPU91845326ß8",
+        "ÜÜÜÜ",
+        "32U\u{2003}PU 91845 32618x",
+    ] {
+        assert!(input.parse::<Mgrs>().is_err(), "{}", input);
+    }
+    for input in [
+        "32Ü 691607 5334760",
+        "Ü 691607 5334760",
+        "32UÜ 691607 5334760",
+    ] {
+        assert!(input.parse::<Utm>().is_err(), "{}", input);
+    }
+}
+
+#[test]
+fn converts_only_valid_mgrs_references() {
+    let mut mgrs: Mgrs = "32U PU 91845 32618".parse().unwrap();
+    assert_eq!(LatLon::try_from(mgrs), mgrs.to_latlon());
+    mgrs.column = 'I';
+    assert!(LatLon::try_from(mgrs).is_err());
+}
+
+#[test]
+fn reverse_searches_grid_references() {
+    let (url, requests) = common::serve_json(common::feature_collection(&[]));
+    let api = PhotonApiClient::new(&url);
+
+    let mut mgrs: Mgrs = "32U PU 91845 32618".parse().unwrap();
+    assert!(api.reverse_search(mgrs, None).unwrap().is_empty());
+    let utm = mgrs.to_utm().unwrap();
+    assert!(api.reverse_search(utm, None).unwrap().is_empty());
+    {
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let coords = mgrs.to_latlon().unwrap();
+        let query = format!("lon={}&lat={}", coords.lon, coords.lat);
+        assert!(requests[0].contains(&query), "{}", requests[0]);
+    }
+
+    // Invalid references are rejected before a request is sent.
+    mgrs.column = 'I';
+    let error = api.reverse_search(mgrs, None).unwrap_err();
+    assert!(error.is::<InvalidInputError>());
+    assert_eq!(requests.lock().unwrap().len(), 2);
+}
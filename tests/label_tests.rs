@@ -0,0 +1,107 @@
+mod common;
+
+use photon_geocoding::label::{AddressFormatter, AddressTemplate};
+use photon_geocoding::PhotonFeature;
+
+use common::{feature, munich_house};
+
+#[test]
+fn formats_german_addresses() {
+    let house = PhotonFeature {
+        name: Some(String::from("Neues Rathaus")),
+        ..munich_house()
+    };
+    let formatter = AddressFormatter::new();
+
+    assert_eq!(
+        formatter.one_line(&house),
+        "Neues Rathaus, Marienplatz 8, 80331 München, Deutschland"
+    );
+    assert_eq!(
+        formatter.multi_line(&house),
+        "Neues Rathaus\nMarienplatz 8\n80331 München\nDeutschland"
+    );
+}
+
+#[test]
+fn formats_by_country_code() {
+    let us = PhotonFeature {
+        street: Some(String::from("Pennsylvania Avenue NW")),
+        house_number: Some(String::from("1600")),
+        city: Some(String::from("Washington")),
+        state: Some(String::from("District of Columbia")),
+        postcode: Some(String::from("20500")),
+        country: Some(String::from("United States")),
+        country_iso_code: Some(String::from("us")),
+        ..feature(38.8977, -77.0365)
+    };
+    let gb = PhotonFeature {
+        street: Some(String::from("Downing Street")),
+        house_number: Some(String::from("10")),
+        city: Some(String::from("London")),
+        postcode: Some(String::from("SW1A 2AA")),
+        country_iso_code: Some(String::from("GB")),
+        ..feature(51.5034, -0.1276)
+    };
+
+    assert_eq!(
+        us.label(),
+        "1600 Pennsylvania Avenue NW, Washington, District of Columbia 20500, United States"
+    );
+    assert_eq!(gb.label(), "10 Downing Street, London, SW1A 2AA");
+}
+
+#[test]
+fn drops_repeated_and_missing_parts() {
+    let street = PhotonFeature {
+        name: Some(String::from("Marienplatz")),
+        house_number: None,
+        postcode: None,
+        ..munich_house()
+    };
+    let berlin = PhotonFeature {
+        name: Some(String::from("Berlin")),
+        city: Some(String::from("Berlin")),
+        state: Some(String::from("Berlin")),
+        country: Some(String::from("Deutschland")),
+        ..feature(52.52, 13.405)
+    };
+
+    assert_eq!(street.label(), "Marienplatz, München, Deutschland");
+    assert_eq!(berlin.label(), "Berlin, Deutschland");
+}
+
+#[test]
+fn uses_custom_templates() {
+    let template = AddressTemplate::parse("{city} ({postcode})\n{street} {house_number}").unwrap();
+    let formatter = AddressFormatter::new().template("de", template);
+
+    assert_eq!(
+        formatter.one_line(&munich_house()),
+        "München (80331), Marienplatz 8"
+    );
+    let without_postcode = PhotonFeature {
+        postcode: None,
+        ..munich_house()
+    };
+    assert_eq!(
+        formatter.one_line(&without_postcode),
+        "München, Marienplatz 8"
+    );
+
+    let template = AddressTemplate::parse("{street} [{house_number}] - {city}!").unwrap();
+    let formatter = AddressFormatter::new().default_template(template);
+    let street = PhotonFeature {
+        house_number: None,
+        city: None,
+        ..munich_house()
+    };
+    assert_eq!(
+        formatter.one_line(&munich_house()),
+        "Marienplatz [8] - München!"
+    );
+    assert_eq!(formatter.one_line(&street), "Marienplatz");
+
+    assert!(AddressTemplate::parse("{town}").is_err());
+    assert!(AddressTemplate::parse("{city").is_err());
+}
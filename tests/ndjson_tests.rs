@@ -0,0 +1,166 @@
+use std::io::{self, Write};
+
+use photon_geocoding::batch::BatchOptions;
+use photon_geocoding::error::InvalidInputError;
+use photon_geocoding::filter::ForwardFilter;
+use photon_geocoding::{LatLon, PhotonApiClient};
+use serde_json::Value;
+
+// Nothing listens on this port, so any request that is actually sent fails with a transport error.
+const UNREACHABLE: &str = "http://127.0.0.1:9";
+
+fn geocode(input: &str, options: Option<BatchOptions>) -> Vec<Value> {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let mut output = Vec::new();
+    api.geocode_ndjson(input.as_bytes(), &mut output, options)
+        .unwrap();
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn error_kind(result: &Value) -> &str {
+    result["error"]["kind"].as_str().unwrap()
+}
+
+#[test]
+fn writes_one_result_per_query() {
+    let input = r#"{"id": "a", "query": "Marienplatz 8, München"}
+
+{"id": 7, "street": "Unter den Linden 1", "city": "Berlin", "lang": "de"}
+{"query": ""}
+not json
+"#;
+    let results = geocode(input, None);
+
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0]["line"], 1);
+    assert_eq!(results[0]["id"], "a");
+    assert_eq!(results[0]["query"], "Marienplatz 8, München");
+    assert!(results[0]["feature"].is_null());
+    assert_eq!(error_kind(&results[0]), "transport");
+
+    assert_eq!(results[1]["line"], 3);
+    assert_eq!(results[1]["id"], 7);
+    assert_eq!(results[1]["query"], "Unter den Linden 1, Berlin");
+    assert_eq!(error_kind(&results[1]), "transport");
+
+    assert_eq!(results[2]["line"], 4);
+    assert!(results[2].get("id").is_none());
+    assert_eq!(error_kind(&results[2]), "invalid_input");
+
+    assert_eq!(results[3]["line"], 5);
+    assert_eq!(error_kind(&results[3]), "invalid_input");
+    assert!(results[3]["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("line 5 is not a valid query"));
+}
+
+#[test]
+fn rejects_invalid_queries() {
+    let input = r#"{"query": "Berlin", "city": "Berlin"}
+{"query": "Berlin", "layer": ["moon"]}
+{"query": "Berlin", "bias": {"lat": 95, "lon": 0}}
+{"query": "Berlin", "bbox": [13.0, 52.0, 14.0]}
+{"query": "Berlin", "housenumber": "1"}
+"#;
+    let results = geocode(input, None);
+
+    assert_eq!(results.len(), 5);
+    for result in &results {
+        assert_eq!(error_kind(result), "invalid_input", "{}", result);
+    }
+    assert!(results[0]["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("both a free-text query and address fields"));
+    assert!(results[1]["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("unknown layer 'moon'"));
+}
+
+#[test]
+fn keeps_input_order_with_threads() {
+    let input: String = (0..50)
+        .map(|i| {
+            if i % 5 == 0 {
+                format!("{{\"id\": {}, \"query\": \" \"}}\n", i)
+            } else {
+                format!("{{\"id\": {}, \"query\": \"Berlin\"}}\n", i)
+            }
+        })
+        .collect();
+    let options = BatchOptions::new().threads(4);
+    let results = geocode(&input, Some(options));
+
+    assert_eq!(results.len(), 50);
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(result["id"], i);
+        assert_eq!(result["line"], i + 1);
+        let expected = if i % 5 == 0 {
+            "invalid_input"
+        } else {
+            "transport"
+        };
+        assert_eq!(error_kind(result), expected);
+    }
+}
+
+#[test]
+fn counts_queries() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let input = "{\"query\": \"Berlin\"}\n\n{\"query\": \"\"}\n";
+    let stats = api
+        .geocode_ndjson(input.as_bytes(), io::sink(), None)
+        .unwrap();
+    assert_eq!(stats.queries, 2);
+    assert_eq!(stats.matched, 0);
+    assert_eq!(stats.failed, 2);
+}
+
+/// A writer that fails after a number of lines.
+struct FailingWriter {
+    lines: usize,
+}
+
+impl Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.lines == 0 {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"));
+        }
+        self.lines -= buf.iter().filter(|&&b| b == b'\n').count().min(self.lines);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn stops_on_write_errors() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let input = "{\"query\": \"\"}\n".repeat(100);
+    for threads in [1, 3] {
+        let options = BatchOptions::new().threads(threads);
+        let error = api
+            .geocode_ndjson(input.as_bytes(), FailingWriter { lines: 5 }, Some(options))
+            .unwrap_err();
+        assert!(error.downcast_ref::<io::Error>().is_some());
+    }
+}
+
+#[test]
+fn rejects_invalid_filter() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let filter = ForwardFilter::new().location_bias(LatLon::new(95.0, 0.0), None, None);
+    let options = BatchOptions::new().filter(filter);
+    let error = api
+        .geocode_ndjson("".as_bytes(), io::sink(), Some(options))
+        .unwrap_err();
+    assert!(error.downcast_ref::<InvalidInputError>().is_some());
+}
@@ -0,0 +1,76 @@
+use photon_geocoding::parse::{is_coordinate, parse_coordinates};
+use photon_geocoding::LatLon;
+
+fn assert_parses(input: &str, lat: f64, lon: f64) {
+    let coords = parse_coordinates(input).unwrap_or_else(|e| panic!("{}: {}", input, e));
+    assert!(
+        (coords.lat - lat).abs() < 1e-4 && (coords.lon - lon).abs() < 1e-4,
+        "{} parsed as {:?}",
+        input,
+        coords
+    );
+}
+
+#[test]
+fn parses_decimal_degrees() {
+    assert_parses("48.137, 11.575", 48.137, 11.575);
+    assert_parses("48.137 11.575", 48.137, 11.575);
+    assert_parses("-33.8568;151.2153", -33.8568, 151.2153);
+    assert_parses("48.137° N, 11.575° E", 48.137, 11.575);
+    assert_parses("33.8568S 151.2153E", -33.8568, 151.2153);
+    assert_parses("E 11.575 N 48.137", 48.137, 11.575);
+}
+
+#[test]
+fn parses_degrees_minutes_seconds() {
+    assert_parses("48°08'14\"N 11°34'31\"E", 48.137222, 11.575278);
+    assert_parses("48° 8′ 14″ N, 11° 34′ 31″ E", 48.137222, 11.575278);
+    assert_parses("40°26'46''N 79°58'56''W", 40.446111, -79.982222);
+    assert_parses("48 08 14 N 11 34 31 E", 48.137222, 11.575278);
+}
+
+#[test]
+fn parses_degrees_decimal_minutes() {
+    assert_parses("N 48° 08.233 E 011° 34.517", 48.137217, 11.575283);
+    assert_parses("48°08.233'N 11°34.517'E", 48.137217, 11.575283);
+}
+
+#[test]
+fn parses_geo_uris() {
+    assert_parses("geo:48.13,11.57", 48.13, 11.57);
+    assert_parses("GEO:48.13,11.57,520;u=35", 48.13, 11.57);
+    assert_eq!(
+        "geo:48.13,11.57".parse::<LatLon>(),
+        Ok(LatLon::new(48.13, 11.57))
+    );
+}
+
+#[test]
+fn rejects_text_and_invalid_values() {
+    for input in [
+        "munich",
+        "Hauptstraße 12, 80331 München",
+        "10115 Berlin",
+        "12",
+        "1 2 3",
+        "95.0, 11.0",
+        "48°75'N 11°E",
+        "-48.1S 11.5E",
+        "48.1N 11.5N",
+        "geo:48.13",
+        "7 11",
+        "10 20",
+        "-7 11",
+    ] {
+        assert!(!is_coordinate(input), "{} was parsed", input);
+    }
+}
+
+#[test]
+fn accepts_coordinates_with_markers() {
+    for input in ["48.137 11.575", "48, 11", "48° 11°", "48N 11E", "geo:48,11"] {
+        assert!(is_coordinate(input), "{} was not accepted", input);
+    }
+    // Bare integers still parse when a coordinate is expected.
+    assert_eq!(parse_coordinates("10 20"), Ok(LatLon::new(10.0, 20.0)));
+}
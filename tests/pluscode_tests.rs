@@ -0,0 +1,118 @@
+use photon_geocoding::pluscode::{self, decode, encode, is_full, is_short, is_valid};
+use photon_geocoding::LatLon;
+
+#[test]
+fn encodes_reference_vectors() {
+    assert_eq!(encode(&LatLon::new(20.375, 2.775), 6).unwrap(), "7FG49Q00+");
+    assert_eq!(
+        encode(&LatLon::new(20.3700625, 2.7821875), 10).unwrap(),
+        "7FG49QCJ+2V"
+    );
+    assert_eq!(
+        encode(&LatLon::new(20.3701125, 2.782234375), 11).unwrap(),
+        "7FG49QCJ+2VX"
+    );
+    assert_eq!(
+        encode(&LatLon::new(47.0000625, 8.0000625), 10).unwrap(),
+        "8FVC2222+22"
+    );
+    assert_eq!(encode(&LatLon::new(90.0, 1.0), 4).unwrap(), "CFX30000+");
+    assert!(encode(&LatLon::new(20.0, 2.0), 7).is_err());
+}
+
+#[test]
+fn decodes_reference_vectors() {
+    let area = decode("7FG49QCJ+2V").unwrap();
+
+    assert_eq!(area.code_length, 10);
+    assert_eq!(area.bounds.south_west, LatLon::new(20.37, 2.782125));
+    assert_eq!(area.bounds.north_east, LatLon::new(20.370125, 2.78225));
+    assert_eq!(
+        LatLon::from_plus_code("7fg49qcj+2v").unwrap(),
+        area.center()
+    );
+}
+
+#[test]
+fn validates_codes() {
+    assert!(is_full("8FWC2345+G6"));
+    assert!(is_full("8FWC2300+"));
+    assert!(is_short("WC2345+G6"));
+    assert!(!is_full("WC2345+G6"));
+    for invalid in [
+        "8FWC2345+G",
+        "8FWC2_45+G6",
+        "8FWC2345G6+",
+        "8FWC2300+G6",
+        "8F0C2345+",
+        "G+",
+    ] {
+        assert!(!is_valid(invalid), "{} is valid", invalid);
+    }
+}
+
+#[test]
+fn recovers_short_codes_near_reference() {
+    let mountain_view = LatLon::new(37.4, -122.1);
+
+    assert_eq!(
+        pluscode::recover_nearest("CWC8+R9", &mountain_view).unwrap(),
+        "849VCWC8+R9"
+    );
+    assert_eq!(
+        pluscode::recover_nearest("+2VX", &LatLon::new(51.3701125, -1.217765625)).unwrap(),
+        "9C3W9QCJ+2VX"
+    );
+    assert!(pluscode::recover_nearest("CWC8+R9", &LatLon::new(f64::NAN, 0.0)).is_err());
+}
+
+#[test]
+fn roundtrips_coordinates() {
+    let munich = LatLon::new(48.137, 11.575);
+    let code = munich.to_plus_code().unwrap();
+    let decoded = LatLon::from_plus_code(&code).unwrap();
+
+    assert_eq!(code, "8FWH4HPG+R2");
+    assert!(munich.haversine_distance(&decoded) < 10.0);
+}
+
+#[test]
+fn resolves_full_codes_without_request() {
+    // Nothing listens on this port, so the test fails if a request is sent.
+    let api = photon_geocoding::PhotonApiClient::new("http://127.0.0.1:9");
+
+    assert_eq!(
+        api.resolve_plus_code("8FWH4HPG+R2").unwrap(),
+        LatLon::from_plus_code("8FWH4HPG+R2").unwrap()
+    );
+    assert!(api.resolve_plus_code("4HPG+R2").is_err());
+}
+
+#[test]
+fn finds_codes_followed_by_separators() {
+    // Nothing listens on this port, so the locality lookup fails with a transport error.
+    let api = photon_geocoding::PhotonApiClient::new("http://127.0.0.1:9");
+
+    assert_eq!(
+        api.resolve_plus_code("8FWH4HPG+R2, München").unwrap(),
+        LatLon::from_plus_code("8FWH4HPG+R2").unwrap()
+    );
+    assert_eq!(
+        api.resolve_plus_code("8FWH4HPG+R2; München").unwrap(),
+        LatLon::from_plus_code("8FWH4HPG+R2").unwrap()
+    );
+
+    // The short code is found, so the locality is looked up instead of the input being rejected.
+    let error = api.resolve_plus_code("4HPG+R2, München").unwrap_err();
+    assert!(
+        !error.to_string().contains("contains no Plus Code"),
+        "{}",
+        error
+    );
+    assert!(error.is::<ureq::Error>(), "{}", error);
+    let error = api.resolve_plus_code("4HPG+R2,").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid input: short Plus Code '4HPG+R2' needs a reference locality"
+    );
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use photon_geocoding::scoring::{self, AddressField, MatchQuery};
+use photon_geocoding::{PhotonFeature, StructuredAddress};
+
+fn munich_street() -> PhotonFeature {
+    PhotonFeature {
+        r#type: String::from("street"),
+        name: Some(String::from("Marienplatz")),
+        house_number: None,
+        street: None,
+        ..common::munich_house()
+    }
+}
+
+fn munich_city() -> PhotonFeature {
+    PhotonFeature {
+        r#type: String::from("city"),
+        name: Some(String::from("München")),
+        house_number: None,
+        street: None,
+        ..common::munich_house()
+    }
+}
+
+#[test]
+fn exact_structured_match_is_confident() {
+    let query = StructuredAddress::new()
+        .street("Marienplatz")
+        .house_number("8")
+        .postcode("80331")
+        .city("München");
+    let score = common::munich_house().match_score(query);
+    assert!((score.confidence - 1.0).abs() < 1e-9);
+    assert_eq!(score.fields.len(), 4);
+    assert!(score
+        .fields
+        .iter()
+        .all(|m| (m.similarity - 1.0).abs() < 1e-9));
+}
+
+#[test]
+fn normalisation_ignores_case_diacritics_and_abbreviations() {
+    let feature = PhotonFeature {
+        street: Some(String::from("Hauptstraße")),
+        ..common::munich_house()
+    };
+    let query = StructuredAddress::new()
+        .street("hauptstr.")
+        .city("MUENCHEN");
+    let score = feature.match_score(query);
+    let street = score.field(AddressField::Street).unwrap();
+    assert!((street.similarity - 1.0).abs() < 1e-9);
+    assert_eq!(street.feature.as_deref(), Some("Hauptstraße"));
+    assert!(score.field(AddressField::City).unwrap().similarity > 0.9);
+}
+
+#[test]
+fn house_number_and_postcode_must_agree() {
+    let feature = common::munich_house();
+    let wrong_number = feature.match_score(StructuredAddress::new().house_number("18"));
+    assert_eq!(wrong_number.confidence, 0.0);
+    let suffix = feature.match_score(StructuredAddress::new().house_number("8a"));
+    assert!(suffix.confidence > 0.5 && suffix.confidence < 1.0);
+
+    let neighbour = feature.match_score(StructuredAddress::new().postcode("80333"));
+    let postcode = neighbour.field(AddressField::Postcode).unwrap();
+    assert!(postcode.similarity > 0.0 && postcode.similarity < 0.5);
+}
+
+#[test]
+fn missing_fields_do_not_match() {
+    let feature = common::feature(48.1, 11.5);
+    let score = feature.match_score(StructuredAddress::new().street("Marienplatz"));
+    let street = score.field(AddressField::Street).unwrap();
+    assert_eq!(street.feature, None);
+    assert_eq!(street.similarity, 0.0);
+}
+
+#[test]
+fn coarser_results_are_penalised() {
+    let query = StructuredAddress::new()
+        .street("Marienplatz")
+        .house_number("8")
+        .city("München");
+    let house = common::munich_house().match_score(&query);
+    let street = munich_street().match_score(&query);
+    let city = munich_city().match_score(&query);
+    assert_eq!(house.granularity, 1.0);
+    assert!(street.granularity < 1.0);
+    assert!(city.granularity < street.granularity);
+    assert!(house.confidence > street.confidence);
+    assert!(street.confidence > city.confidence);
+
+    // Without a house number a street is as fine as it gets.
+    let street_query = StructuredAddress::new()
+        .street("Marienplatz")
+        .city("München");
+    assert_eq!(munich_street().match_score(street_query).granularity, 1.0);
+}
+
+#[test]
+fn country_matches_name_or_iso_code() {
+    let feature = common::munich_house();
+    for country in ["DE", "de", "Deutschland"] {
+        let score = feature.match_score(StructuredAddress::new().country(country));
+        assert!((score.confidence - 1.0).abs() < 1e-9, "{}", country);
+    }
+    assert!(
+        feature
+            .match_score(StructuredAddress::new().country("AT"))
+            .confidence
+            < 0.5
+    );
+}
+
+#[test]
+fn free_text_query() {
+    let feature = common::munich_house();
+    let exact = feature.match_score("Marienplatz 8, 80331 München");
+    assert!((exact.confidence - 1.0).abs() < 1e-9);
+    assert_eq!(
+        exact.field(AddressField::Street).unwrap().query,
+        "marienplatz"
+    );
+    assert_eq!(
+        exact.field(AddressField::HouseNumber).unwrap().similarity,
+        1.0
+    );
+
+    let partial = feature.match_score("Marienplatz 8, Berlin");
+    assert!(partial.confidence < exact.confidence);
+    assert!(partial.confidence > 0.5);
+
+    let street = munich_street().match_score("Marienplatz 8 München");
+    assert!(street.granularity < 1.0);
+    assert_eq!(
+        munich_street()
+            .match_score("Marienplatz München")
+            .granularity,
+        1.0
+    );
+}
+
+#[test]
+fn empty_queries_have_no_confidence() {
+    let feature = common::munich_house();
+    assert_eq!(feature.match_score("").confidence, 0.0);
+    assert_eq!(
+        feature.match_score(StructuredAddress::new()).confidence,
+        0.0
+    );
+}
+
+#[test]
+fn rank_sorts_by_confidence() {
+    let query = MatchQuery::from("Marienplatz 8 München");
+    let ranked = scoring::rank(
+        &query,
+        vec![munich_city(), common::munich_house(), munich_street()],
+    );
+    let types: Vec<&str> = ranked.iter().map(|(f, _)| f.r#type.as_str()).collect();
+    assert_eq!(types, ["house", "street", "city"]);
+    assert!(ranked
+        .windows(2)
+        .all(|pair| pair[0].1.confidence >= pair[1].1.confidence));
+}
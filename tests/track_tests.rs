@@ -0,0 +1,127 @@
+mod common;
+
+use photon_geocoding::error::InvalidInputError;
+use photon_geocoding::track::{self, TrackOptions, TrackSegment};
+use photon_geocoding::{LatLon, PhotonApiClient, PhotonFeature};
+
+// Nothing listens on this port, so any request that is actually sent fails with a transport error.
+const UNREACHABLE: &str = "http://127.0.0.1:9";
+
+/// `count` points 10 meters apart, heading east and then, after `turn_after` points, north.
+fn l_shaped_track(count: usize, turn_after: usize) -> Vec<LatLon> {
+    let mut points = vec![LatLon::new(48.1372, 11.5755)];
+    for i in 1..count {
+        let bearing = if i <= turn_after { 90.0 } else { 0.0 };
+        points.push(points[i - 1].destination(bearing, 10.0));
+    }
+    points
+}
+
+fn on_street(street: &str, city: &str) -> Option<PhotonFeature> {
+    Some(PhotonFeature {
+        street: Some(String::from(street)),
+        city: Some(String::from(city)),
+        ..common::feature(48.0, 11.0)
+    })
+}
+
+#[test]
+fn samples_straight_track_by_distance() {
+    let track = l_shaped_track(101, 100);
+    let samples = track::sample(&track, &TrackOptions::new());
+    // One kilometer straight ahead: every 250 meters plus the first point.
+    assert_eq!(samples, [0, 25, 50, 75, 100]);
+}
+
+#[test]
+fn samples_turns() {
+    let track = l_shaped_track(21, 10);
+    let samples = track::sample(&track, &TrackOptions::new());
+    assert_eq!(samples, [0, 11, 20]);
+
+    // Turns before the minimum distance are ignored.
+    let options = TrackOptions::new().min_distance(200.0);
+    assert_eq!(track::sample(&track, &options), [0, 20]);
+
+    assert!(track::sample(&[], &TrackOptions::new()).is_empty());
+    assert_eq!(track::sample(&track[..1], &TrackOptions::new()), [0]);
+}
+
+#[test]
+fn compresses_segments() {
+    let track = l_shaped_track(10, 10);
+    let samples = vec![
+        (0, on_street("Marienplatz", "München")),
+        (3, on_street("Marienplatz", "München")),
+        (5, on_street("Kaufingerstraße", "München")),
+        (8, None),
+    ];
+    let segments = track::segments(&track, &samples);
+    assert_eq!(segments.len(), 3);
+    assert_eq!(
+        segments[0],
+        TrackSegment {
+            points: 0..5,
+            street: Some(String::from("Marienplatz")),
+            city: Some(String::from("München")),
+            distance: segments[0].distance,
+        }
+    );
+    assert!((segments[0].distance - 50.0).abs() < 0.1);
+    assert_eq!(segments[1].points, 5..8);
+    assert_eq!(segments[2].points, 8..10);
+    assert_eq!(segments[2].street, None);
+    let total: f64 = segments.iter().map(|segment| segment.distance).sum();
+    assert!((total - 90.0).abs() < 0.1);
+}
+
+#[test]
+fn street_features_name_the_street() {
+    let track = l_shaped_track(3, 3);
+    let street = PhotonFeature {
+        r#type: String::from("street"),
+        name: Some(String::from("Sendlinger Straße")),
+        city: Some(String::from("München")),
+        ..common::feature(48.0, 11.0)
+    };
+    let segments = track::segments(&track, &[(0, Some(street))]);
+    assert_eq!(segments[0].street.as_deref(), Some("Sendlinger Straße"));
+}
+
+#[test]
+fn parses_gpx() {
+    let gpx = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test">
+  <metadata><name>Ride</name></metadata>
+  <trk><name>Ride</name><trkseg>
+    <trkpt lat="48.1372" lon="11.5755"><ele>520</ele></trkpt>
+    <trkpt lon='11.5765' lat='48.1373'/>
+  </trkseg></trk>
+</gpx>"#;
+    let points = track::parse_gpx(gpx).unwrap();
+    assert_eq!(
+        points,
+        [LatLon::new(48.1372, 11.5755), LatLon::new(48.1373, 11.5765)]
+    );
+
+    let route = r#"<gpx><rte><rtept lat="1" lon="2"></rtept></rte></gpx>"#;
+    assert_eq!(track::parse_gpx(route).unwrap(), [LatLon::new(1.0, 2.0)]);
+
+    assert!(track::parse_gpx(r#"<gpx><trkpt lat="95" lon="2"/></gpx>"#).is_err());
+    assert!(track::parse_gpx(r#"<gpx><trkpt lat="1"/></gpx>"#).is_err());
+    assert!(track::read_gpx("does/not/exist.gpx").is_err());
+}
+
+#[test]
+fn invalid_input_is_rejected_before_request() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+    let track = l_shaped_track(5, 5);
+
+    let options = TrackOptions::new().min_distance(100.0).max_distance(50.0);
+    let invalid_options = api.annotate_track(&track, Some(options));
+    let invalid_point = api.annotate_track(&[LatLon::new(f64::NAN, 0.0)], None);
+
+    assert!(invalid_options.unwrap_err().is::<InvalidInputError>());
+    assert!(invalid_point.unwrap_err().is::<InvalidInputError>());
+    assert!(api.annotate_track(&[], None).unwrap().is_empty());
+}
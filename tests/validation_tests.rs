@@ -0,0 +1,58 @@
+use photon_geocoding::error::InvalidInputError;
+use photon_geocoding::filter::{ForwardFilter, ReverseFilter};
+use photon_geocoding::{
+    BoundingBox, LatLon, LongitudeHandling, PhotonApiClient, StructuredAddress,
+};
+
+// Nothing listens on this port, so any request that is actually sent fails with a transport error.
+const UNREACHABLE: &str = "http://127.0.0.1:9";
+
+#[test]
+fn try_new_rejects_invalid_values() {
+    assert!(LatLon::try_new(48.1, 11.5).is_ok());
+    assert!(LatLon::try_new(f64::NAN, 11.5).is_err());
+    assert!(LatLon::try_new(48.1, f64::INFINITY).is_err());
+    assert!(LatLon::try_new(90.5, 11.5).is_err());
+    assert!(LatLon::try_new(48.1, 180.5).is_err());
+}
+
+#[test]
+fn try_new_with_wraps_or_clamps_longitude() {
+    let wrapped = LatLon::try_new_with(10.0, 190.0, LongitudeHandling::Wrap).unwrap();
+    let clamped = LatLon::try_new_with(10.0, 190.0, LongitudeHandling::Clamp).unwrap();
+
+    assert_eq!(wrapped, LatLon::new(10.0, -170.0));
+    assert_eq!(clamped, LatLon::new(10.0, 180.0));
+    assert!(LatLon::try_new_with(95.0, 0.0, LongitudeHandling::Wrap).is_err());
+    assert!(LatLon::try_new_with(0.0, f64::NAN, LongitudeHandling::Clamp).is_err());
+}
+
+#[test]
+fn filters_reject_invalid_options() {
+    let bias = ForwardFilter::new().location_bias(LatLon::new(48.1, 11.5), Some(30), None);
+    let scale = ForwardFilter::new().location_bias(LatLon::new(48.1, 11.5), None, Some(1.5));
+    let bbox = ForwardFilter::new().bounding_box(BoundingBox {
+        south_west: LatLon::new(50.0, 10.0),
+        north_east: LatLon::new(40.0, 12.0),
+    });
+
+    assert!(bias.validate().is_err());
+    assert!(scale.validate().is_err());
+    assert!(bbox.validate().is_err());
+    assert!(ForwardFilter::new().limit(0).validate().is_err());
+    assert!(ReverseFilter::new().radius(0).validate().is_err());
+    assert!(ReverseFilter::new().radius(1).limit(5).validate().is_ok());
+}
+
+#[test]
+fn invalid_input_is_rejected_before_request() {
+    let api = PhotonApiClient::new(UNREACHABLE);
+
+    let reverse = api.reverse_search(LatLon::new(f64::NAN, 0.0), None);
+    let forward = api.forward_search("munich", Some(ForwardFilter::new().limit(0)));
+    let structured = api.structured_search(&StructuredAddress::new().name("Museum"), None);
+
+    assert!(reverse.unwrap_err().is::<InvalidInputError>());
+    assert!(forward.unwrap_err().is::<InvalidInputError>());
+    assert!(structured.unwrap_err().is::<InvalidInputError>());
+}
@@ -0,0 +1,173 @@
+use photon_geocoding::{BoundingBox, LatLon};
+
+mod common;
+
+fn munich() -> BoundingBox {
+    BoundingBox {
+        south_west: LatLon::new(48.06, 11.36),
+        north_east: LatLon::new(48.25, 11.72),
+    }
+}
+
+fn fiji() -> BoundingBox {
+    BoundingBox {
+        south_west: LatLon::new(-21.0, 176.5),
+        north_east: LatLon::new(-12.0, -178.0),
+    }
+}
+
+#[test]
+fn writes_points() {
+    let coords = LatLon::new(48.137, 11.575);
+    assert_eq!(coords.to_wkt(), "POINT(11.575 48.137)");
+    assert_eq!(coords.to_ewkt(), "SRID=4326;POINT(11.575 48.137)");
+    assert_eq!(LatLon::new(-33.0, -70.5).to_wkt(), "POINT(-70.5 -33)");
+}
+
+#[test]
+fn parses_points() {
+    let coords = LatLon::new(48.137, 11.575);
+    assert_eq!(LatLon::from_wkt("POINT(11.575 48.137)"), Ok(coords));
+    assert_eq!(LatLon::from_wkt("  point ( 11.575   48.137 ) "), Ok(coords));
+    assert_eq!(LatLon::from_wkt("POINT(1.1575e1 48.137)"), Ok(coords));
+    assert_eq!(
+        LatLon::from_ewkt("SRID=4326;POINT(11.575 48.137)"),
+        Ok(coords)
+    );
+    assert_eq!(LatLon::from_ewkt("POINT(11.575 48.137)"), Ok(coords));
+    assert_eq!(LatLon::from_wkt(&coords.to_wkt()), Ok(coords));
+}
+
+#[test]
+fn writes_bounding_boxes() {
+    assert_eq!(
+        munich().to_wkt(),
+        "POLYGON((11.36 48.06, 11.72 48.06, 11.72 48.25, 11.36 48.25, 11.36 48.06))"
+    );
+    assert_eq!(
+        munich().to_ewkt(),
+        "SRID=4326;POLYGON((11.36 48.06, 11.72 48.06, 11.72 48.25, 11.36 48.25, 11.36 48.06))"
+    );
+    assert_eq!(
+        munich().to_envelope_wkt(),
+        "ENVELOPE(11.36, 11.72, 48.25, 48.06)"
+    );
+    assert_eq!(
+        fiji().to_wkt(),
+        "MULTIPOLYGON(((176.5 -21, 180 -21, 180 -12, 176.5 -12, 176.5 -21)),\
+         ((-180 -21, -178 -21, -178 -12, -180 -12, -180 -21)))"
+    );
+    assert_eq!(fiji().to_envelope_wkt(), "ENVELOPE(176.5, -178, -12, -21)");
+}
+
+#[test]
+fn parses_bounding_boxes() {
+    for bbox in [munich(), fiji()] {
+        assert_eq!(BoundingBox::from_wkt(&bbox.to_wkt()), Ok(bbox));
+        assert_eq!(BoundingBox::from_ewkt(&bbox.to_ewkt()), Ok(bbox));
+        assert_eq!(BoundingBox::from_wkt(&bbox.to_envelope_wkt()), Ok(bbox));
+    }
+    // Clockwise, starting at another corner, without the space after commas.
+    assert_eq!(
+        BoundingBox::from_wkt(
+            "POLYGON((11.72 48.25,11.72 48.06,11.36 48.06,11.36 48.25,11.72 48.25))"
+        ),
+        Ok(munich())
+    );
+    // The halves of a box across the antimeridian in the other order.
+    assert_eq!(
+        BoundingBox::from_wkt(
+            "MULTIPOLYGON(((-180 -21, -178 -21, -178 -12, -180 -12, -180 -21)),\
+             ((176.5 -21, 180 -21, 180 -12, 176.5 -12, 176.5 -21)))"
+        ),
+        Ok(fiji())
+    );
+}
+
+#[test]
+fn rejects_invalid_wkt() {
+    let error = LatLon::from_wkt("POINT(11.575)").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid input: 'POINT(11.575)' is not valid WKT: only positions with two coordinates are \
+         supported"
+    );
+    for wkt in [
+        "",
+        "POINT",
+        "POINT EMPTY",
+        "POINT(11.575 48.137",
+        "POINT(11.575 48.137) x",
+        "POINT(a b)",
+        "POINT(1 2, 3 4)",
+        "POINT Z (1 2 3)",
+        "LINESTRING(1 2, 3 4)",
+        "SRID=4326;POINT(11.575 48.137)",
+    ] {
+        assert!(LatLon::from_wkt(wkt).is_err(), "{}", wkt);
+    }
+    // Out of range.
+    assert!(LatLon::from_wkt("POINT(48.137 110)").is_err());
+    // Nested deeper than any supported geometry, without running out of stack.
+    let nested = format!("POINT{}", "(".repeat(1_000_000));
+    assert!(LatLon::from_wkt(&nested)
+        .unwrap_err()
+        .to_string()
+        .ends_with("too many nested parentheses"));
+    assert!(BoundingBox::from_wkt("MULTIPOLYGON((((0 0, 1 0, 1 1, 0 1, 0 0))))").is_err());
+
+    for wkt in [
+        "POINT(1 2)",
+        "POLYGON((0 0, 1 0, 1 1, 0 1))",
+        "POLYGON((0 0, 1 0, 1 1, 0 1, 0 0), (0.2 0.2, 0.4 0.2, 0.4 0.4, 0.2 0.4, 0.2 0.2))",
+        "POLYGON((0 0, 2 0, 1 1, 0 1, 0 0))",
+        "POLYGON((0 0, 1 1, 1 0, 0 1, 0 0))",
+        "MULTIPOLYGON(((0 0, 1 0, 1 1, 0 1, 0 0)), ((5 0, 6 0, 6 1, 5 1, 5 0)))",
+        "ENVELOPE(1, 2, 3)",
+        "ENVELOPE(11.36, 11.72, 48.06, 48.25)",
+    ] {
+        assert!(BoundingBox::from_wkt(wkt).is_err(), "{}", wkt);
+    }
+}
+
+#[test]
+fn accepts_only_wgs84() {
+    let error = LatLon::from_ewkt("SRID=3857;POINT(1288555 6130800)").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid input: SRID 3857 is not supported, coordinates must be WGS 84 (SRID 4326)"
+    );
+    assert!(BoundingBox::from_ewkt("SRID=25832;ENVELOPE(1, 2, 4, 3)").is_err());
+    assert!(LatLon::from_ewkt("SRID=x;POINT(1 2)").is_err());
+    assert!(LatLon::from_ewkt("SRID=4326 POINT(1 2)").is_err());
+}
+
+#[test]
+fn round_trips_photon_extents() {
+    let munich = common::munich_city().extent.unwrap();
+    assert_eq!(
+        munich.to_wkt(),
+        "POLYGON((11.36 48.06, 11.72 48.06, 11.72 48.25, 11.36 48.25, 11.36 48.06))"
+    );
+
+    // Photon orders extents as [min_lon, max_lat, max_lon, min_lat].
+    let crossing = common::photon_feature(
+        r#"{
+            "geometry": {"coordinates": [178.0, -17.7], "type": "Point"},
+            "type": "Feature",
+            "properties": {
+                "osm_id": 571747, "osm_type": "R", "osm_key": "place", "osm_value": "country",
+                "type": "country", "extent": [176.5, -12.0, -178.0, -21.0], "name": "Fiji"
+            }
+        }"#,
+    )
+    .extent
+    .unwrap();
+    assert_eq!(crossing, fiji());
+
+    for extent in [munich, crossing] {
+        assert_eq!(BoundingBox::from_wkt(&extent.to_wkt()), Ok(extent));
+        assert_eq!(BoundingBox::from_ewkt(&extent.to_ewkt()), Ok(extent));
+        assert_eq!(BoundingBox::from_wkt(&extent.to_envelope_wkt()), Ok(extent));
+    }
+}